@@ -2,8 +2,13 @@ use criterion::{criterion_group, criterion_main, Criterion};
 
 use geo::{coord, line_string};
 use openrailwaymap_exporter::{
-    importer::overpass_importer::{find_next_existing_node, from_railway_elements, RailwayElement},
-    railway_algorithms::{PathFinding, RailwayEdgeAlgos, RailwayGraphAlgos},
+    importer::overpass_importer::{
+        find_next_existing_node, from_railway_elements, Coordinate, ElementType, RailwayElement,
+    },
+    railway_algorithms::{
+        DirectionalPathFinding, PathFinding, RailwayEdgeAlgos, RailwayGraphAlgos, ReversalOptions,
+        RoutingTable,
+    },
     railway_model::RailwayEdge,
 };
 use petgraph::stable_graph::NodeIndex;
@@ -17,6 +22,59 @@ fn railway_elements() -> Vec<RailwayElement> {
     RailwayElement::from_json(&test_data).unwrap()
 }
 
+/// Builds a main line of `length` nodes running east with a siding of `siding_length` nodes
+/// branching off northwards at every node.
+fn main_line_with_sidings(length: i64, siding_length: i64) -> Vec<RailwayElement> {
+    const SPACING: f64 = 0.001;
+    let node_id = |main: i64, siding: i64| main * (siding_length + 1) + siding + 1;
+    let mut elements = Vec::new();
+    let mut coordinates = HashMap::new();
+    for main in 0..length {
+        for siding in 0..=siding_length {
+            let id = node_id(main, siding);
+            let coordinate = Coordinate {
+                lat: 50.0 + siding as f64 * SPACING,
+                lon: 8.0 + main as f64 * SPACING,
+            };
+            coordinates.insert(id, coordinate.clone());
+            elements.push(RailwayElement {
+                id,
+                tags: None,
+                element_type: ElementType::Node,
+                nodes: None,
+                geometry: None,
+                lat: Some(coordinate.lat),
+                lon: Some(coordinate.lon),
+            });
+        }
+    }
+    let mut way_id = node_id(length, 0);
+    let mut add_way = |source: i64, target: i64| {
+        elements.push(RailwayElement {
+            id: way_id,
+            tags: None,
+            element_type: ElementType::Way,
+            nodes: Some(vec![source, target]),
+            geometry: Some(vec![
+                coordinates[&source].clone(),
+                coordinates[&target].clone(),
+            ]),
+            lat: None,
+            lon: None,
+        });
+        way_id += 1;
+    };
+    for main in 0..length {
+        if main + 1 < length {
+            add_way(node_id(main, 0), node_id(main + 1, 0));
+        }
+        for siding in 0..siding_length {
+            add_way(node_id(main, siding), node_id(main, siding + 1));
+        }
+    }
+    elements
+}
+
 fn benchmark_from_railway_elements(c: &mut Criterion) {
     let elements = railway_elements();
     c.bench_function("from_railway_elements", |b| {
@@ -58,6 +116,71 @@ fn shortest_path_edges_benchmark(c: &mut Criterion) {
     });
 }
 
+fn astar_vs_dijkstra_benchmark(c: &mut Criterion) {
+    let vilbel = from_railway_elements(&railway_elements());
+    let large = from_railway_elements(&main_line_with_sidings(1000, 20));
+    let queries = [
+        ("vilbel", &vilbel, 6204567489, 6204567501),
+        ("main_line_with_sidings", &large, 1, 999 * 21 + 1),
+    ];
+
+    let mut group = c.benchmark_group("astar_vs_dijkstra");
+    for (name, railway_graph, start, end) in queries {
+        group.bench_function(format!("{name}/astar_path_nodes"), |b| {
+            b.iter(|| {
+                assert!(railway_graph
+                    .astar_path_nodes(black_box(start), black_box(end))
+                    .is_some())
+            })
+        });
+        group.bench_function(format!("{name}/shortest_path_nodes"), |b| {
+            b.iter(|| {
+                assert!(railway_graph
+                    .shortest_path_nodes(black_box(start), black_box(end))
+                    .is_some())
+            })
+        });
+        group.bench_function(format!("{name}/shortest_path_distance"), |b| {
+            b.iter(|| {
+                assert!(railway_graph
+                    .shortest_path_distance(black_box(start), black_box(end))
+                    .is_some())
+            })
+        });
+    }
+    group.finish();
+}
+
+fn next_node_benchmark(c: &mut Criterion) {
+    let vilbel = from_railway_elements(&railway_elements());
+    let large = from_railway_elements(&main_line_with_sidings(1000, 20));
+    let options = ReversalOptions::default();
+    let queries = [
+        ("vilbel", &vilbel, 6204567489, 6204567501),
+        ("main_line_with_sidings", &large, 1, 999 * 21 + 1),
+    ];
+
+    let mut group = c.benchmark_group("next_node");
+    for (name, railway_graph, start, end) in queries {
+        group.bench_function(format!("{name}/next_directional_node"), |b| {
+            b.iter(|| {
+                assert!(railway_graph
+                    .next_directional_node(black_box(start), None, black_box(end), &options)
+                    .is_some())
+            })
+        });
+        let table = RoutingTable::new(railway_graph, &options);
+        group.bench_function(format!("{name}/routing_table"), |b| {
+            b.iter(|| {
+                assert!(table
+                    .next_node(black_box(start), None, black_box(end))
+                    .is_some())
+            })
+        });
+    }
+    group.finish();
+}
+
 fn reachable_nodes_benchmark(c: &mut Criterion) {
     let elements = railway_elements();
     let railway_graph = from_railway_elements(&elements);
@@ -85,18 +208,14 @@ fn update_position_benchmark(c: &mut Criterion) {
         target: 2,
     };
 
-    let current_location = coord! { x: 10.0, y: 20.0 };
+    let start = edge.start_position(1).unwrap();
     let distance_to_travel = Length::new::<meter>(25.0);
-    let direction_coord = coord! { x: 100.0, y: 100.0 };
 
     c.bench_function("update_position", |b| {
         b.iter(|| {
-            let updated_position = edge.position_on_edge(
-                black_box(current_location),
-                black_box(distance_to_travel),
-                black_box(direction_coord),
-            );
-            assert_ne!(updated_position, current_location);
+            let updated_position =
+                edge.position_on_edge(black_box(&start), black_box(distance_to_travel));
+            assert_ne!(updated_position, start);
         })
     });
 }
@@ -106,6 +225,8 @@ criterion_group!(
     benchmark_from_railway_elements,
     find_next_existing_node_benchmark,
     shortest_path_edges_benchmark,
+    astar_vs_dijkstra_benchmark,
+    next_node_benchmark,
     reachable_nodes_benchmark,
     update_position_benchmark
 );
@@ -1,5 +1,5 @@
 use crate::app3d::init_with_graph;
-use crate::prelude::RailwayGraph;
+use crate::prelude::{RailwayGraph, RailwayNode, RailwayTags};
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
 use yew::prelude::*;
@@ -11,10 +11,12 @@ pub struct BevyComponent {
 #[derive(PartialEq, Properties)]
 pub struct Props {
     pub graph: Option<RailwayGraph>,
+    pub tags: Option<RailwayTags>,
+    pub stations: Vec<RailwayNode>,
 }
 
-fn show_graph(graph: RailwayGraph) {
-    init_with_graph(graph);
+fn show_graph(graph: RailwayGraph, tags: RailwayTags, stations: Vec<RailwayNode>) {
+    init_with_graph(graph, tags, &stations);
 }
 
 pub enum Msg {}
@@ -36,8 +38,10 @@ impl Component for BevyComponent {
     fn rendered(&mut self, ctx: &Context<Self>, _: bool) {
         if self.window_loop.is_none() {
             if let Some(graph) = ctx.props().graph.clone() {
+                let tags = ctx.props().tags.clone().unwrap_or_default();
+                let stations = ctx.props().stations.clone();
                 let future = async move {
-                    show_graph(graph);
+                    show_graph(graph, tags, stations);
                     Ok(JsValue::null())
                 };
 
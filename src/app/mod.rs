@@ -2,16 +2,19 @@
 //!
 //! The `App` component is responsible for managing the state of the application, handling user input, and
 //! rendering the UI. The component uses the `RailwayApiClient` to fetch data from the Overpass API, and it
-//! displays the railway graph in either an SVG or a 3D view using the `Kiss3dComponent`.
+//! displays the railway graph in either an SVG or a 3D view using the `Kiss3dComponent`. Start and
+//! end nodes of a path are picked in the graph or by station name with the `StationSearch`.
 //!
 //! The `Msg` enum represents the different messages that can be sent to the `App` component to trigger
 //! state updates and UI changes.
 
 use crate::exporter::svg::SvgComponent;
-use crate::importer::overpass_importer::from_railway_elements;
-use crate::importer::overpass_importer::{count_node_elements, count_way_elements, RailwayElement};
-use crate::prelude::{OverpassApiClient, RailwayApiClient, RailwayGraph};
-use crate::railway_model::railway_graph::RailwayGraphExt;
+use crate::importer::overpass_importer::{
+    create_nodes, from_railway_elements, tags_from_railway_elements, RailwayElement,
+};
+use crate::prelude::{OverpassApiClient, RailwayApiClient, RailwayGraph, RailwayNode, RailwayTags};
+use crate::railway_algorithms::StationIndex;
+use crate::statistics::{network_statistics, NetworkStatistics};
 use crate::types::NodeId;
 use wasm_bindgen::prelude::*;
 use web_sys::EventTarget;
@@ -35,6 +38,9 @@ use kiss3d_component::Kiss3dComponent;
 mod path_display;
 pub use path_display::PathDisplay;
 
+mod station_search;
+pub use station_search::StationSearch;
+
 mod statistics;
 pub use statistics::Statistics;
 
@@ -43,11 +49,12 @@ pub struct App {
     link: Scope<Self>,
     input_area: String,
     loading: bool,
-    switch_count: u32,
-    track_count: u32,
-    total_length: f64,
+    statistics: NetworkStatistics,
     show_svg: bool,
     graph: Option<RailwayGraph>,
+    tags: Option<RailwayTags>,
+    station_nodes: Vec<RailwayNode>,
+    stations: Option<StationIndex>,
     selected_node_id: Option<NodeId>,
     start_node_id: Option<NodeId>,
     end_node_id: Option<NodeId>,
@@ -60,8 +67,8 @@ pub enum Msg {
     InputChanged(String),
     /// Button clicked.
     GetGraph,
-    /// Update Graph with loaded data.
-    GraphLoaded((Vec<RailwayElement>, RailwayGraph)),
+    /// Update Graph with loaded data and the stations fetched apart from the graph.
+    GraphLoaded((Vec<RailwayElement>, RailwayGraph, Vec<RailwayElement>)),
     /// Toggle between svg and 3d.
     ToggleView,
     /// Node selected
@@ -83,11 +90,12 @@ impl Component for App {
             link: _ctx.link().clone(),
             input_area: String::new(),
             loading: false,
-            switch_count: 0,
-            track_count: 0,
-            total_length: 0.0,
+            statistics: NetworkStatistics::default(),
             show_svg: true,
             graph: None,
+            tags: None,
+            station_nodes: Vec::new(),
+            stations: None,
             selected_node_id: None,
             start_node_id: None,
             end_node_id: None,
@@ -134,14 +142,34 @@ impl Component for App {
                     };
 
                     let graph = from_railway_elements(&railway_elements);
-                    link.send_message(Msg::GraphLoaded((railway_elements, graph)));
+
+                    // Without stations, nodes can still be picked in the graph.
+                    let stations_json = if area_name.contains(',') {
+                        client.fetch_stations_by_bbox(&area_name).await
+                    } else {
+                        client.fetch_stations_by_area_name(&area_name).await
+                    };
+                    let station_elements = stations_json
+                        .ok()
+                        .and_then(|value| RailwayElement::from_json(&value).ok())
+                        .unwrap_or_default();
+                    link.send_message(Msg::GraphLoaded((
+                        railway_elements,
+                        graph,
+                        station_elements,
+                    )));
                 });
             }
-            Msg::GraphLoaded((railway_elements, graph)) => {
-                self.switch_count = count_node_elements(&railway_elements) as u32;
-                self.track_count = count_way_elements(&railway_elements) as u32;
-                self.total_length = graph.total_length();
+            Msg::GraphLoaded((railway_elements, graph, station_elements)) => {
+                let mut tags = tags_from_railway_elements(&railway_elements);
+                self.statistics = network_statistics(&graph, &tags);
+                tags.nodes
+                    .extend(tags_from_railway_elements(&station_elements).nodes);
+                let stations = create_nodes(&station_elements);
+                self.stations = Some(StationIndex::new(&graph, &tags, &stations));
                 self.graph = Some(graph);
+                self.tags = Some(tags);
+                self.station_nodes = stations;
                 self.loading = false;
             }
             Msg::ToggleView => {
@@ -187,7 +215,8 @@ impl Component for App {
         } else {
             #[cfg(feature = "app3d")]
             html! {
-            <BevyComponent graph={self.graph.clone()} />
+            <BevyComponent graph={self.graph.clone()} tags={self.tags.clone()}
+                stations={self.station_nodes.clone()} />
             }
             #[cfg(not(feature = "app3d"))]
             html! {
@@ -220,7 +249,9 @@ impl Component for App {
                         { if self.show_svg { "Show 3D View" } else { "Show SVG" } }
                     </button>
                 </div>
-                <Statistics switches={self.switch_count} tracks={self.track_count} total_length={self.total_length} />
+                <Statistics statistics={self.statistics.clone()} />
+                <StationSearch stations={self.stations.clone()}
+                    on_from_here={on_select_start_node.clone()} on_to_here={on_select_end_node.clone()} />
                 <NodeContextMenu graph={self.graph.clone()} node_id={self.selected_node_id}
                     on_from_here={on_select_start_node} on_to_here={on_select_end_node} />
                 { loading_message }
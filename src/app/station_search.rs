@@ -0,0 +1,104 @@
+use crate::railway_algorithms::StationIndex;
+use crate::types::NodeId;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlInputElement};
+use yew::prelude::*;
+
+/// The number of matches listed below the search field.
+const MAX_MATCHES: usize = 5;
+
+/// The `StationSearch` component lets the user pick the start and end node of a path by typing
+/// the name of a station.
+pub struct StationSearch {
+    query: String,
+}
+
+pub enum Msg {
+    QueryChanged(String),
+    FromHere(NodeId),
+    ToHere(NodeId),
+}
+
+/// The `Props` struct represents the properties of the `StationSearch` component.
+#[derive(PartialEq, Properties, Clone)]
+pub struct Props {
+    /// The index of the station names of the displayed graph.
+    pub stations: Option<StationIndex>,
+    /// The `on_from_here` property is an optional callback that will be called with the track node of the station when its "Start here" button is clicked.
+    pub on_from_here: Option<Callback<NodeId>>,
+    /// The `on_to_here` property is an optional callback that will be called with the track node of the station when its "End here" button is clicked.
+    pub on_to_here: Option<Callback<NodeId>>,
+}
+
+impl Component for StationSearch {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        StationSearch {
+            query: String::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::QueryChanged(query) => {
+                self.query = query;
+                return true;
+            }
+            Msg::FromHere(node_id) => {
+                if let Some(on_from_here) = &ctx.props().on_from_here {
+                    on_from_here.emit(node_id);
+                }
+            }
+            Msg::ToHere(node_id) => {
+                if let Some(on_to_here) = &ctx.props().on_to_here {
+                    on_to_here.emit(node_id);
+                }
+            }
+        }
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let Some(stations) = ctx.props().stations.as_ref() else {
+            return html! { <></> };
+        };
+        let on_input = ctx.link().callback(|e: InputEvent| {
+            let target: EventTarget = e
+                .target()
+                .expect("Event should have a target when dispatched");
+            Msg::QueryChanged(target.unchecked_into::<HtmlInputElement>().value())
+        });
+
+        let matches: Vec<Html> = stations
+            .search(&self.query, MAX_MATCHES)
+            .into_iter()
+            .filter_map(|found| {
+                let node_id = found.track_node_id?;
+                Some(html! {
+                    <li>
+                        { found.name }
+                        <button onclick={ctx.link().callback(move |_| Msg::FromHere(node_id))}>
+                            { "Start here" }
+                        </button>
+                        <button onclick={ctx.link().callback(move |_| Msg::ToHere(node_id))}>
+                            { "End here" }
+                        </button>
+                    </li>
+                })
+            })
+            .collect();
+
+        html! {
+            <div class="station-search">
+                <input
+                    value={self.query.clone()}
+                    oninput={on_input}
+                    placeholder="Search station"
+                />
+                <ul>{ for matches }</ul>
+            </div>
+        }
+    }
+}
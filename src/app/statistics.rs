@@ -1,20 +1,19 @@
 use yew::{html, Component, Context, Html, Properties};
 
+use crate::statistics::NetworkStatistics;
+
 /// The `Statistics` component displays statistics for a railway network.
 ///
-/// It shows the number of switches, tracks, and the total length of the network.
+/// It shows the size of the network, the track length per type, the number of switches, buffer
+/// stops and crossings, the node degrees, connected components and edge length percentiles.
 ///
 pub struct Statistics {}
 
 /// Properties for the `Statistics` component.
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
-    /// The number of switches in the railway network.
-    pub switches: u32,
-    /// The number of tracks in the railway network.
-    pub tracks: u32,
-    /// The total length of the railway network in meters.
-    pub total_length: f64,
+    /// The statistics of the railway network.
+    pub statistics: NetworkStatistics,
 }
 
 impl Component for Statistics {
@@ -30,12 +29,36 @@ impl Component for Statistics {
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let statistics = &ctx.props().statistics;
+        let percentiles = &statistics.edge_length_percentiles;
         html! {
             <div class="statistics">
                 <h2>{ "Statistics" }</h2>
-                <p>{ format!("Switches: {}", ctx.props().switches) }</p>
-                <p>{ format!("Tracks: {}", ctx.props().tracks) }</p>
-                <p>{ format!("Total Length: {:.0} m", ctx.props().total_length) }</p>
+                <p>{ format!("Nodes: {}", statistics.node_count) }</p>
+                <p>{ format!("Tracks: {}", statistics.edge_count) }</p>
+                <p>{ format!("Total Length: {:.0} m", statistics.total_length) }</p>
+                <ul>
+                    { for statistics.length_by_type.iter().map(|(track_type, length)| html! {
+                        <li>{ format!("{}: {:.0} m", track_type, length) }</li>
+                    }) }
+                </ul>
+                <p>{ format!("Switches: {}", statistics.switches) }</p>
+                <p>{ format!("Buffer Stops: {}", statistics.buffer_stops) }</p>
+                <p>{ format!("Crossings: {}", statistics.crossings) }</p>
+                <p>{ format!("Node Degrees: {}", statistics.degree_distribution
+                    .iter()
+                    .map(|(degree, count)| format!("{}: {}", degree, count))
+                    .collect::<Vec<_>>()
+                    .join(", ")) }</p>
+                <p>{ format!("Connected Components: {}", statistics.connected_components) }</p>
+                <p>{ format!("Largest Component: {} nodes", statistics.component_sizes.first().copied().unwrap_or_default()) }</p>
+                if let Some(diameter) = statistics.diameter {
+                    <p>{ format!("Diameter: {:.0} m", diameter) }</p>
+                }
+                <p>{ format!(
+                    "Edge Lengths: min {:.0} m, median {:.0} m, 90% {:.0} m, max {:.0} m",
+                    percentiles.min, percentiles.p50, percentiles.p90, percentiles.max
+                ) }</p>
             </div>
         }
     }
@@ -44,13 +67,19 @@ impl Component for Statistics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
 
     #[wasm_bindgen_test::wasm_bindgen_test]
     async fn test_render() {
         let props = Props {
-            switches: 5,
-            tracks: 10,
-            total_length: 1500.0,
+            statistics: NetworkStatistics {
+                switches: 5,
+                edge_count: 10,
+                total_length: 1500.0,
+                length_by_type: BTreeMap::from([("rail".to_string(), 1500.0)]),
+                connected_components: 2,
+                ..Default::default()
+            },
         };
         let rendered = yew::LocalServerRenderer::<Statistics>::with_props(props)
             .render()
@@ -60,5 +89,7 @@ mod tests {
         assert!(rendered.contains("Switches: 5"));
         assert!(rendered.contains("Tracks: 10"));
         assert!(rendered.contains("Total Length: 1500 m"));
+        assert!(rendered.contains("rail: 1500 m"));
+        assert!(rendered.contains("Connected Components: 2"));
     }
 }
@@ -1,12 +1,113 @@
+use std::collections::HashMap;
+
+use bevy::color::palettes::css;
 use bevy::prelude::*;
 
+use uom::si::{f64::Length, length::meter};
+
 use crate::{
-    railway_algorithms::{PathFinding, RailwayGraphAlgos},
-    types::EdgeId,
+    railway_algorithms::{
+        KShortestPathsOptions, PathFinding, RailwayGraphAlgos, Reachability, ReachabilityBudget,
+        Route,
+    },
+    types::{EdgeId, NodeId},
 };
 
 use super::{nodes::SelectedNode, AppResource, Projection};
 
+/// The colours of the routes shown between the selected nodes, the shortest first.
+pub const ROUTE_COLORS: [Srgba; 3] = [css::RED, css::ORANGE, css::YELLOW];
+
+/// Options for the alternative routes shown between the selected nodes. Routes sharing more than
+/// 90% of their length with a shorter route are not shown.
+pub fn route_options() -> KShortestPathsOptions {
+    KShortestPathsOptions {
+        k: ROUTE_COLORS.len(),
+        min_difference: 0.1,
+    }
+}
+
+/// The alternative routes between the selected nodes. They are found once when the selection
+/// changes, not in every frame.
+#[derive(Default, Resource)]
+pub struct RouteCache {
+    /// The start and end node the routes were found for.
+    key: Option<(NodeId, NodeId)>,
+    /// The routes, the shortest first.
+    pub routes: Vec<Route>,
+}
+
+/// Finds the alternative routes between the selected nodes when the selection changes.
+pub fn update_route_cache(
+    app_resource: Res<AppResource>,
+    selected_node: Res<SelectedNode>,
+    mut route_cache: ResMut<RouteCache>,
+) {
+    let key = match (selected_node.start_node_id, selected_node.end_node_id) {
+        (Some(start_node_id), Some(end_node_id)) if start_node_id != end_node_id => {
+            Some((start_node_id, end_node_id))
+        }
+        _ => None,
+    };
+    if route_cache.key == key {
+        return;
+    }
+    route_cache.routes = match (key, &app_resource.graph) {
+        (Some((start_node_id, end_node_id)), Some(graph)) => {
+            graph.k_shortest_paths(start_node_id, end_node_id, &route_options())
+        }
+        _ => Vec::new(),
+    };
+    route_cache.key = key;
+}
+
+/// The colour of the tracks reachable from the selected node.
+pub const REACHABLE_COLOR: Srgba = css::LIME;
+
+/// The distance in meters around the selected start node shown as reachable, or zero to show
+/// nothing.
+#[derive(Default, Resource)]
+pub struct ReachabilityResource {
+    pub distance: f64,
+}
+
+/// The part of the network reachable from the selected start node. It is searched once when the
+/// start node or the distance changes, not in every frame.
+#[derive(Default, Resource)]
+pub struct ReachabilityCache {
+    /// The start node and the distance in meters the search ran for.
+    key: Option<(NodeId, f64)>,
+    /// The reachable part of the network.
+    pub reachability: Option<Reachability>,
+}
+
+/// Searches the part of the network reachable from the selected start node when the start node
+/// or the distance changes.
+pub fn update_reachability_cache(
+    app_resource: Res<AppResource>,
+    selected_node: Res<SelectedNode>,
+    reachability: Res<ReachabilityResource>,
+    mut reachability_cache: ResMut<ReachabilityCache>,
+) {
+    let key = match (selected_node.start_node_id, selected_node.end_node_id) {
+        (Some(start_node_id), None) if reachability.distance > 0.0 => {
+            Some((start_node_id, reachability.distance))
+        }
+        _ => None,
+    };
+    if reachability_cache.key == key {
+        return;
+    }
+    reachability_cache.reachability = match (key, &app_resource.graph) {
+        (Some((start_node_id, distance)), Some(graph)) => {
+            let budget = ReachabilityBudget::Distance(Length::new::<meter>(distance));
+            Some(graph.reachable_within(start_node_id, &budget))
+        }
+        _ => None,
+    };
+    reachability_cache.key = key;
+}
+
 /// Represents an edge in the railway graph.
 #[derive(Component)]
 pub struct Edge {
@@ -16,22 +117,47 @@ pub struct Edge {
 pub fn show_edges(
     app_resource: Res<AppResource>,
     selected_node: Res<SelectedNode>,
+    reachability_cache: Res<ReachabilityCache>,
+    route_cache: Res<RouteCache>,
     projection: Res<Projection>,
     mut gizmos: Gizmos,
 ) {
     if let Some(graph) = &app_resource.graph {
-        let mut highlighted_edges = Vec::new();
+        let mut highlighted_edges: HashMap<EdgeId, Srgba> = HashMap::new();
+        // Whole edges are coloured below, the parts where the distance runs out are drawn on
+        // their own
+        for reached in reachability_cache
+            .reachability
+            .iter()
+            .flat_map(|reachability| &reachability.edges)
+        {
+            if reached.complete {
+                highlighted_edges.insert(reached.edge_id, REACHABLE_COLOR);
+            } else {
+                let points = reached
+                    .path
+                    .0
+                    .iter()
+                    .map(|coords| projection.project(*coords).unwrap())
+                    .collect::<Vec<_>>();
+                gizmos.linestrip(points, REACHABLE_COLOR);
+            }
+        }
         if let (Some(start_node_id), Some(end_node_id)) =
             (selected_node.start_node_id, selected_node.end_node_id)
         {
-            let path_edge_ids = if start_node_id == end_node_id {
-                Some(graph.reachable_edges(start_node_id))
+            if start_node_id == end_node_id {
+                for edge_id in graph.reachable_edges(start_node_id) {
+                    highlighted_edges.insert(edge_id, ROUTE_COLORS[0]);
+                }
             } else {
-                graph.shortest_path_edges(start_node_id, end_node_id)
-            };
-            // Use graph.shortest_path_edges to get the Vec of edge IDs
-            if let Some(path_edge_ids) = path_edge_ids {
-                highlighted_edges = path_edge_ids;
+                // Colour the alternatives first so that edges shared with a shorter route get
+                // the colour of the shorter one
+                for (route, color) in route_cache.routes.iter().zip(ROUTE_COLORS).rev() {
+                    for edge_id in &route.edges {
+                        highlighted_edges.insert(*edge_id, color);
+                    }
+                }
             }
         }
         for edge in graph.physical_graph.graph.edge_references() {
@@ -42,11 +168,11 @@ pub fn show_edges(
                 .iter()
                 .map(|coords| projection.project(*coords).unwrap())
                 .collect::<Vec<_>>();
-            if highlighted_edges.contains(&edge_data.id) {
-                gizmos.linestrip(points, bevy::color::palettes::css::RED);
-            } else {
-                gizmos.linestrip(points, bevy::color::palettes::css::BLUE);
-            }
+            let color = highlighted_edges
+                .get(&edge_data.id)
+                .copied()
+                .unwrap_or(css::BLUE);
+            gizmos.linestrip(points, color);
         }
     }
 }
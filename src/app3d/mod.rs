@@ -7,7 +7,10 @@
 
 use std::sync::{Arc, RwLock};
 
-use crate::prelude::{RailwayGraph, RailwayGraphExt};
+use crate::prelude::{
+    RailwayGraph, RailwayGraphExt, RailwayNode, RailwayTags, RailwayTrackAttributes,
+};
+use crate::railway_algorithms::StationIndex;
 use crate::simulation::Simulation;
 use bevy::ecs::observer::On;
 use bevy::input::ButtonInput;
@@ -36,11 +39,14 @@ pub use projection::Projection;
 
 use self::train_agent::SelectedTrain;
 
-/// Holds application state, including the area name, railway graph, and camera look-at position.
+/// Holds application state, including the area name, railway graph, station search, and camera
+/// look-at position.
 #[derive(Default, Resource)]
 pub struct AppResource {
     area_name: String,
     graph: Option<RailwayGraph>,
+    stations: StationIndex,
+    station_query: String,
     simulation: Option<Arc<RwLock<Simulation>>>,
     look_at_position: Option<Vec3>,
 }
@@ -89,20 +95,27 @@ pub fn setup_app(app: &mut App, app_resource: AppResource) {
         .insert_resource(SelectedTrain::default())
         .insert_resource(InteractionModeResource::default())
         .insert_resource(DebugResource::default())
-        .add_systems(Startup, (setup, camera::setup_camera))
+        .insert_resource(edges::ReachabilityResource::default())
+        .insert_resource(edges::RouteCache::default())
+        .insert_resource(edges::ReachabilityCache::default())
+        .add_systems(Startup, (setup, camera::setup_camera, spawn_ground))
         .add_systems(
             Update,
             (
                 update_look_at_position_system,
                 nodes::select_node_system,
+                edges::update_route_cache.before(edges::show_edges),
+                edges::update_reachability_cache.before(edges::show_edges),
                 edges::show_edges,
                 train_agent::update_train_position_system,
                 train_agent::update_train_agent_line_system,
                 train_agent::select_train_system,
+                train_agent::place_train_system,
                 update_simulation_system,
             ),
         )
         .add_message::<train_agent::TrainSelectedEvent>()
+        .add_message::<train_agent::GroundClickedEvent>()
         .add_message::<nodes::NodeSelectedEvent>();
     ui::add_ui_systems_to_app(app);
     console::add_console_to_app(app);
@@ -117,17 +130,25 @@ pub fn setup_app(app: &mut App, app_resource: AppResource) {
 /// # Arguments
 ///
 /// * `graph` - A `RailwayGraph` to display in the application.
+/// * `tags` - The tags of the nodes and edges of the graph and of the stations.
+/// * `stations` - The locations of the stations that are not part of the graph.
 ///
-pub fn init_with_graph(graph: RailwayGraph) {
+pub fn init_with_graph(graph: RailwayGraph, tags: RailwayTags, stations: &[RailwayNode]) {
     let mut projection = Projection::new(5000.0, 5000.0);
     let (min_coord, max_coord) = graph.bounding_box();
     projection.set_bounding_box(min_coord, max_coord);
 
+    let mut simulation = Simulation::new(graph.clone());
+    *simulation.environment.track_attributes_mut() = RailwayTrackAttributes::from_tags(&tags);
+    simulation.environment.prepare_routing_table();
+    let stations = StationIndex::new(&graph, &tags, stations);
     let app_resource = AppResource {
         area_name: "".to_string(),
-        graph: Some(graph.clone()),
+        graph: Some(graph),
+        stations,
+        station_query: "".to_string(),
         look_at_position: None,
-        simulation: Some(Arc::new(RwLock::new(Simulation::new(graph)))),
+        simulation: Some(Arc::new(RwLock::new(simulation))),
     };
     let mut app = App::new();
     app.add_plugins(DefaultPlugins.set(WindowPlugin {
@@ -200,6 +221,38 @@ fn setup(mut commands: Commands) {
     ));
 }
 
+/// Half the side length of the invisible plane below the network that receives clicks on the map.
+const GROUND_HALF_SIZE: f32 = 10_000.0;
+
+/// Spawns an invisible plane below the network, so that clicks between the nodes can place trains
+/// on the tracks.
+fn spawn_ground(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn((
+            Mesh3d(meshes.add(Plane3d::new(Vec3::Z, Vec2::splat(GROUND_HALF_SIZE)))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::NONE,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..Default::default()
+            })),
+            Transform::from_xyz(0.0, 0.0, -1.0),
+            Pickable::default(),
+        ))
+        .observe(
+            |click: On<Pointer<Click>>,
+             mut events: MessageWriter<train_agent::GroundClickedEvent>| {
+                if let Some(position) = click.hit.position {
+                    events.write(train_agent::GroundClickedEvent(position));
+                }
+            },
+        );
+}
+
 fn update_simulation_system(app_resource: Res<AppResource>, time: Res<Time>) {
     if let Some(simulation) = &app_resource.simulation {
         simulation.write().unwrap().update(time.delta())
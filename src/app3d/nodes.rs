@@ -1,11 +1,10 @@
 use bevy::prelude::*;
 
 use crate::{
-    app3d::train_agent::{self, create_new_train_id, create_train, TrainAgent},
+    app3d::train_agent::{self, create_new_train_id, create_train},
     prelude::RailwayGraphExt,
     types::NodeId,
 };
-use bevy::picking::prelude::Pickable;
 
 use super::{AppResource, InteractionMode, InteractionModeResource};
 
@@ -67,26 +66,15 @@ pub fn select_node_system(
                     let mut sim = simulation.write().unwrap();
                     let id = create_new_train_id();
                     let id = create_train(id, Some(node_id), None, &mut sim);
-                    let train_agent = TrainAgent::new(id);
 
                     println!("Placing train on node: {:?}", node_id);
-                    commands
-                        .spawn((
-                            Transform::from_xyz(
-                                transform.translation.x,
-                                transform.translation.y,
-                                transform.translation.z + 1.0,
-                            ),
-                            GlobalTransform::default(),
-                            InheritedVisibility::default(),
-                            Visibility::Inherited,
-                            train_agent,
-                        ))
-                        .insert(Pickable::default())
-                        .with_children(train_agent::create_train_agent_bundle(
-                            materials,
-                            asset_server,
-                        ));
+                    train_agent::spawn_train_agent(
+                        &mut commands,
+                        id,
+                        transform.translation,
+                        materials,
+                        asset_server,
+                    );
                 }
             }
         }
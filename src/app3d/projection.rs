@@ -1,14 +1,19 @@
 use bevy::prelude::{Resource, Vec3};
 use geo::Coord;
 
+use crate::crs::ViewTransform;
+
 /// The `Projection` resource is responsible for converting geographical coordinates
 /// (latitude and longitude) to view coordinates, taking into account the view dimensions
 /// and the bounding box of a given `RailwayGraph`.
+///
+/// Coordinates are projected to a plane tangent at the center of the bounding box, so both axes
+/// share the same scale in meters.
 #[derive(Default, Resource)]
 pub struct Projection {
     view_width: f64,
     view_height: f64,
-    bounding_box: Option<(Coord, Coord)>,
+    transform: Option<ViewTransform>,
 }
 
 impl Projection {
@@ -23,7 +28,7 @@ impl Projection {
         Self {
             view_width,
             view_height,
-            bounding_box: None,
+            transform: None,
         }
     }
 
@@ -35,7 +40,12 @@ impl Projection {
     /// * `max_coord` - The maximum coordinate of the bounding box.
     ///
     pub fn set_bounding_box(&mut self, min_coord: Coord, max_coord: Coord) {
-        self.bounding_box = Some((min_coord, max_coord));
+        self.transform = Some(ViewTransform::fit_local(
+            min_coord,
+            max_coord,
+            self.view_width,
+            self.view_height,
+        ));
     }
 
     /// Projects the given geographical coordinate to a view coordinate.
@@ -49,18 +59,34 @@ impl Projection {
     /// * `Option<Vec3>` - A view coordinate as a 3D vector if the bounding box is set, or `None` otherwise.
     ///
     pub fn project(&self, coord: Coord) -> Option<Vec3> {
-        if let Some((min_coord, max_coord)) = self.bounding_box {
-            let x = ((coord.x - min_coord.x) / (max_coord.x - min_coord.x)) * self.view_width;
-            let y = ((coord.y - min_coord.y) / (max_coord.y - min_coord.y)) * self.view_height;
+        self.transform.map(|transform| {
+            let view = transform.to_view(coord);
 
-            // Center the projection
-            let centered_x = x - self.view_width / 2.0;
-            let centered_y = y - self.view_height / 2.0;
+            // Center the projection, with y pointing up
+            let centered_x = view.x - self.view_width / 2.0;
+            let centered_y = self.view_height / 2.0 - view.y;
 
-            Some(Vec3::new(centered_x as f32, centered_y as f32, 0.0))
-        } else {
-            None
-        }
+            Vec3::new(centered_x as f32, centered_y as f32, 0.0)
+        })
+    }
+
+    /// Converts a view coordinate back to a geographical coordinate, ignoring its height.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A view coordinate as returned by `project`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Coord>` - The geographical coordinate if the bounding box is set, or `None` otherwise.
+    ///
+    pub fn unproject(&self, position: Vec3) -> Option<Coord> {
+        self.transform.map(|transform| {
+            transform.to_geo(Coord {
+                x: position.x as f64 + self.view_width / 2.0,
+                y: self.view_height / 2.0 - position.y as f64,
+            })
+        })
     }
 }
 
@@ -71,15 +97,21 @@ mod tests {
     #[test]
     fn test_projection() {
         let mut projection = Projection::new(1000.0, 1000.0);
-        projection.set_bounding_box(Coord { x: 0.0, y: 0.0 }, Coord { x: 10.0, y: 10.0 });
+        assert_eq!(projection.project(Coord { x: 0.0, y: 60.0 }), None);
+        projection.set_bounding_box(Coord { x: 0.0, y: 60.0 }, Coord { x: 4.0, y: 61.0 });
 
-        let coord = Coord { x: 0.0, y: 0.0 };
-        let projected_coord = projection.project(coord).unwrap();
+        let projected_coord = projection.project(Coord { x: 2.0, y: 60.5 }).unwrap();
+        assert!(projected_coord.abs_diff_eq(Vec3::ZERO, 1e-3));
 
-        assert_eq!(projected_coord, Vec3::new(-500.0, -500.0, 0.0));
-        let coord = Coord { x: 5.0, y: 5.0 };
-        let projected_coord = projection.project(coord).unwrap();
+        // At 60° latitude four degrees of longitude are about twice as long as one of latitude,
+        // so the bounding box fills the width but only about half of the height.
+        let projected_coord = projection.project(Coord { x: 0.0, y: 60.0 }).unwrap();
+        assert!((projected_coord.x + 500.0).abs() < 1e-3);
+        assert!((projected_coord.y + 253.4).abs() < 1.0);
 
-        assert_eq!(projected_coord, Vec3::new(0.0, 0.0, 0.0));
+        let coord = projection.unproject(projected_coord).unwrap();
+        assert!((coord.x - 0.0).abs() < 1e-6);
+        assert!((coord.y - 60.0).abs() < 1e-6);
+        assert_eq!(Projection::new(1.0, 1.0).unproject(Vec3::ZERO), None);
     }
 }
@@ -1,12 +1,12 @@
 use bevy::ecs::observer::On;
 use bevy::picking::prelude::{Click, Pickable, Pointer};
 use bevy::prelude::*;
+use geo::Coord;
 use uom::si::velocity::{kilometer_per_hour, meter_per_second, Velocity};
 
-use super::{AppResource, Node};
+use super::{AppResource, InteractionMode, InteractionModeResource, Node, Projection};
 use crate::app3d::DebugResource;
 use crate::prelude::RailwayGraphExt;
-use crate::railway_algorithms::RailwayGraphAlgos;
 use crate::types::NodeId;
 use crate::{
     prelude::{RailwayEdge, RailwayGraph},
@@ -88,7 +88,26 @@ pub fn create_train(
             .get_node_by_id(position.unwrap());
         Some(node.unwrap().location)
     };
+    add_train(id, position, geo_location, target, simulation)
+}
+
+/// Creates a train at an arbitrary location, which the simulation snaps onto the closest track.
+pub fn create_train_at(
+    id: RailwayObjectId,
+    location: Coord,
+    target: Option<NodeId>,
+    simulation: &mut Simulation,
+) -> RailwayObjectId {
+    add_train(id, None, Some(location), target, simulation)
+}
 
+fn add_train(
+    id: RailwayObjectId,
+    position: Option<NodeId>,
+    geo_location: Option<Coord>,
+    target: Option<NodeId>,
+    simulation: &mut Simulation,
+) -> RailwayObjectId {
     let agent = ForwardUntilTargetAgent::new(id);
     let train = Train {
         id,
@@ -106,6 +125,64 @@ pub fn create_train(
     id
 }
 
+/// Spawns the entity showing a train of the simulation at a view position.
+pub fn spawn_train_agent(
+    commands: &mut Commands,
+    id: RailwayObjectId,
+    translation: Vec3,
+    materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands
+        .spawn((
+            Transform::from_xyz(translation.x, translation.y, translation.z + 1.0),
+            GlobalTransform::default(),
+            InheritedVisibility::default(),
+            Visibility::Inherited,
+            TrainAgent::new(id),
+        ))
+        .insert(Pickable::default())
+        .with_children(create_train_agent_bundle(materials, asset_server));
+}
+
+/// A click on the map away from the nodes, at the given view position.
+#[derive(Debug, Message)]
+pub struct GroundClickedEvent(pub Vec3);
+
+/// Places a train on the track closest to a click on the map while placing trains.
+pub fn place_train_system(
+    mut events: MessageReader<GroundClickedEvent>,
+    app_resource: Res<AppResource>,
+    interaction_mode: Res<InteractionModeResource>,
+    projection: Res<Projection>,
+    mut commands: Commands,
+    materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(click) = events.read().last().map(|event| event.0) else {
+        return;
+    };
+    if interaction_mode.mode != InteractionMode::PlaceTrain {
+        return;
+    }
+    let (Some(simulation), Some(location)) =
+        (&app_resource.simulation, projection.unproject(click))
+    else {
+        return;
+    };
+
+    let mut sim = simulation.write().unwrap();
+    let id = create_train_at(create_new_train_id(), location, None, &mut sim);
+    let Some(train) = sim.environment.objects.get(&id) else {
+        return;
+    };
+    let translation = train
+        .geo_location()
+        .and_then(|location| projection.project(location))
+        .unwrap_or(click);
+    spawn_train_agent(&mut commands, id, translation, materials, asset_server);
+}
+
 pub fn create_train_agent_bundle(
     mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>,
@@ -157,17 +234,12 @@ pub fn update_train_position_system(
                 if let Some(view_coord) = &projection.project(location) {
                     transform.translation.x = view_coord.x;
                     transform.translation.y = view_coord.y;
-                    // Add next target information
-                    if let Some(target_node_id) = train.next_target() {
-                        if let Some(simulation) = &app_resource.simulation {
-                            if let Ok(simulation) = simulation.read() {
-                                let graph = simulation.get_observable_environment().get_graph();
-                                if let Some(next_node_id) = graph.get_next_node(
-                                    train.position().unwrap_or_default(),
-                                    target_node_id,
-                                ) {
-                                    update_look_at(&projection, &mut transform, graph, next_node_id)
-                                }
+                    // Look towards the node the train is heading to
+                    if let Some(simulation) = &app_resource.simulation {
+                        if let Ok(simulation) = simulation.read() {
+                            let graph = simulation.get_observable_environment().get_graph();
+                            if let Some(next_node_id) = simulation.next_node(train.id()) {
+                                update_look_at(&projection, &mut transform, graph, next_node_id)
                             }
                         }
                     }
@@ -3,6 +3,9 @@
     allow(dead_code, unused_imports, unused_variables, unused_mut)
 )]
 
+use super::edges::{
+    ReachabilityCache, ReachabilityResource, RouteCache, REACHABLE_COLOR, ROUTE_COLORS,
+};
 use super::nodes::SelectedNode;
 use super::train_agent::{clone_train_from_app, TrainAgent};
 use super::{display_graph, SelectedTrain};
@@ -15,7 +18,8 @@ use crate::prelude::OverpassImporter;
 use crate::prelude::RailwayApiClient;
 use crate::prelude::RailwayGraph;
 use crate::prelude::RailwayGraphImporter;
-use crate::railway_algorithms::PathFinding;
+use crate::prelude::RailwayTrackAttributes;
+use crate::railway_algorithms::{PathFinding, Route, StationIndex};
 use crate::railway_objects::{Movable, NextTarget, RailwayObject, Train};
 use crate::simulation::agents::decision_agent_factory::{
     DecisionAgentFactory, DecisionAgentOption,
@@ -23,6 +27,7 @@ use crate::simulation::agents::decision_agent_factory::{
 use crate::simulation::agents::ForwardUntilTargetAgent;
 use crate::statistics::path_length;
 use crate::types::NodeId;
+use bevy::color::ColorToPacked;
 use bevy::prelude::Commands;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
@@ -45,11 +50,13 @@ pub fn add_ui_systems_to_app(app: &mut App) {
 #[allow(clippy::too_many_arguments)]
 pub fn selection_ui_system(
     mut contexts: EguiContexts,
-    app_resource: ResMut<AppResource>,
-    selected_node: Res<SelectedNode>,
+    mut app_resource: ResMut<AppResource>,
+    mut selected_node: ResMut<SelectedNode>,
     selected_train: Res<SelectedTrain>,
     q_train: Query<&TrainAgent>,
     mut interaction_mode: ResMut<InteractionModeResource>,
+    mut reachability: ResMut<ReachabilityResource>,
+    route_cache: Res<RouteCache>,
     time: Res<Time>,
     mut ui_update_timer: ResMut<UiUpdateTimer>,
 ) {
@@ -62,10 +69,25 @@ pub fn selection_ui_system(
         return;
     };
     egui::Window::new("").show(context, |ui| {
+        if !app_resource.stations.is_empty() {
+            let AppResource {
+                stations,
+                station_query,
+                ..
+            } = &mut *app_resource;
+            display_station_search(ui, stations, station_query, &mut selected_node);
+            ui.add_space(15.0); // Add space
+        }
         if let Some(node_id) = selected_node.start_node_id {
             if let Some(graph) = &app_resource.graph {
                 display_selected_node_info(ui, graph, node_id);
             }
+            let [red, green, blue, _] = REACHABLE_COLOR.to_u8_array();
+            ui.colored_label(
+                egui::Color32::from_rgb(red, green, blue),
+                "Reachable within (meters):",
+            );
+            ui.add(egui::Slider::new(&mut reachability.distance, 0.0..=20000.0));
         } else {
             ui.label("No node selected");
         }
@@ -74,7 +96,7 @@ pub fn selection_ui_system(
             (selected_node.start_node_id, selected_node.end_node_id)
         {
             if let Some(graph) = &app_resource.graph {
-                display_path_info(ui, graph, start_node_id, end_node_id);
+                display_path_info(ui, graph, start_node_id, end_node_id, &route_cache.routes);
             }
         }
         ui.add_space(15.0); // Add space
@@ -117,6 +139,8 @@ pub fn select_graph_ui_system(
     mut app_resource: ResMut<AppResource>,
     node_query: Query<Entity, With<Node>>,
     mut projection: ResMut<Projection>,
+    mut route_cache: ResMut<RouteCache>,
+    mut reachability_cache: ResMut<ReachabilityCache>,
     meshes: ResMut<Assets<Mesh>>,
     materials: ResMut<Assets<StandardMaterial>>,
 ) {
@@ -134,6 +158,9 @@ pub fn select_graph_ui_system(
 
         if ui.button("Load Railway Graph").clicked() {
             let area_name = app_resource.area_name.clone();
+            // The routes and the reachable part of the selected nodes belong to the previous graph.
+            *route_cache = RouteCache::default();
+            *reachability_cache = ReachabilityCache::default();
             // Process input and update Bevy resources or systems
             println!("Loading railway graph data: {}", area_name);
 
@@ -153,10 +180,29 @@ pub fn select_graph_ui_system(
                 };
 
                 let graph = OverpassImporter::import(&api_json_value).unwrap();
+                let mut tags = OverpassImporter::import_tags(&api_json_value).unwrap();
+                // Without stations, nodes can still be picked in the graph.
+                let stations_json = if area_name.contains(',') {
+                    client.fetch_stations_by_bbox(&area_name).await
+                } else {
+                    client.fetch_stations_by_area_name(&area_name).await
+                };
+                let stations = stations_json
+                    .and_then(|value| {
+                        tags.nodes
+                            .extend(OverpassImporter::import_tags(&value)?.nodes);
+                        OverpassImporter::import_nodes(&value)
+                    })
+                    .unwrap_or_default();
+                app_resource.stations = StationIndex::new(&graph, &tags, &stations);
                 let (min_coord, max_coord) = graph.bounding_box();
                 projection.set_bounding_box(min_coord, max_coord);
                 app_resource.graph = Some(graph.clone());
-                app_resource.simulation = Some(Arc::new(RwLock::new(Simulation::new(graph))));
+                let mut simulation = Simulation::new(graph);
+                *simulation.environment.track_attributes_mut() =
+                    RailwayTrackAttributes::from_tags(&tags);
+                simulation.environment.prepare_routing_table();
+                app_resource.simulation = Some(Arc::new(RwLock::new(simulation)));
                 display_graph(
                     commands,
                     app_resource.into(),
@@ -170,6 +216,33 @@ pub fn select_graph_ui_system(
     });
 }
 
+/// The number of matches listed below the station search field.
+const MAX_STATION_MATCHES: usize = 5;
+
+pub fn display_station_search(
+    ui: &mut egui::Ui,
+    stations: &StationIndex,
+    query: &mut String,
+    selected_node: &mut SelectedNode,
+) {
+    ui.label("Search station:");
+    ui.text_edit_singleline(query);
+    for found in stations.search(query, MAX_STATION_MATCHES) {
+        let Some(node_id) = found.track_node_id else {
+            continue;
+        };
+        ui.horizontal(|ui| {
+            ui.label(&found.name);
+            if ui.button("Start here").clicked() {
+                selected_node.start_node_id = Some(node_id);
+            }
+            if ui.button("End here").clicked() {
+                selected_node.end_node_id = Some(node_id);
+            }
+        });
+    }
+}
+
 pub fn display_selected_node_info(ui: &mut egui::Ui, graph: &RailwayGraph, node_id: NodeId) {
     if let Some(&node_index) = graph.physical_graph.id_to_index(node_id) {
         let node = &graph.physical_graph.graph[node_index];
@@ -274,6 +347,7 @@ pub fn display_path_info(
     graph: &RailwayGraph,
     start_node_id: NodeId,
     end_node_id: NodeId,
+    routes: &[Route],
 ) {
     let start_node_index = graph.physical_graph.id_to_index(start_node_id);
     let end_node_index = graph.physical_graph.id_to_index(end_node_id);
@@ -294,5 +368,13 @@ pub fn display_path_info(
                 .unwrap_or_else(|| "unknown".to_string());
             ui.label(format!("Distance: {}", distance));
         }
+
+        for (index, (route, color)) in routes.iter().zip(ROUTE_COLORS).enumerate().skip(1) {
+            let [red, green, blue, _] = color.to_u8_array();
+            ui.colored_label(
+                egui::Color32::from_rgb(red, green, blue),
+                format!("Alternative {}: {:.2} meters", index, route.length),
+            );
+        }
     }
 }
@@ -0,0 +1,465 @@
+//! Module `crs` converts between geographic and projected coordinate reference systems.
+//!
+//! All geometry of a `RailwayGraph` is stored as longitude/latitude in degrees, where one unit
+//! along x is shorter than one unit along y everywhere but on the equator. A `Crs` projects
+//! coordinates to a plane in meters and back: UTM for accurate measurements over a region, Web
+//! Mercator to match web maps, or a plane tangent to the earth for small areas. The
+//! `RailwayGraphProjection` trait converts whole graphs and the `ViewTransform` maps coordinates
+//! to aspect-correct view coordinates for renderers.
+use geo::{Coord, LineString};
+
+use crate::prelude::RailwayGraph;
+
+/// Semi-major axis of the WGS 84 ellipsoid in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// Flattening of the WGS 84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// Scale factor on the central meridian of a UTM zone.
+const UTM_K0: f64 = 0.9996;
+/// False easting of UTM coordinates in meters.
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+/// False northing of UTM coordinates on the southern hemisphere in meters.
+const UTM_FALSE_NORTHING: f64 = 10_000_000.0;
+
+/// A coordinate reference system with coordinates in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crs {
+    /// Universal Transverse Mercator on the WGS 84 ellipsoid, with easting and northing.
+    Utm {
+        /// The zone from 1 to 60, each six degrees of longitude wide.
+        zone: u8,
+        /// `true` for the northern and `false` for the southern hemisphere.
+        north: bool,
+    },
+    /// Spherical Web Mercator (EPSG:3857) as used by web maps.
+    ///
+    /// Web Mercator preserves angles but not distances, which are stretched by `1 / cos(lat)`.
+    WebMercator,
+    /// A plane tangent to the WGS 84 ellipsoid at `origin`, with x to the east and y to the north.
+    ///
+    /// Distances are accurate to a few parts per thousand within about 50 km of the origin.
+    LocalTangentPlane {
+        /// The longitude/latitude of the tangent point, projected to `(0, 0)`.
+        origin: Coord<f64>,
+    },
+}
+
+impl Crs {
+    /// Returns the UTM zone containing the given longitude/latitude.
+    pub fn utm_for(location: Coord<f64>) -> Self {
+        let zone = ((location.x + 180.0) / 6.0).floor().clamp(0.0, 59.0) as u8 + 1;
+        Crs::Utm {
+            zone,
+            north: location.y >= 0.0,
+        }
+    }
+
+    /// Projects a longitude/latitude in degrees to coordinates in meters.
+    pub fn project(&self, location: Coord<f64>) -> Coord<f64> {
+        match *self {
+            Crs::Utm { zone, north } => utm_forward(location, zone, north),
+            Crs::WebMercator => Coord {
+                x: WGS84_A * location.x.to_radians(),
+                y: WGS84_A
+                    * (std::f64::consts::FRAC_PI_4 + location.y.to_radians() / 2.0)
+                        .tan()
+                        .ln(),
+            },
+            Crs::LocalTangentPlane { origin } => {
+                let (meridian, normal) = radii_of_curvature(origin.y.to_radians());
+                Coord {
+                    x: (location.x - origin.x).to_radians() * normal * origin.y.to_radians().cos(),
+                    y: (location.y - origin.y).to_radians() * meridian,
+                }
+            }
+        }
+    }
+
+    /// Converts coordinates in meters back to a longitude/latitude in degrees.
+    pub fn unproject(&self, point: Coord<f64>) -> Coord<f64> {
+        match *self {
+            Crs::Utm { zone, north } => utm_inverse(point, zone, north),
+            Crs::WebMercator => Coord {
+                x: (point.x / WGS84_A).to_degrees(),
+                y: (2.0 * (point.y / WGS84_A).exp().atan() - std::f64::consts::FRAC_PI_2)
+                    .to_degrees(),
+            },
+            Crs::LocalTangentPlane { origin } => {
+                let (meridian, normal) = radii_of_curvature(origin.y.to_radians());
+                Coord {
+                    x: origin.x + (point.x / (normal * origin.y.to_radians().cos())).to_degrees(),
+                    y: origin.y + (point.y / meridian).to_degrees(),
+                }
+            }
+        }
+    }
+
+    /// Projects every coordinate of a line string.
+    pub fn project_line_string(&self, line_string: &LineString<f64>) -> LineString<f64> {
+        line_string.0.iter().map(|c| self.project(*c)).collect()
+    }
+
+    /// Converts every coordinate of a projected line string back to longitude/latitude.
+    pub fn unproject_line_string(&self, line_string: &LineString<f64>) -> LineString<f64> {
+        line_string.0.iter().map(|c| self.unproject(*c)).collect()
+    }
+}
+
+/// Conversion of railway graphs between coordinate reference systems.
+pub trait RailwayGraphProjection {
+    /// Returns a copy of the graph with all node locations and edge geometries projected to
+    /// `crs`.
+    ///
+    /// Edge lengths are kept, as they are already given in meters.
+    fn to_crs(&self, crs: &Crs) -> RailwayGraph;
+
+    /// Returns a copy of a graph projected to `crs` with all coordinates converted back to
+    /// longitude/latitude.
+    fn to_geographic(&self, crs: &Crs) -> RailwayGraph;
+}
+
+impl RailwayGraphProjection for RailwayGraph {
+    fn to_crs(&self, crs: &Crs) -> RailwayGraph {
+        map_coordinates(self, |c| crs.project(c))
+    }
+
+    fn to_geographic(&self, crs: &Crs) -> RailwayGraph {
+        map_coordinates(self, |c| crs.unproject(c))
+    }
+}
+
+fn map_coordinates(graph: &RailwayGraph, map: impl Fn(Coord<f64>) -> Coord<f64>) -> RailwayGraph {
+    let mut graph = graph.clone();
+    for node in graph.physical_graph.graph.node_weights_mut() {
+        node.location = map(node.location);
+    }
+    for edge in graph.physical_graph.graph.edge_weights_mut() {
+        edge.path = edge.path.0.iter().map(|c| map(*c)).collect();
+    }
+    graph
+}
+
+/// Maps longitude/latitude coordinates to view coordinates with the same scale on both axes.
+///
+/// View coordinates start at the top left corner with y pointing down, as in SVG. The projected
+/// bounding box is scaled to fit the view and centered on it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewTransform {
+    crs: Crs,
+    min: Coord<f64>,
+    scale: f64,
+    margin: Coord<f64>,
+    view_height: f64,
+}
+
+impl ViewTransform {
+    /// Creates a transform fitting a longitude/latitude bounding box into a view.
+    ///
+    /// # Arguments
+    ///
+    /// * `crs` - The coordinate reference system to project to.
+    /// * `min_coord` - The minimum longitude/latitude of the bounding box.
+    /// * `max_coord` - The maximum longitude/latitude of the bounding box.
+    /// * `view_width` - The width of the view.
+    /// * `view_height` - The height of the view.
+    pub fn fit(
+        crs: Crs,
+        min_coord: Coord<f64>,
+        max_coord: Coord<f64>,
+        view_width: f64,
+        view_height: f64,
+    ) -> Self {
+        let corners = [
+            min_coord,
+            Coord {
+                x: min_coord.x,
+                y: max_coord.y,
+            },
+            Coord {
+                x: max_coord.x,
+                y: min_coord.y,
+            },
+            max_coord,
+        ]
+        .map(|corner| crs.project(corner));
+        let min = Coord {
+            x: corners.iter().map(|c| c.x).fold(f64::INFINITY, f64::min),
+            y: corners.iter().map(|c| c.y).fold(f64::INFINITY, f64::min),
+        };
+        let max = Coord {
+            x: corners
+                .iter()
+                .map(|c| c.x)
+                .fold(f64::NEG_INFINITY, f64::max),
+            y: corners
+                .iter()
+                .map(|c| c.y)
+                .fold(f64::NEG_INFINITY, f64::max),
+        };
+        let (width, height) = (max.x - min.x, max.y - min.y);
+
+        let scale = match (width > 0.0, height > 0.0) {
+            (true, true) => (view_width / width).min(view_height / height),
+            (true, false) => view_width / width,
+            (false, true) => view_height / height,
+            (false, false) => 1.0,
+        };
+        Self {
+            crs,
+            min,
+            scale,
+            margin: Coord {
+                x: (view_width - width * scale) / 2.0,
+                y: (view_height - height * scale) / 2.0,
+            },
+            view_height,
+        }
+    }
+
+    /// Creates a transform projecting to a plane tangent at the center of the bounding box.
+    pub fn fit_local(
+        min_coord: Coord<f64>,
+        max_coord: Coord<f64>,
+        view_width: f64,
+        view_height: f64,
+    ) -> Self {
+        let origin = Coord {
+            x: (min_coord.x + max_coord.x) / 2.0,
+            y: (min_coord.y + max_coord.y) / 2.0,
+        };
+        Self::fit(
+            Crs::LocalTangentPlane { origin },
+            min_coord,
+            max_coord,
+            view_width,
+            view_height,
+        )
+    }
+
+    /// Returns the view coordinate of a longitude/latitude.
+    pub fn to_view(&self, location: Coord<f64>) -> Coord<f64> {
+        let point = self.crs.project(location);
+        Coord {
+            x: self.margin.x + (point.x - self.min.x) * self.scale,
+            y: self.view_height - self.margin.y - (point.y - self.min.y) * self.scale,
+        }
+    }
+
+    /// Returns the longitude/latitude of a view coordinate, the inverse of `to_view`.
+    pub fn to_geo(&self, view: Coord<f64>) -> Coord<f64> {
+        self.crs.unproject(Coord {
+            x: self.min.x + (view.x - self.margin.x) / self.scale,
+            y: self.min.y + (self.view_height - self.margin.y - view.y) / self.scale,
+        })
+    }
+
+    /// Returns the view units per unit of the coordinate reference system.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+}
+
+/// Returns the meridian and the prime vertical radius of curvature of the WGS 84 ellipsoid.
+fn radii_of_curvature(lat: f64) -> (f64, f64) {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let w = 1.0 - e2 * lat.sin().powi(2);
+    (WGS84_A * (1.0 - e2) / w.powf(1.5), WGS84_A / w.sqrt())
+}
+
+/// Returns the longitude of the central meridian of a UTM zone in radians.
+fn central_meridian(zone: u8) -> f64 {
+    (zone as f64 * 6.0 - 183.0).to_radians()
+}
+
+/// Returns the length of the meridian arc from the equator to `lat`.
+fn meridian_arc(lat: f64) -> f64 {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    WGS84_A
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * lat
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * lat).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * lat).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * lat).sin())
+}
+
+/// Transverse Mercator forward projection after Snyder, "Map Projections: A Working Manual".
+fn utm_forward(location: Coord<f64>, zone: u8, north: bool) -> Coord<f64> {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let ep2 = e2 / (1.0 - e2);
+    let lat = location.y.to_radians();
+    let (sin, cos, tan) = (lat.sin(), lat.cos(), lat.tan());
+
+    let n = WGS84_A / (1.0 - e2 * sin * sin).sqrt();
+    let t = tan * tan;
+    let c = ep2 * cos * cos;
+    let a = cos * (location.x.to_radians() - central_meridian(zone));
+
+    let x = UTM_K0
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+    let y = UTM_K0
+        * (meridian_arc(lat)
+            + n * tan
+                * (a * a / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+    Coord {
+        x: x + UTM_FALSE_EASTING,
+        y: if north { y } else { y + UTM_FALSE_NORTHING },
+    }
+}
+
+/// Transverse Mercator inverse projection after Snyder, "Map Projections: A Working Manual".
+fn utm_inverse(point: Coord<f64>, zone: u8, north: bool) -> Coord<f64> {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let ep2 = e2 / (1.0 - e2);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    let northing = if north {
+        point.y
+    } else {
+        point.y - UTM_FALSE_NORTHING
+    };
+
+    let mu = northing / UTM_K0 / (WGS84_A * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let lat1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let (sin, cos, tan) = (lat1.sin(), lat1.cos(), lat1.tan());
+    let c1 = ep2 * cos * cos;
+    let t1 = tan * tan;
+    let n1 = WGS84_A / (1.0 - e2 * sin * sin).sqrt();
+    let r1 = WGS84_A * (1.0 - e2) / (1.0 - e2 * sin * sin).powf(1.5);
+    let d = (point.x - UTM_FALSE_EASTING) / (n1 * UTM_K0);
+
+    let lat = lat1
+        - (n1 * tan / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lon = central_meridian(zone)
+        + (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+            + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1)
+                * d.powi(5)
+                / 120.0)
+            / cos;
+    Coord {
+        x: lon.to_degrees(),
+        y: lat.to_degrees(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Distance;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::RailwayGraphExt;
+    use crate::railway_algorithms::tests::test_elements;
+    use approx::assert_relative_eq;
+    use geo::coord;
+    use uom::si::length::meter;
+
+    #[test]
+    fn test_utm() {
+        let frankfurt = coord! { x: 8.6821, y: 50.1109 };
+        let crs = Crs::utm_for(frankfurt);
+        assert_eq!(
+            crs,
+            Crs::Utm {
+                zone: 32,
+                north: true
+            }
+        );
+
+        // On the central meridian the northing is the scaled meridian arc.
+        let point = crs.project(coord! { x: 9.0, y: 50.0 });
+        assert_relative_eq!(point.x, 500_000.0, epsilon = 1e-6);
+        assert_relative_eq!(point.y, 0.9996 * 5_540_847.04, epsilon = 1.0);
+
+        let round_trip = crs.unproject(crs.project(frankfurt));
+        assert_relative_eq!(round_trip.x, frankfurt.x, epsilon = 1e-8);
+        assert_relative_eq!(round_trip.y, frankfurt.y, epsilon = 1e-8);
+
+        let south = Crs::utm_for(coord! { x: -77.0, y: -12.0 });
+        let lima = coord! { x: -77.03, y: -12.05 };
+        let round_trip = south.unproject(south.project(lima));
+        assert_relative_eq!(round_trip.x, lima.x, epsilon = 1e-8);
+        assert_relative_eq!(round_trip.y, lima.y, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn test_web_mercator_and_local_tangent_plane() {
+        let location = coord! { x: 8.6921, y: 50.1209 };
+        for crs in [
+            Crs::WebMercator,
+            Crs::LocalTangentPlane {
+                origin: coord! { x: 8.6821, y: 50.1109 },
+            },
+        ] {
+            let round_trip = crs.unproject(crs.project(location));
+            assert_relative_eq!(round_trip.x, location.x, epsilon = 1e-9);
+            assert_relative_eq!(round_trip.y, location.y, epsilon = 1e-9);
+        }
+
+        let origin = coord! { x: 8.6821, y: 50.1109 };
+        let plane = Crs::LocalTangentPlane { origin };
+        let point = plane.project(location);
+        assert_relative_eq!(
+            point.x.hypot(point.y),
+            origin.distance(&location).get::<meter>(),
+            max_relative = 0.005
+        );
+    }
+
+    #[test]
+    fn test_graph_to_crs() {
+        let graph = from_railway_elements(&test_elements());
+        let crs = Crs::utm_for(coord! { x: 8.6821, y: 50.1109 });
+        let projected = graph.to_crs(&crs);
+
+        let node = projected.get_node_by_id(1).unwrap();
+        assert!(node.location.x > 100_000.0 && node.location.y > 5_000_000.0);
+        let edge = projected.get_edge_by_id(4).unwrap();
+        let (start, end) = (edge.path.0[0], *edge.path.0.last().unwrap());
+        assert_relative_eq!(
+            (end.x - start.x).hypot(end.y - start.y),
+            edge.length,
+            max_relative = 0.01
+        );
+
+        let back = projected.to_geographic(&crs);
+        let node = back.get_node_by_id(1).unwrap();
+        assert_relative_eq!(node.location.x, 8.6821, epsilon = 1e-8);
+        assert_relative_eq!(node.location.y, 50.1109, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn test_view_transform() {
+        let min = coord! { x: 0.0, y: 60.0 };
+        let max = coord! { x: 4.0, y: 61.0 };
+        let transform = ViewTransform::fit_local(min, max, 1000.0, 1000.0);
+
+        // At 60° latitude four degrees of longitude are about twice as long as one of latitude.
+        let bottom_left = transform.to_view(min);
+        let top_right = transform.to_view(max);
+        let center = transform.to_view(coord! { x: 2.0, y: 60.5 });
+        assert_relative_eq!(center.x, 500.0, epsilon = 1e-6);
+        assert_relative_eq!(center.y, 500.0, epsilon = 1e-6);
+        let (width, height) = (top_right.x - bottom_left.x, bottom_left.y - top_right.y);
+        assert!(width <= 1000.0 + 1e-6);
+        assert_relative_eq!(height / width, 111_420.0 / 219_820.0, max_relative = 0.01);
+
+        let location = transform.to_geo(coord! { x: 250.0, y: 600.0 });
+        let view = transform.to_view(location);
+        assert_relative_eq!(view.x, 250.0, epsilon = 1e-6);
+        assert_relative_eq!(view.y, 600.0, epsilon = 1e-6);
+        assert_relative_eq!(transform.to_geo(center).x, 2.0, epsilon = 1e-9);
+    }
+}
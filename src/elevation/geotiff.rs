@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use geo::Coord;
+
+use super::ElevationSource;
+
+const IMAGE_WIDTH: u16 = 256;
+const IMAGE_LENGTH: u16 = 257;
+const BITS_PER_SAMPLE: u16 = 258;
+const COMPRESSION: u16 = 259;
+const STRIP_OFFSETS: u16 = 273;
+const SAMPLES_PER_PIXEL: u16 = 277;
+const STRIP_BYTE_COUNTS: u16 = 279;
+const SAMPLE_FORMAT: u16 = 339;
+const MODEL_PIXEL_SCALE: u16 = 33550;
+const MODEL_TIEPOINT: u16 = 33922;
+const GEO_KEY_DIRECTORY: u16 = 34735;
+const GDAL_NODATA: u16 = 42113;
+
+/// GeoKey of the raster type, `1` for pixels covering an area and `2` for point samples.
+const GT_RASTER_TYPE_GEO_KEY: u16 = 1025;
+
+/// A single band GeoTIFF elevation raster in geographic coordinates.
+///
+/// Only uncompressed, strip organised images with one sample per pixel are supported, as written
+/// by `gdal_translate -co COMPRESS=NONE`. Compressed files have to be converted first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoTiffTile {
+    width: usize,
+    height: usize,
+    /// Longitude and latitude of the centre of the first pixel.
+    origin: Coord<f64>,
+    /// Size of a pixel in degrees of longitude and latitude.
+    scale: Coord<f64>,
+    no_data: Option<f64>,
+    values: Vec<f32>,
+}
+
+impl GeoTiffTile {
+    /// Reads a GeoTIFF file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Reads a GeoTIFF from the content of a file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let reader = TiffReader::new(bytes)?;
+        let tags = reader.tags()?;
+        let tag = |id: u16| {
+            tags.get(&id)
+                .ok_or_else(|| anyhow!("missing TIFF tag {}", id))
+        };
+        let first = |id: u16| -> Result<f64> {
+            tag(id)?
+                .first()
+                .copied()
+                .ok_or_else(|| anyhow!("empty TIFF tag {}", id))
+        };
+        let optional = |id: u16| -> Result<Option<f64>> {
+            tags.contains_key(&id).then(|| first(id)).transpose()
+        };
+
+        let width = first(IMAGE_WIDTH)? as usize;
+        let height = first(IMAGE_LENGTH)? as usize;
+        let bits = first(BITS_PER_SAMPLE)? as usize;
+        let format = optional(SAMPLE_FORMAT)?.unwrap_or(1.0) as u16;
+        if width == 0 || height == 0 {
+            return Err(anyhow!("empty GeoTIFF image"));
+        }
+        if !matches!(bits, 8 | 16 | 32 | 64) {
+            return Err(anyhow!("unsupported sample size of {} bits", bits));
+        }
+        if optional(COMPRESSION)?.is_some_and(|compression| compression != 1.0) {
+            return Err(anyhow!("compressed GeoTIFF files are not supported"));
+        }
+        if optional(SAMPLES_PER_PIXEL)?.is_some_and(|samples| samples != 1.0) {
+            return Err(anyhow!("only single band GeoTIFF files are supported"));
+        }
+        let scale = tag(MODEL_PIXEL_SCALE)?;
+        let tiepoint = tag(MODEL_TIEPOINT)?;
+        if scale.len() < 2 || tiepoint.len() < 6 {
+            return Err(anyhow!("invalid GeoTIFF georeferencing"));
+        }
+        let pixel_is_point = tags
+            .get(&GEO_KEY_DIRECTORY)
+            .is_some_and(|keys| geo_key(keys, GT_RASTER_TYPE_GEO_KEY) == Some(2.0));
+        let centre = if pixel_is_point { 0.0 } else { 0.5 };
+        let origin = Coord {
+            x: tiepoint[3] + (centre - tiepoint[0]) * scale[0],
+            y: tiepoint[4] - (centre - tiepoint[1]) * scale[1],
+        };
+        let no_data = reader
+            .ascii(GDAL_NODATA)
+            .and_then(|value| value.trim().parse().ok());
+
+        let mut values = Vec::with_capacity(width * height);
+        let bytes_per_sample = bits / 8;
+        for (offset, count) in tag(STRIP_OFFSETS)?.iter().zip(tag(STRIP_BYTE_COUNTS)?) {
+            let strip = bytes
+                .get(*offset as usize..(*offset + *count) as usize)
+                .ok_or_else(|| anyhow!("TIFF strip out of bounds"))?;
+            for sample in strip.chunks_exact(bytes_per_sample) {
+                values.push(reader.sample(sample, format, bits)? as f32);
+            }
+        }
+        if values.len() < width * height {
+            return Err(anyhow!("GeoTIFF contains fewer samples than pixels"));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            origin,
+            scale: Coord {
+                x: scale[0],
+                y: scale[1],
+            },
+            no_data,
+            values,
+        })
+    }
+
+    fn value(&self, row: usize, col: usize) -> Option<f64> {
+        let value = *self.values.get(row * self.width + col)? as f64;
+        if self.no_data == Some(value) || value.is_nan() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+impl ElevationSource for GeoTiffTile {
+    fn elevation(&self, location: Coord<f64>) -> Option<f64> {
+        let x = (location.x - self.origin.x) / self.scale.x;
+        let y = (self.origin.y - location.y) / self.scale.y;
+        let (max_x, max_y) = ((self.width - 1) as f64, (self.height - 1) as f64);
+        if !(0.0..=max_x).contains(&x) || !(0.0..=max_y).contains(&y) {
+            return None;
+        }
+        let col = (x as usize).min(self.width.saturating_sub(2));
+        let row = (y as usize).min(self.height.saturating_sub(2));
+        let (dx, dy) = (x - col as f64, y - row as f64);
+        let next_col = (col + 1).min(self.width - 1);
+        let next_row = (row + 1).min(self.height - 1);
+
+        let top = self.value(row, col)? * (1.0 - dx) + self.value(row, next_col)? * dx;
+        let bottom = self.value(next_row, col)? * (1.0 - dx) + self.value(next_row, next_col)? * dx;
+        Some(top * (1.0 - dy) + bottom * dy)
+    }
+}
+
+/// Returns the value of a GeoKey from the GeoKeyDirectory, if it is stored inline.
+fn geo_key(directory: &[f64], key: u16) -> Option<f64> {
+    directory
+        .get(4..)?
+        .chunks_exact(4)
+        .find(|entry| entry[0] as u16 == key && entry[1] == 0.0)
+        .map(|entry| entry[3])
+}
+
+/// A minimal reader for the first image file directory of a classic TIFF file.
+struct TiffReader<'a> {
+    bytes: &'a [u8],
+    little_endian: bool,
+    entries: Vec<(u16, u16, u32, [u8; 4])>,
+}
+
+impl<'a> TiffReader<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self> {
+        let little_endian = match bytes.get(0..2) {
+            Some(b"II") => true,
+            Some(b"MM") => false,
+            _ => return Err(anyhow!("not a TIFF file")),
+        };
+        let mut reader = Self {
+            bytes,
+            little_endian,
+            entries: Vec::new(),
+        };
+        if reader.u16_at(2)? != 42 {
+            return Err(anyhow!("only classic TIFF files are supported"));
+        }
+        let ifd = reader.u32_at(4)? as usize;
+        let count = reader.u16_at(ifd)? as usize;
+        for index in 0..count {
+            let entry = ifd + 2 + index * 12;
+            let value = bytes
+                .get(entry + 8..entry + 12)
+                .ok_or_else(|| anyhow!("TIFF directory out of bounds"))?;
+            reader.entries.push((
+                reader.u16_at(entry)?,
+                reader.u16_at(entry + 2)?,
+                reader.u32_at(entry + 4)?,
+                [value[0], value[1], value[2], value[3]],
+            ));
+        }
+        Ok(reader)
+    }
+
+    /// Returns all numeric tags as `f64` values.
+    fn tags(&self) -> Result<HashMap<u16, Vec<f64>>> {
+        let mut tags = HashMap::new();
+        for (tag, field_type, count, value) in &self.entries {
+            let size = match field_type {
+                3 => 2,
+                4 => 4,
+                12 => 8,
+                _ => continue,
+            };
+            let data = self.field_data(size * *count as usize, value)?;
+            let values = data
+                .chunks_exact(size)
+                .map(|chunk| match field_type {
+                    3 => self.u16(chunk) as f64,
+                    4 => self.u32(chunk) as f64,
+                    _ => self.f64(chunk),
+                })
+                .collect();
+            tags.insert(*tag, values);
+        }
+        Ok(tags)
+    }
+
+    /// Returns the value of an ASCII tag.
+    fn ascii(&self, tag: u16) -> Option<String> {
+        let (_, _, count, value) = self
+            .entries
+            .iter()
+            .find(|(id, field_type, _, _)| *id == tag && *field_type == 2)?;
+        let data = self.field_data(*count as usize, value).ok()?;
+        Some(
+            String::from_utf8_lossy(data)
+                .trim_end_matches('\0')
+                .to_string(),
+        )
+    }
+
+    fn field_data<'b>(&'b self, length: usize, value: &'b [u8; 4]) -> Result<&'b [u8]> {
+        if length <= 4 {
+            Ok(&value[..length])
+        } else {
+            let offset = self.u32(value) as usize;
+            self.bytes
+                .get(offset..offset + length)
+                .ok_or_else(|| anyhow!("TIFF tag data out of bounds"))
+        }
+    }
+
+    fn sample(&self, bytes: &[u8], format: u16, bits: usize) -> Result<f64> {
+        Ok(match (format, bits) {
+            (1, 8) => bytes[0] as f64,
+            (2, 8) => bytes[0] as i8 as f64,
+            (1, 16) => self.u16(bytes) as f64,
+            (2, 16) => self.u16(bytes) as i16 as f64,
+            (1, 32) => self.u32(bytes) as f64,
+            (2, 32) => self.u32(bytes) as i32 as f64,
+            (3, 32) => f32::from_bits(self.u32(bytes)) as f64,
+            (3, 64) => self.f64(bytes),
+            _ => {
+                return Err(anyhow!(
+                    "unsupported sample format {} with {} bits",
+                    format,
+                    bits
+                ))
+            }
+        })
+    }
+
+    fn u16_at(&self, offset: usize) -> Result<u16> {
+        self.bytes
+            .get(offset..offset + 2)
+            .map(|bytes| self.u16(bytes))
+            .ok_or_else(|| anyhow!("unexpected end of TIFF file"))
+    }
+
+    fn u32_at(&self, offset: usize) -> Result<u32> {
+        self.bytes
+            .get(offset..offset + 4)
+            .map(|bytes| self.u32(bytes))
+            .ok_or_else(|| anyhow!("unexpected end of TIFF file"))
+    }
+
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
+    }
+
+    fn f64(&self, bytes: &[u8]) -> f64 {
+        let mut array = [0; 8];
+        array.copy_from_slice(&bytes[..8]);
+        if self.little_endian {
+            f64::from_le_bytes(array)
+        } else {
+            f64::from_be_bytes(array)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use geo::coord;
+
+    /// Writes a little-endian 3x2 Int16 GeoTIFF with pixels of 0.5 degrees starting at 8°E 51°N.
+    fn test_tiff() -> Vec<u8> {
+        let pixels: [i16; 6] = [100, 110, 120, 200, 210, -9999];
+        let entries: Vec<(u16, u16, u32, Vec<u8>)> = vec![
+            (IMAGE_WIDTH, 3, 1, 3u16.to_le_bytes().to_vec()),
+            (IMAGE_LENGTH, 3, 1, 2u16.to_le_bytes().to_vec()),
+            (BITS_PER_SAMPLE, 3, 1, 16u16.to_le_bytes().to_vec()),
+            (COMPRESSION, 3, 1, 1u16.to_le_bytes().to_vec()),
+            (STRIP_OFFSETS, 4, 1, Vec::new()),
+            (SAMPLES_PER_PIXEL, 3, 1, 1u16.to_le_bytes().to_vec()),
+            (STRIP_BYTE_COUNTS, 4, 1, 12u32.to_le_bytes().to_vec()),
+            (SAMPLE_FORMAT, 3, 1, 2u16.to_le_bytes().to_vec()),
+            (
+                MODEL_PIXEL_SCALE,
+                12,
+                3,
+                [0.5f64, 0.5, 0.0]
+                    .iter()
+                    .flat_map(|v| v.to_le_bytes())
+                    .collect(),
+            ),
+            (
+                MODEL_TIEPOINT,
+                12,
+                6,
+                [0.0f64, 0.0, 0.0, 8.0, 51.0, 0.0]
+                    .iter()
+                    .flat_map(|v| v.to_le_bytes())
+                    .collect(),
+            ),
+            (GDAL_NODATA, 2, 6, b"-9999\0".to_vec()),
+        ];
+
+        let ifd_offset = 8;
+        let mut data_offset = ifd_offset + 2 + entries.len() * 12 + 4;
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+        let strip_offset = data_offset + 24 + 48 + 6;
+        for (tag, field_type, count, mut value) in entries {
+            if tag == STRIP_OFFSETS {
+                value = (strip_offset as u32).to_le_bytes().to_vec();
+            }
+            directory.extend(tag.to_le_bytes());
+            directory.extend(field_type.to_le_bytes());
+            directory.extend(count.to_le_bytes());
+            if value.len() <= 4 {
+                value.resize(4, 0);
+                directory.extend(value);
+            } else {
+                directory.extend((data_offset as u32).to_le_bytes());
+                data_offset += value.len();
+                data.extend(value);
+            }
+        }
+
+        let mut bytes = b"II".to_vec();
+        bytes.extend(42u16.to_le_bytes());
+        bytes.extend((ifd_offset as u32).to_le_bytes());
+        bytes.extend(11u16.to_le_bytes());
+        bytes.extend(directory);
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(data);
+        assert_eq!(bytes.len(), strip_offset);
+        bytes.extend(pixels.iter().flat_map(|p| p.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn test_geotiff_tile() {
+        let tile = GeoTiffTile::from_bytes(&test_tiff()).unwrap();
+
+        // Pixel centres lie at 8.25, 8.75, 9.25 east and 50.75, 50.25 north.
+        assert_relative_eq!(tile.elevation(coord! { x: 8.25, y: 50.75 }).unwrap(), 100.0);
+        assert_relative_eq!(tile.elevation(coord! { x: 8.5, y: 50.5 }).unwrap(), 155.0);
+        assert_eq!(tile.elevation(coord! { x: 9.0, y: 50.5 }), None);
+        assert_eq!(tile.elevation(coord! { x: 7.0, y: 50.5 }), None);
+    }
+
+    #[test]
+    fn test_malformed_geotiff() {
+        let tiff = test_tiff();
+        // The value of the first directory entry, the image width.
+        let width = 8 + 2 + 8;
+        // The count of the eighth directory entry, the sample format.
+        let format_count = 8 + 2 + 7 * 12 + 4;
+        // The value of the third directory entry, the bits per sample.
+        let bits = 8 + 2 + 2 * 12 + 8;
+
+        let mut zero_sized = tiff.clone();
+        zero_sized[width..width + 2].copy_from_slice(&0u16.to_le_bytes());
+        assert!(GeoTiffTile::from_bytes(&zero_sized).is_err());
+
+        let mut empty_tag = tiff.clone();
+        empty_tag[format_count..format_count + 4].copy_from_slice(&0u32.to_le_bytes());
+        assert!(GeoTiffTile::from_bytes(&empty_tag).is_err());
+
+        let mut small_samples = tiff.clone();
+        small_samples[bits..bits + 2].copy_from_slice(&4u16.to_le_bytes());
+        assert!(GeoTiffTile::from_bytes(&small_samples).is_err());
+
+        assert!(GeoTiffTile::from_bytes(&tiff[..tiff.len() - 4]).is_err());
+        assert!(GeoTiffTile::from_bytes(&tiff[..40]).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use geo::Coord;
+
+use super::ElevationSource;
+
+/// Value used by SRTM for cells without data.
+const VOID: i16 = -32768;
+
+/// A single SRTM `.hgt` tile covering one degree of latitude and longitude.
+///
+/// The tile is a square grid of big-endian 16 bit elevations in meters, starting at the north
+/// west corner. SRTM1 tiles have 3601 and SRTM3 tiles 1201 samples per row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HgtTile {
+    /// Latitude of the south west corner.
+    pub lat: i32,
+    /// Longitude of the south west corner.
+    pub lon: i32,
+    size: usize,
+    heights: Vec<i16>,
+}
+
+impl HgtTile {
+    /// Reads a tile from a file named after its south west corner, e.g. `N50E008.hgt`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("invalid file name {}", path.display()))?;
+        let (lat, lon) = parse_tile_name(name)?;
+        Self::from_bytes(lat, lon, &std::fs::read(path)?)
+    }
+
+    /// Creates a tile from the raw content of a `.hgt` file.
+    ///
+    /// # Arguments
+    ///
+    /// * `lat` - Latitude of the south west corner.
+    /// * `lon` - Longitude of the south west corner.
+    /// * `bytes` - The big-endian elevations of the square grid.
+    pub fn from_bytes(lat: i32, lon: i32, bytes: &[u8]) -> Result<Self> {
+        let size = ((bytes.len() / 2) as f64).sqrt() as usize;
+        if size < 2 || size * size * 2 != bytes.len() {
+            return Err(anyhow!("invalid hgt tile size of {} bytes", bytes.len()));
+        }
+        let heights = bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        Ok(Self {
+            lat,
+            lon,
+            size,
+            heights,
+        })
+    }
+
+    fn height(&self, row: usize, col: usize) -> Option<f64> {
+        match self.heights[row * self.size + col] {
+            VOID => None,
+            height => Some(height as f64),
+        }
+    }
+}
+
+impl ElevationSource for HgtTile {
+    fn elevation(&self, location: Coord<f64>) -> Option<f64> {
+        let cells = (self.size - 1) as f64;
+        let x = (location.x - self.lon as f64) * cells;
+        let y = (self.lat as f64 + 1.0 - location.y) * cells;
+        if !(0.0..=cells).contains(&x) || !(0.0..=cells).contains(&y) {
+            return None;
+        }
+        let (col, row) = (
+            (x as usize).min(self.size - 2),
+            (y as usize).min(self.size - 2),
+        );
+        let (dx, dy) = (x - col as f64, y - row as f64);
+
+        let top = self.height(row, col)? * (1.0 - dx) + self.height(row, col + 1)? * dx;
+        let bottom = self.height(row + 1, col)? * (1.0 - dx) + self.height(row + 1, col + 1)? * dx;
+        Some(top * (1.0 - dy) + bottom * dy)
+    }
+}
+
+/// Parses tile names such as `N50E008` or `S12W077` into the south west corner.
+fn parse_tile_name(name: &str) -> Result<(i32, i32)> {
+    let invalid = || anyhow!("invalid hgt tile name {}", name);
+    let name = name.to_ascii_uppercase();
+    let lon_start = name.find(['E', 'W']).ok_or_else(invalid)?;
+    let (lat_part, lon_part) = name.split_at(lon_start);
+
+    let sign = |part: &str, negative: char| if part.starts_with(negative) { -1 } else { 1 };
+    let lat: i32 = lat_part
+        .get(1..)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(invalid)?;
+    let lon: i32 = lon_part
+        .get(1..)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(invalid)?;
+    if !lat_part.starts_with(['N', 'S']) {
+        return Err(invalid());
+    }
+    Ok((lat * sign(lat_part, 'S'), lon * sign(lon_part, 'W')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use geo::coord;
+
+    #[test]
+    fn test_hgt_tile() {
+        // 3x3 grid, north row first.
+        let heights: [i16; 9] = [120, 130, 140, 110, 120, 130, 100, 110, VOID];
+        let bytes: Vec<u8> = heights.iter().flat_map(|h| h.to_be_bytes()).collect();
+        let tile = HgtTile::from_bytes(50, 8, &bytes).unwrap();
+
+        assert_relative_eq!(tile.elevation(coord! { x: 8.0, y: 51.0 }).unwrap(), 120.0);
+        assert_relative_eq!(tile.elevation(coord! { x: 8.25, y: 50.75 }).unwrap(), 120.0);
+        assert_relative_eq!(tile.elevation(coord! { x: 8.0, y: 50.25 }).unwrap(), 105.0);
+        assert_eq!(tile.elevation(coord! { x: 8.75, y: 50.25 }), None);
+        assert_eq!(tile.elevation(coord! { x: 9.5, y: 50.5 }), None);
+
+        assert_eq!(parse_tile_name("N50E008").unwrap(), (50, 8));
+        assert_eq!(parse_tile_name("s12w077").unwrap(), (-12, -77));
+        assert!(parse_tile_name("dem").is_err());
+    }
+}
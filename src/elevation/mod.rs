@@ -0,0 +1,223 @@
+//! Module `elevation` samples digital elevation models along the edges of a `RailwayGraph`.
+//!
+//! Elevation data is read offline from SRTM `.hgt` tiles or uncompressed GeoTIFF files on disk.
+//! The `RailwayGraphElevation` trait samples the elevation along every edge and stores the
+//! profiles in a `RailwayElevation` table, from which gradients per segment and per edge are
+//! derived. The simulation takes the gradients into account when set as the `elevation` of its
+//! environment.
+use std::path::Path;
+
+use anyhow::Result;
+use geo::Coord;
+use uom::si::{f64::Length, length::meter};
+
+use crate::algorithms::Distance;
+use crate::prelude::{
+    ElevationProfile, ElevationSample, RailwayEdge, RailwayElevation, RailwayGraph,
+};
+use crate::railway_algorithms::RailwayEdgeAlgos;
+
+mod geotiff;
+mod hgt;
+
+pub use geotiff::GeoTiffTile;
+pub use hgt::HgtTile;
+
+/// A source of elevation data.
+pub trait ElevationSource {
+    /// Returns the elevation in meters at the given location, or `None` if it is not covered.
+    fn elevation(&self, location: Coord<f64>) -> Option<f64>;
+}
+
+/// A collection of elevation tiles loaded from a directory.
+#[derive(Default)]
+pub struct DemTiles {
+    tiles: Vec<Box<dyn ElevationSource + Send + Sync>>,
+}
+
+impl DemTiles {
+    /// Loads all `.hgt`, `.tif` and `.tiff` files of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory containing the elevation tiles.
+    ///
+    /// # Returns
+    ///
+    /// The loaded tiles, or an error if a file could not be read.
+    pub fn open(directory: impl AsRef<Path>) -> Result<Self> {
+        let mut tiles = Self::default();
+        let mut paths: Vec<_> = std::fs::read_dir(directory)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        paths.sort();
+
+        for path in paths {
+            let extension = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_ascii_lowercase);
+            match extension.as_deref() {
+                Some("hgt") => tiles.add(HgtTile::from_file(&path)?),
+                Some("tif") | Some("tiff") => tiles.add(GeoTiffTile::from_file(&path)?),
+                _ => {}
+            }
+        }
+        Ok(tiles)
+    }
+
+    /// Adds a tile to the collection.
+    pub fn add(&mut self, tile: impl ElevationSource + Send + Sync + 'static) {
+        self.tiles.push(Box::new(tile));
+    }
+
+    /// Returns the number of tiles.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns `true` if no tiles are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+impl ElevationSource for DemTiles {
+    fn elevation(&self, location: Coord<f64>) -> Option<f64> {
+        self.tiles.iter().find_map(|tile| tile.elevation(location))
+    }
+}
+
+/// Elevation sampling for railway graphs.
+pub trait RailwayGraphElevation {
+    /// Samples the elevation along every edge of the graph.
+    ///
+    /// Samples are taken at every vertex of the edge's geometry and at most `spacing` apart in
+    /// between. Edges with a sample outside of the elevation data are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The elevation data.
+    /// * `spacing` - The maximum distance between two samples.
+    ///
+    /// # Returns
+    ///
+    /// A `RailwayElevation` with the elevation profiles of the edges.
+    fn elevation_profiles(&self, source: &dyn ElevationSource, spacing: Length)
+        -> RailwayElevation;
+}
+
+impl RailwayGraphElevation for RailwayGraph {
+    fn elevation_profiles(
+        &self,
+        source: &dyn ElevationSource,
+        spacing: Length,
+    ) -> RailwayElevation {
+        let edges = self
+            .physical_graph
+            .graph
+            .edge_weights()
+            .filter_map(|edge| Some((edge.id, elevation_profile(edge, source, spacing)?)))
+            .collect();
+        RailwayElevation { edges }
+    }
+}
+
+/// Samples the elevation along a single edge.
+pub fn elevation_profile(
+    edge: &RailwayEdge,
+    source: &dyn ElevationSource,
+    spacing: Length,
+) -> Option<ElevationProfile> {
+    let spacing = spacing.get::<meter>();
+    let mut offsets = vec![0.0];
+    let mut vertex_offset = 0.0;
+    for pair in edge.path.0.windows(2) {
+        let segment = pair[0].distance(&pair[1]).get::<meter>();
+        let steps = if spacing > 0.0 {
+            (segment / spacing).ceil().max(1.0) as usize
+        } else {
+            1
+        };
+        offsets
+            .extend((1..=steps).map(|step| vertex_offset + segment * step as f64 / steps as f64));
+        vertex_offset += segment;
+    }
+
+    let samples = offsets
+        .into_iter()
+        .map(|offset| {
+            let location = edge.coordinate_at(Length::new::<meter>(offset));
+            source
+                .elevation(location)
+                .map(|elevation| ElevationSample { offset, elevation })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(ElevationProfile { samples })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::test_elements;
+    use approx::assert_relative_eq;
+
+    /// A plane rising by one meter per thousandth degree of latitude.
+    struct Slope;
+
+    impl ElevationSource for Slope {
+        fn elevation(&self, location: Coord<f64>) -> Option<f64> {
+            Some(100.0 + (location.y - 50.0) * 1000.0)
+        }
+    }
+
+    #[test]
+    fn test_elevation_profiles() {
+        let graph = from_railway_elements(&test_elements());
+        let elevation = graph.elevation_profiles(&Slope, Length::new::<meter>(100.0));
+
+        let profile = elevation.profile(4).unwrap();
+        let edge = graph
+            .physical_graph
+            .graph
+            .edge_weights()
+            .find(|e| e.id == 4)
+            .unwrap();
+        assert!(profile.samples.len() > edge.path.0.len());
+        assert_relative_eq!(profile.samples[0].elevation, 210.9, epsilon = 0.001);
+        assert_relative_eq!(
+            profile.samples.last().unwrap().elevation,
+            220.9,
+            epsilon = 0.001
+        );
+        assert_relative_eq!(
+            elevation.average_gradient(4).unwrap(),
+            10.0 / edge.path_length().get::<meter>(),
+            epsilon = 1e-6
+        );
+        assert!(profile.gradients().iter().all(|gradient| *gradient > 0.0));
+    }
+
+    #[test]
+    fn test_dem_tiles_open() {
+        let directory = std::env::temp_dir().join(format!(
+            "openrailwaymap_exporter_test_dem_tiles_open_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        let bytes: Vec<u8> = [100i16, 100, 200, 200]
+            .iter()
+            .flat_map(|h| h.to_be_bytes())
+            .collect();
+        std::fs::write(directory.join("N50E008.hgt"), bytes).unwrap();
+        std::fs::write(directory.join("readme.txt"), "not a tile").unwrap();
+
+        let tiles = DemTiles::open(&directory);
+        std::fs::remove_dir_all(&directory).unwrap();
+        let tiles = tiles.unwrap();
+        assert_eq!(tiles.len(), 1);
+        assert_relative_eq!(tiles.elevation(Coord { x: 8.5, y: 50.5 }).unwrap(), 150.0);
+        assert_eq!(tiles.elevation(Coord { x: 10.0, y: 50.5 }), None);
+    }
+}
@@ -3,7 +3,9 @@
 //! This module provides functions to generate a DOT or SVG string representation of a RailwayGraph.
 //! The generated strings can be used to visualize the railway infrastructure data.
 
-pub use crate::exporter::svg::generate_svg_string;
+pub use crate::exporter::svg::{
+    generate_heat_map_svg_string, generate_reachability_svg_string, generate_svg_string,
+};
 use crate::prelude::RailwayGraph;
 use petgraph::dot::{Config, Dot};
 use std::error::Error;
@@ -155,4 +157,34 @@ mod tests {
         assert!(svg_string.contains("<circle"));
         assert!(svg_string.contains("<path"));
     }
+
+    #[test]
+    fn test_generate_reachability_svg_string() {
+        use crate::railway_algorithms::{
+            tests::siding_elements, RailwayGraphAlgos, ReachabilityBudget,
+        };
+        use uom::si::{f64::Length, length::meter};
+
+        let railway_graph = from_railway_elements(&siding_elements());
+        let reachability = railway_graph.reachable_within(
+            10,
+            &ReachabilityBudget::Distance(Length::new::<meter>(1000.0)),
+        );
+        let svg_string = generate_reachability_svg_string(&railway_graph, &reachability).unwrap();
+
+        assert!(svg_string.contains("stroke=\"green\""));
+        assert!(svg_string.contains("stroke=\"black\""));
+    }
+
+    #[test]
+    fn test_generate_heat_map_svg_string() {
+        use crate::railway_algorithms::{tests::siding_elements, RailwayGraphResilience};
+
+        let railway_graph = from_railway_elements(&siding_elements());
+        let betweenness = railway_graph.edge_betweenness();
+        let svg_string = generate_heat_map_svg_string(&railway_graph, &betweenness).unwrap();
+
+        assert!(svg_string.contains("stroke=\"rgb(255,0,0)\""));
+        assert!(svg_string.contains("stroke=\"rgb(0,255,0)\""));
+    }
 }
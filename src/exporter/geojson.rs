@@ -0,0 +1,317 @@
+//! Export of railway data as GeoJSON feature collections.
+//!
+//! Coordinates are written as longitude and latitude in WGS 84, as GeoJSON requires.
+use geo::{Coord, LineString};
+use serde_json::{json, Map, Value};
+
+use crate::map_matching::MapMatch;
+use crate::prelude::{GraphDiff, RailwayGraph, RailwayGraphExt, TrackDirection};
+use crate::railway_algorithms::Reachability;
+use uom::si::length::meter;
+
+/// Builds a GeoJSON `Point` geometry.
+pub fn point_geometry(coord: Coord<f64>) -> Value {
+    json!({ "type": "Point", "coordinates": [coord.x, coord.y] })
+}
+
+/// Builds a GeoJSON `LineString` geometry.
+pub fn line_string_geometry(line: &LineString<f64>) -> Value {
+    let coordinates: Vec<[f64; 2]> = line.coords().map(|coord| [coord.x, coord.y]).collect();
+    json!({ "type": "LineString", "coordinates": coordinates })
+}
+
+/// Builds a GeoJSON `Feature` from a geometry and its properties.
+pub fn feature(geometry: Value, properties: Map<String, Value>) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+/// Builds a GeoJSON `FeatureCollection`.
+pub fn feature_collection(features: Vec<Value>) -> Value {
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+/// Converts the result of a reachability search into a GeoJSON feature collection.
+///
+/// Every reached edge or part of an edge becomes a `LineString` feature with its `edge_id`,
+/// the `from` node, the `cost` to reach it, the reached `length` and whether it is `complete`.
+/// Every reached node becomes a `Point` feature with its `node_id` and `cost`.
+///
+/// # Arguments
+///
+/// * `graph` - The `RailwayGraph` the search ran on, used for the node locations.
+/// * `reachability` - The result of `RailwayGraphAlgos::reachable_within`.
+///
+/// # Returns
+///
+/// A `serde_json::Value` holding the feature collection.
+pub fn reachability_to_geojson(graph: &RailwayGraph, reachability: &Reachability) -> Value {
+    let edges = reachability.edges.iter().map(|edge| {
+        let mut properties = Map::new();
+        properties.insert("edge_id".to_string(), json!(edge.edge_id));
+        properties.insert("from".to_string(), json!(edge.from));
+        properties.insert("cost".to_string(), json!(edge.cost));
+        properties.insert("length".to_string(), json!(edge.length));
+        properties.insert("complete".to_string(), json!(edge.complete));
+        feature(line_string_geometry(&edge.path), properties)
+    });
+    let nodes = reachability.nodes.iter().filter_map(|node| {
+        let location = graph.get_node_by_id(node.node_id)?.location;
+        let mut properties = Map::new();
+        properties.insert("node_id".to_string(), json!(node.node_id));
+        properties.insert("cost".to_string(), json!(node.cost));
+        Some(feature(point_geometry(location), properties))
+    });
+    feature_collection(edges.chain(nodes).collect())
+}
+
+/// Converts the result of a map matching into a GeoJSON feature collection.
+///
+/// Every edge of the matched path becomes a `LineString` feature with its `edge_id` and its
+/// `index` in the path. Every matched fix becomes a `Point` feature at its position on the track
+/// with the `fix` index in the trace, the `edge_id`, the `offset` and the `distance` to the
+/// recorded location in meters and whether it moved `forward` along the edge.
+///
+/// # Arguments
+///
+/// * `graph` - The `RailwayGraph` the trace was matched onto, used for the edge geometries.
+/// * `map_match` - The result of `RailwayGraphMapMatching::match_trace`.
+///
+/// # Returns
+///
+/// A `serde_json::Value` holding the feature collection.
+pub fn map_match_to_geojson(graph: &RailwayGraph, map_match: &MapMatch) -> Value {
+    let edges = map_match
+        .edges
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &edge_id)| {
+            let edge = graph.get_edge_by_id(edge_id)?;
+            let mut properties = Map::new();
+            properties.insert("edge_id".to_string(), json!(edge_id));
+            properties.insert("index".to_string(), json!(index));
+            Some(feature(line_string_geometry(&edge.path), properties))
+        });
+    let fixes = map_match
+        .fixes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, fix)| {
+            let fix = fix.as_ref()?;
+            let mut properties = Map::new();
+            properties.insert("fix".to_string(), json!(index));
+            properties.insert("edge_id".to_string(), json!(fix.position.edge_id));
+            properties.insert(
+                "offset".to_string(),
+                json!(fix.position.offset.get::<meter>()),
+            );
+            properties.insert("distance".to_string(), json!(fix.distance.get::<meter>()));
+            properties.insert(
+                "forward".to_string(),
+                json!(fix.position.direction == TrackDirection::Forward),
+            );
+            Some(feature(point_geometry(fix.point), properties))
+        });
+    feature_collection(edges.chain(fixes).collect())
+}
+
+/// Converts a diff of two railway graphs into a GeoJSON feature collection.
+///
+/// Every changed edge becomes a `LineString` feature with its `change` (`added`, `removed` or
+/// `modified`), the `edge_id`, its `length` and the `length_delta` in meters and whether its
+/// geometry moved, its length changed or its tags changed. Removed edges keep their old geometry.
+/// Every changed node becomes a `Point` feature with its `change`, the `node_id` and whether it
+/// `moved`. Edges and nodes that moved get a second feature with their old geometry, marked by
+/// the `previous` property, which is `false` on all other features. The `summary` of the diff
+/// is added as a member of the feature collection.
+///
+/// # Arguments
+///
+/// * `diff` - The result of `RailwayGraphDiff::diff`.
+///
+/// # Returns
+///
+/// A `serde_json::Value` holding the feature collection.
+pub fn graph_diff_to_geojson(diff: &GraphDiff) -> Value {
+    let edges = diff.edges.iter().flat_map(|change| {
+        let mut properties = Map::new();
+        properties.insert("change".to_string(), json!(change.kind));
+        properties.insert("edge_id".to_string(), json!(change.edge_id));
+        properties.insert("previous".to_string(), json!(false));
+        properties.insert("length".to_string(), json!(change.length));
+        properties.insert("length_delta".to_string(), json!(change.length_delta()));
+        properties.insert("geometry_moved".to_string(), json!(change.geometry_moved()));
+        properties.insert("length_changed".to_string(), json!(change.length_changed()));
+        properties.insert("tags_changed".to_string(), json!(change.tags_changed()));
+        properties.insert("changed_tags".to_string(), json!(change.changed_tags));
+        let previous = change.previous_path.as_ref().map(|previous_path| {
+            let mut properties = Map::new();
+            properties.insert("change".to_string(), json!(change.kind));
+            properties.insert("edge_id".to_string(), json!(change.edge_id));
+            properties.insert("previous".to_string(), json!(true));
+            properties.insert(
+                "length".to_string(),
+                json!(change.previous_length.unwrap_or(change.length)),
+            );
+            feature(line_string_geometry(previous_path), properties)
+        });
+        std::iter::once(feature(line_string_geometry(&change.path), properties)).chain(previous)
+    });
+    let nodes = diff.nodes.iter().flat_map(|change| {
+        let mut properties = Map::new();
+        properties.insert("change".to_string(), json!(change.kind));
+        properties.insert("node_id".to_string(), json!(change.node_id));
+        properties.insert("previous".to_string(), json!(false));
+        properties.insert("moved".to_string(), json!(change.moved()));
+        properties.insert("changed_tags".to_string(), json!(change.changed_tags));
+        let previous = change.previous_location.map(|previous_location| {
+            let mut properties = Map::new();
+            properties.insert("change".to_string(), json!(change.kind));
+            properties.insert("node_id".to_string(), json!(change.node_id));
+            properties.insert("previous".to_string(), json!(true));
+            feature(point_geometry(previous_location), properties)
+        });
+        std::iter::once(feature(point_geometry(change.location), properties)).chain(previous)
+    });
+    let mut collection = feature_collection(edges.chain(nodes).collect());
+    collection["summary"] = json!(diff.summary);
+    collection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::{
+        tests::siding_elements, RailwayGraphAlgos, ReachabilityBudget,
+    };
+    use uom::si::f64::Length;
+
+    #[test]
+    fn test_reachability_to_geojson() {
+        let graph = from_railway_elements(&siding_elements());
+        let length_20 = graph.get_edge_by_id(20).unwrap().length;
+        let reachability = graph.reachable_within(
+            10,
+            &ReachabilityBudget::Distance(Length::new::<meter>(length_20 + 100.0)),
+        );
+
+        let geojson = reachability_to_geojson(&graph, &reachability);
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+
+        let partial = features
+            .iter()
+            .find(|feature| feature["properties"]["complete"] == false)
+            .unwrap();
+        assert_eq!(partial["geometry"]["type"], "LineString");
+        assert_eq!(partial["properties"]["edge_id"], 21);
+        assert_eq!(partial["properties"]["from"], 11);
+        assert_eq!(partial["geometry"]["coordinates"][0][0], 8.01);
+
+        let start = features
+            .iter()
+            .find(|feature| feature["properties"]["node_id"] == 10)
+            .unwrap();
+        assert_eq!(start["geometry"]["coordinates"], json!([8.0, 50.0]));
+        assert_eq!(start["properties"]["cost"], 0.0);
+    }
+
+    #[test]
+    fn test_map_match_to_geojson() {
+        use crate::map_matching::{GpsFix, MapMatchingOptions, RailwayGraphMapMatching};
+
+        let graph = from_railway_elements(&siding_elements());
+        let trace = [
+            GpsFix {
+                time: Some(0.0),
+                location: Coord {
+                    x: 8.005,
+                    y: 50.0001,
+                },
+            },
+            GpsFix {
+                time: Some(60.0),
+                location: Coord {
+                    x: 8.015,
+                    y: 49.9999,
+                },
+            },
+        ];
+        let map_match = graph.match_trace(&trace, &MapMatchingOptions::default());
+
+        let geojson = map_match_to_geojson(&graph, &map_match);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+        assert_eq!(features[0]["properties"]["edge_id"], 20);
+        assert_eq!(features[1]["properties"]["edge_id"], 21);
+        assert_eq!(features[1]["properties"]["index"], 1);
+        let fix = &features[3];
+        assert_eq!(fix["geometry"]["type"], "Point");
+        assert_eq!(fix["properties"]["fix"], 1);
+        assert_eq!(fix["properties"]["edge_id"], 21);
+        assert_eq!(fix["properties"]["forward"], true);
+    }
+
+    #[test]
+    fn test_graph_diff_to_geojson() {
+        use crate::prelude::{DiffOptions, RailwayGraphDiff};
+        use crate::railway_algorithms::tests::{node, way};
+
+        let old = from_railway_elements(&siding_elements());
+        let elements: Vec<_> = siding_elements()
+            .into_iter()
+            .filter(|element| element.id != 23 && element.id != 14)
+            .collect();
+        let new = from_railway_elements(&elements);
+        let length_23 = old.get_edge_by_id(23).unwrap().length;
+
+        let diff = old.diff(&new, &DiffOptions::default());
+        let geojson = graph_diff_to_geojson(&diff);
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+        assert_eq!(features[0]["properties"]["change"], "removed");
+        assert_eq!(features[0]["properties"]["edge_id"], 23);
+        assert_eq!(features[0]["properties"]["length_delta"], -length_23);
+        assert_eq!(features[1]["geometry"]["type"], "Point");
+        assert_eq!(features[1]["properties"]["change"], "removed");
+        assert_eq!(geojson["summary"]["removed_edges"], 1);
+        assert_eq!(geojson["summary"]["net_length"], -length_23);
+        assert!(features
+            .iter()
+            .all(|feature| feature["properties"]["previous"] == false));
+
+        // Moving node 14 moves it and the geometry of edge 23, so both keep their old geometry.
+        let elements: Vec<_> = siding_elements()
+            .into_iter()
+            .filter(|element| element.id != 23 && element.id != 14)
+            .chain([
+                node(14, 8.03, 49.998),
+                way(23, &[(12, 8.02, 50.0), (14, 8.03, 49.998)]),
+            ])
+            .collect();
+        let moved = from_railway_elements(&elements);
+        let diff = old.diff(&moved, &DiffOptions::default());
+        let geojson = graph_diff_to_geojson(&diff);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+        let previous: Vec<_> = features
+            .iter()
+            .filter(|feature| feature["properties"]["previous"] == true)
+            .collect();
+        assert_eq!(previous.len(), 2);
+        assert_eq!(previous[0]["properties"]["edge_id"], 23);
+        assert_eq!(previous[0]["properties"]["length"], length_23);
+        assert_eq!(
+            previous[0]["geometry"]["coordinates"][1],
+            json!([8.03, 49.999])
+        );
+        assert_eq!(previous[1]["properties"]["node_id"], 14);
+        assert_eq!(
+            previous[1]["geometry"]["coordinates"],
+            json!([8.03, 49.999])
+        );
+    }
+}
@@ -1,7 +1,9 @@
 //! A module containing exporters to various formats for `RailwayGraph`.
 //!
 //! This module provides the necessary components to export a `RailwayGraph` to different formats,
-//! such as SVG. It may be expanded in the future to support more formats.
+//! such as SVG and GeoJSON. It may be expanded in the future to support more formats.
 
+/// Export railway data to GeoJSON
+pub mod geojson;
 /// Export a RailwayGraph to SVG
 pub mod svg;
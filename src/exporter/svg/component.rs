@@ -1,12 +1,17 @@
 //! A module containing the `SvgComponent`, which renders a `RailwayGraph` as an SVG.
 use super::{edge::SvgEdge, node::SvgNode};
+use crate::crs::ViewTransform;
 use crate::prelude::RailwayEdge;
 use crate::prelude::RailwayGraph;
 use crate::prelude::RailwayGraphExt;
+use crate::prelude::{ChangeKind, EdgeChange, GraphDiff};
 use crate::railway_algorithms::PathFinding;
-use crate::types::NodeId;
+use crate::railway_algorithms::Reachability;
+use crate::types::{EdgeId, NodeId};
+use geo::{coord, LineString};
 use petgraph::visit::IntoNodeReferences;
 use petgraph::visit::NodeRef;
+use std::collections::HashMap;
 use yew::prelude::*;
 
 /// Properties for the `SvgComponent`.
@@ -24,6 +29,38 @@ pub struct Props {
     pub start_node_id: Option<NodeId>,
     /// The ending node ID for pathfinding.
     pub end_node_id: Option<NodeId>,
+    /// The reachable part of the network to highlight.
+    #[prop_or_default]
+    pub reachability: Option<Reachability>,
+    /// Values per edge, e.g. the edge betweenness, to draw as a heat map from green for the
+    /// lowest to red for the highest value.
+    #[prop_or_default]
+    pub edge_values: Option<HashMap<EdgeId, f64>>,
+    /// The changes to another snapshot of the network to draw as an overlay, with added edges in
+    /// green, removed edges in red and otherwise modified edges in orange. An edge whose geometry
+    /// moved is drawn at its old position in red and at its new position in green.
+    #[prop_or_default]
+    pub diff: Option<GraphDiff>,
+}
+
+/// Maps a share between 0 and 1 to a color from green over yellow to red.
+fn heat_color(share: f64) -> String {
+    let share = share.clamp(0.0, 1.0);
+    let red = (510.0 * share).min(255.0).round();
+    let green = (510.0 * (1.0 - share)).min(255.0).round();
+    format!("rgb({},{},0)", red, green)
+}
+
+/// Returns the paths of a changed edge with the color to draw each in.
+fn diff_paths(change: &EdgeChange) -> Vec<(&LineString, &'static str)> {
+    match (change.kind, &change.previous_path) {
+        (ChangeKind::Added, _) => vec![(&change.path, "green")],
+        (ChangeKind::Removed, _) => vec![(&change.path, "red")],
+        (ChangeKind::Modified, Some(previous)) => {
+            vec![(previous, "red"), (&change.path, "green")]
+        }
+        (ChangeKind::Modified, None) => vec![(&change.path, "orange")],
+    }
 }
 
 /// A component that renders a `RailwayGraph` as an SVG.
@@ -43,9 +80,24 @@ impl Component for SvgComponent {
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         if let Some(graph) = ctx.props().graph.as_ref() {
-            let (min_coord, max_coord) = graph.bounding_box();
-            let scale_x = ctx.props().view_width / (max_coord.x - min_coord.x);
-            let scale_y = ctx.props().view_height / (max_coord.y - min_coord.y);
+            let (mut min_coord, mut max_coord) = graph.bounding_box();
+            // Removed edges may lie outside the rendered graph.
+            for coord in ctx
+                .props()
+                .diff
+                .iter()
+                .flat_map(|diff| diff.edges.iter())
+                .flat_map(|change| change.path.coords())
+            {
+                min_coord = coord! { x: min_coord.x.min(coord.x), y: min_coord.y.min(coord.y) };
+                max_coord = coord! { x: max_coord.x.max(coord.x), y: max_coord.y.max(coord.y) };
+            }
+            let transform = ViewTransform::fit_local(
+                min_coord,
+                max_coord,
+                ctx.props().view_width,
+                ctx.props().view_height,
+            );
 
             let svg_edges: Vec<Html> = graph
                 .physical_graph.graph
@@ -54,7 +106,7 @@ impl Component for SvgComponent {
                     let edge_data = edge.weight();
 
                     html! {
-                        <SvgEdge edge={edge_data.clone()} scale_x={scale_x} scale_y={scale_y} min_coord={(min_coord.x, min_coord.y)} view_height={ctx.props().view_height} />
+                        <SvgEdge edge={edge_data.clone()} transform={transform} />
                     }
                 })
                 .collect();
@@ -66,8 +118,7 @@ impl Component for SvgComponent {
                     let node_data = node.weight();
 
                     html! {
-                        <SvgNode node={*node_data} scale_x={scale_x} scale_y={scale_y}
-                         min_coord={(min_coord.x, min_coord.y)} view_height={ctx.props().view_height}
+                        <SvgNode node={*node_data} transform={transform}
                          on_select={ctx.props().on_select_node.clone()} />
                     }
                 })
@@ -104,19 +155,91 @@ impl Component for SvgComponent {
                     html! {
                         <SvgEdge
                             edge={edge_data.clone()}
-                            scale_x={scale_x}
-                            scale_y={scale_y}
-                            min_coord={(min_coord.x, min_coord.y)}
-                            view_height={ctx.props().view_height}
+                            transform={transform}
                             stroke_color={Some("red".to_string())}
                         />
                     }
                 })
                 .collect();
 
+            let reached_edges: Vec<Html> = ctx
+                .props()
+                .reachability
+                .iter()
+                .flat_map(|reachability| reachability.edges.iter())
+                .filter_map(|reached| {
+                    let edge = graph.get_edge_by_id(reached.edge_id)?;
+                    let edge = RailwayEdge {
+                        length: reached.length,
+                        path: reached.path.clone(),
+                        ..edge
+                    };
+                    Some(html! {
+                        <SvgEdge
+                            edge={edge}
+                            transform={transform}
+                            stroke_color={Some("green".to_string())}
+                        />
+                    })
+                })
+                .collect();
+
+            let heat_edges: Vec<Html> = ctx
+                .props()
+                .edge_values
+                .iter()
+                .flat_map(|values| {
+                    let min = values.values().copied().fold(f64::INFINITY, f64::min);
+                    let max = values.values().copied().fold(f64::NEG_INFINITY, f64::max);
+                    values.iter().filter_map(move |(&edge_id, &value)| {
+                        let edge = graph.get_edge_by_id(edge_id)?;
+                        let share = if max > min {
+                            (value - min) / (max - min)
+                        } else {
+                            0.0
+                        };
+                        Some(html! {
+                            <SvgEdge
+                                edge={edge}
+                                transform={transform}
+                                stroke_color={Some(heat_color(share))}
+                            />
+                        })
+                    })
+                })
+                .collect();
+
+            let diff_edges: Vec<Html> = ctx
+                .props()
+                .diff
+                .iter()
+                .flat_map(|diff| diff.edges.iter())
+                .flat_map(|change| {
+                    diff_paths(change).into_iter().map(move |(path, color)| {
+                        let edge = RailwayEdge {
+                            id: change.edge_id,
+                            length: change.length,
+                            path: path.clone(),
+                            source: 0,
+                            target: 0,
+                        };
+                        html! {
+                            <SvgEdge
+                                edge={edge}
+                                transform={transform}
+                                stroke_color={Some(color.to_string())}
+                            />
+                        }
+                    })
+                })
+                .collect();
+
             html! {
                 <svg xmlns="http://www.w3.org/2000/svg" viewBox={format!("0 0 {} {}", ctx.props().view_width, ctx.props().view_height)}>
                     { for svg_edges }
+                    { for heat_edges }
+                    { for reached_edges }
+                    { for diff_edges }
                     { for svg_nodes }
                     { for path_edges }
                 </svg>
@@ -187,6 +310,9 @@ mod tests {
             on_select_node: None,
             start_node_id: None,
             end_node_id: None,
+            reachability: None,
+            edge_values: None,
+            diff: None,
         };
 
         let rendered = LocalServerRenderer::<SvgComponent>::with_props(props)
@@ -203,4 +329,38 @@ mod tests {
         assert!(rendered.contains("r=\"2\""));
         assert!(rendered.contains("fill=\"red\""));
     }
+
+    #[test]
+    fn test_heat_color() {
+        assert_eq!(heat_color(0.0), "rgb(0,255,0)");
+        assert_eq!(heat_color(0.5), "rgb(255,255,0)");
+        assert_eq!(heat_color(1.0), "rgb(255,0,0)");
+        assert_eq!(heat_color(2.0), "rgb(255,0,0)");
+    }
+
+    #[test]
+    fn test_diff_paths() {
+        let path = LineString::from(vec![coord! { x: 8.0, y: 50.0 }, coord! { x: 8.1, y: 50.0 }]);
+        let mut change = EdgeChange {
+            edge_id: 1,
+            kind: ChangeKind::Added,
+            path: path.clone(),
+            previous_path: None,
+            length: 7000.0,
+            previous_length: None,
+            changed_tags: Vec::new(),
+        };
+        assert_eq!(diff_paths(&change), vec![(&path, "green")]);
+        change.kind = ChangeKind::Removed;
+        assert_eq!(diff_paths(&change), vec![(&path, "red")]);
+        change.kind = ChangeKind::Modified;
+        assert_eq!(diff_paths(&change), vec![(&path, "orange")]);
+        let previous =
+            LineString::from(vec![coord! { x: 8.0, y: 50.1 }, coord! { x: 8.1, y: 50.1 }]);
+        change.previous_path = Some(previous.clone());
+        assert_eq!(
+            diff_paths(&change),
+            vec![(&previous, "red"), (&path, "green")]
+        );
+    }
 }
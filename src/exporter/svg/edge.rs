@@ -1,4 +1,6 @@
+use crate::crs::ViewTransform;
 use crate::prelude::RailwayEdge;
+use geo::Coord;
 use yew::prelude::*;
 
 /// A Yew component for visualizing railway edges as SVG paths.
@@ -10,14 +12,8 @@ pub struct SvgEdge {
 pub struct Props {
     /// The railway edge to visualize.
     pub edge: RailwayEdge,
-    /// The scaling factor for the x-axis.
-    pub scale_x: f64,
-    /// The scaling factor for the y-axis.
-    pub scale_y: f64,
-    /// The height of the SVG view.
-    pub view_height: f64,
-    /// The minimum coordinates of the graph's bounding box.
-    pub min_coord: (f64, f64),
+    /// Maps the edge's coordinates to view coordinates.
+    pub transform: ViewTransform,
     #[prop_or_default]
     pub stroke_color: Option<String>,
 }
@@ -56,9 +52,7 @@ impl Component for SvgEdge {
             .iter()
             .enumerate()
             .map(|(i, coord)| {
-                let x = (coord.x - ctx.props().min_coord.0) * ctx.props().scale_x;
-                let y = ctx.props().view_height
-                    - (coord.y - ctx.props().min_coord.1) * ctx.props().scale_y;
+                let Coord { x, y } = ctx.props().transform.to_view(*coord);
 
                 if i == 0 {
                     format!("M {:.1} {:.1}", x, y)
@@ -105,10 +99,12 @@ mod tests {
         };
         let props = Props {
             edge,
-            scale_x: 1.0,
-            scale_y: 1.0,
-            view_height: 100.0,
-            min_coord: (8.6800, 50.1000),
+            transform: ViewTransform::fit_local(
+                coord! { x: 8.6800, y: 50.1000 },
+                coord! { x: 8.7000, y: 50.1300 },
+                100.0,
+                100.0,
+            ),
             stroke_color: None,
         };
 
@@ -1,12 +1,15 @@
 mod component;
 mod edge;
 mod node;
+use std::collections::HashMap;
 use std::error::Error;
 
 pub use component::{Props, SvgComponent};
 use yew::LocalServerRenderer;
 
-use crate::prelude::RailwayGraph;
+use crate::prelude::{GraphDiff, RailwayGraph};
+use crate::railway_algorithms::Reachability;
+use crate::types::EdgeId;
 
 /// Generates an SVG string representation of a given RailwayGraph.
 ///
@@ -20,6 +23,73 @@ use crate::prelude::RailwayGraph;
 ///
 /// A `Result` containing an SVG-formatted `String` on success, or a `Box<dyn Error>` on failure.
 pub fn generate_svg_string(graph: &RailwayGraph) -> Result<String, Box<dyn Error>> {
+    render_svg(graph, None, None, None)
+}
+
+/// Generates an SVG string of a RailwayGraph with the reachable part of the network in green.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to a RailwayGraph.
+/// * `reachability` - The result of `RailwayGraphAlgos::reachable_within` on the graph.
+///
+/// # Returns
+///
+/// A `Result` containing an SVG-formatted `String` on success, or a `Box<dyn Error>` on failure.
+pub fn generate_reachability_svg_string(
+    graph: &RailwayGraph,
+    reachability: &Reachability,
+) -> Result<String, Box<dyn Error>> {
+    render_svg(graph, Some(reachability.clone()), None, None)
+}
+
+/// Generates an SVG string of a RailwayGraph with the edges colored by a value as a heat map.
+///
+/// The edge with the lowest value is drawn in green and the one with the highest in red. Edges
+/// without a value keep the default color.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to a RailwayGraph.
+/// * `edge_values` - The values per edge ID, e.g. from `RailwayGraphResilience::edge_betweenness`.
+///
+/// # Returns
+///
+/// A `Result` containing an SVG-formatted `String` on success, or a `Box<dyn Error>` on failure.
+pub fn generate_heat_map_svg_string(
+    graph: &RailwayGraph,
+    edge_values: &HashMap<EdgeId, f64>,
+) -> Result<String, Box<dyn Error>> {
+    render_svg(graph, None, Some(edge_values.clone()), None)
+}
+
+/// Generates an SVG string of a RailwayGraph with the changes to an older snapshot as an overlay.
+///
+/// Added edges are drawn in green, removed edges in red and edges with changed length or tags in
+/// orange. An edge whose geometry moved is drawn in red at its old and in green at its new
+/// position.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the new snapshot of the RailwayGraph.
+/// * `diff` - The result of `RailwayGraphDiff::diff` from the old snapshot to `graph`.
+///
+/// # Returns
+///
+/// A `Result` containing an SVG-formatted `String` on success, or a `Box<dyn Error>` on failure.
+pub fn generate_diff_svg_string(
+    graph: &RailwayGraph,
+    diff: &GraphDiff,
+) -> Result<String, Box<dyn Error>> {
+    render_svg(graph, None, None, Some(diff.clone()))
+}
+
+fn render_svg(
+    graph: &RailwayGraph,
+    reachability: Option<Reachability>,
+    edge_values: Option<HashMap<EdgeId, f64>>,
+    diff: Option<GraphDiff>,
+) -> Result<String, Box<dyn Error>> {
     let width = 2500.0;
     let height = 2500.0;
     let renderer = LocalServerRenderer::<SvgComponent>::with_props(Props {
@@ -29,6 +99,9 @@ pub fn generate_svg_string(graph: &RailwayGraph) -> Result<String, Box<dyn Error
         on_select_node: None,
         start_node_id: None,
         end_node_id: None,
+        reachability,
+        edge_values,
+        diff,
     })
     .hydratable(false);
     #[cfg(target_arch = "wasm32")]
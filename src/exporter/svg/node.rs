@@ -1,4 +1,5 @@
-use crate::{prelude::RailwayNode, types::NodeId};
+use crate::{crs::ViewTransform, prelude::RailwayNode, types::NodeId};
+use geo::Coord;
 use yew::prelude::*;
 
 /// A Yew component for visualizing railway nodes as SVG circles.
@@ -13,14 +14,8 @@ pub struct SvgNode {
 pub struct Props {
     /// The railway node to visualize.
     pub node: RailwayNode,
-    /// The scaling factor for the x-axis.
-    pub scale_x: f64,
-    /// The scaling factor for the y-axis.
-    pub scale_y: f64,
-    /// The height of the SVG view.
-    pub view_height: f64,
-    /// The minimum coordinates of the graph's bounding box.
-    pub min_coord: (f64, f64),
+    /// Maps the node's location to view coordinates.
+    pub transform: ViewTransform,
     /// Callback for when the circle is clicked.
     pub on_select: Option<Callback<NodeId>>,
 }
@@ -68,9 +63,7 @@ impl Component for SvgNode {
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let radius = if self.hovered { 5.0 } else { 2.0 };
-        let x = (ctx.props().node.location.x - ctx.props().min_coord.0) * ctx.props().scale_x;
-        let y = ctx.props().view_height
-            - (ctx.props().node.location.y - ctx.props().min_coord.1) * ctx.props().scale_y;
+        let Coord { x, y } = ctx.props().transform.to_view(ctx.props().node.location);
 
         let color = if self.clicked { "blue" } else { "red" };
 
@@ -104,12 +97,16 @@ mod tests {
                 y: 50.0,
             },
         };
+        let transform = ViewTransform::fit_local(
+            coord! { x: 29.9, y: 49.9 },
+            coord! { x: 30.1, y: 50.1 },
+            100.0,
+            100.0,
+        );
+        let expected = transform.to_view(node.location);
         let props = Props {
             node,
-            scale_x: 1.0,
-            scale_y: 1.0,
-            view_height: 100.0,
-            min_coord: (0.0, 0.0),
+            transform,
             on_select: None,
         };
 
@@ -118,8 +115,8 @@ mod tests {
             .await;
 
         assert!(rendered.contains("<circle"));
-        assert!(rendered.contains("cx=\"30\""));
-        assert!(rendered.contains("cy=\"50\""));
+        assert!(rendered.contains(&format!("cx=\"{}\"", expected.x)));
+        assert!(rendered.contains(&format!("cy=\"{}\"", expected.y)));
         assert!(rendered.contains("r=\"2\""));
     }
 }
@@ -2,8 +2,9 @@
 //! railway graph data from the Overpass API.
 mod coordinate;
 mod railway_element;
+mod route_relation;
 use crate::algorithms::Distance;
-use crate::railway_model::{RailwayEdge, RailwayGraph, RailwayNode};
+use crate::railway_model::{RailwayEdge, RailwayGraph, RailwayNode, RailwayTags};
 use crate::types::{EdgeId, NodeId};
 use anyhow::Result;
 pub use coordinate::Coordinate;
@@ -11,6 +12,7 @@ use geo::{coord, Coord, LineString};
 use geoutils::Location;
 use petgraph::stable_graph::NodeIndex;
 pub use railway_element::RailwayElement;
+pub use route_relation::RouteRelation;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use transit_grid::prelude::{TransitNetworkModifier, TransitNetworkRepairer};
@@ -31,6 +33,92 @@ impl RailwayGraphImporter for OverpassImporter {
     }
 }
 
+impl OverpassImporter {
+    /// Imports the OpenStreetMap tags of the nodes and edges of a railway graph.
+    ///
+    /// The ids of the returned `RailwayTags` match the graph returned by `import` for the same
+    /// input.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A reference to a `serde_json::Value` with the Overpass API response.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<RailwayTags>` containing the tags, or an error if the input could not be parsed.
+    pub fn import_tags(input: &Value) -> Result<RailwayTags> {
+        let railway_elements = RailwayElement::from_json(input)?;
+        Ok(tags_from_railway_elements(&railway_elements))
+    }
+
+    /// Imports the nodes of a response without building a graph, e.g. the stations fetched with
+    /// `fetch_stations_by_area_name`, which are often mapped beside the track.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A reference to a `serde_json::Value` with the Overpass API response.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<RailwayNode>>` containing the nodes, or an error if the input could not be
+    /// parsed.
+    pub fn import_nodes(input: &Value) -> Result<Vec<RailwayNode>> {
+        let railway_elements = RailwayElement::from_json(input)?;
+        Ok(create_nodes_from_node_elements(&railway_elements))
+    }
+
+    /// Imports the `route=train` relations of a railway graph.
+    ///
+    /// The ways of the returned relations are the ids of the edges of the graph returned by
+    /// `import` for the same input.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A reference to a `serde_json::Value` with the Overpass API response.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<RouteRelation>>` containing the relations, or an error if the input could
+    /// not be parsed.
+    pub fn import_route_relations(input: &Value) -> Result<Vec<RouteRelation>> {
+        Ok(RouteRelation::from_json(input)?)
+    }
+}
+
+/// Collect the tags of `RailwayElement`s into a `RailwayTags` table.
+///
+/// Tags of `Node` elements are stored by node id and tags of `Way` elements by edge id. Elements
+/// without tags are skipped.
+///
+/// # Arguments
+///
+/// * `elements` - A slice of `RailwayElement`s to collect the tags from.
+///
+/// # Returns
+///
+/// A `RailwayTags` table with the tags of the input elements.
+pub fn tags_from_railway_elements(elements: &[RailwayElement]) -> RailwayTags {
+    let mut railway_tags = RailwayTags::default();
+    for element in elements {
+        let Some(tags) = element.tags.as_ref().filter(|tags| !tags.is_empty()) else {
+            continue;
+        };
+        match element.element_type {
+            ElementType::Node => {
+                railway_tags
+                    .nodes
+                    .insert(element.id as NodeId, tags.clone());
+            }
+            ElementType::Way => {
+                railway_tags
+                    .edges
+                    .insert(element.id as EdgeId, tags.clone());
+            }
+        }
+    }
+    railway_tags
+}
+
 /// Create a `RailwayGraph` from a vector of `RailwayElement`s.
 ///
 /// The function processes the input elements to create a graph with nodes and edges.
@@ -523,6 +611,51 @@ mod tests {
         assert_eq!(node_1.location.x, 8.6090232);
     }
 
+    #[test]
+    fn test_import_tags() {
+        let json_value = json!({
+            "elements": [
+                {
+                    "type": "node",
+                    "id": 1,
+                    "lat": 50.1191127,
+                    "lon": 8.6090232,
+                    "tags": {
+                        "railway": "switch"
+                    }
+                },
+                {
+                    "type": "node",
+                    "id": 3,
+                    "lat": 50.1191127,
+                    "lon": 8.6090232,
+                    "tags": {}
+                },
+                {
+                    "type": "way",
+                    "id": 2,
+                    "nodes": [1, 2, 3],
+                    "tags": {
+                        "railway": "rail",
+                        "maxspeed": "120"
+                    }
+                }
+            ]
+        });
+
+        let tags = OverpassImporter::import_tags(&json_value).unwrap();
+        assert_eq!(tags.node_tag(1, "railway"), Some("switch"));
+        assert!(tags.node_tags(3).is_none());
+        assert_eq!(tags.edge_tag(2, "maxspeed"), Some("120"));
+
+        let nodes = OverpassImporter::import_nodes(&json_value).unwrap();
+        assert_eq!(
+            nodes.iter().map(|node| node.id).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(nodes[0].location, coord! { x: 8.6090232, y: 50.1191127 });
+    }
+
     #[test]
     fn test_find_next_existing_node() {
         let node_ids = vec![1, 3, 5];
@@ -0,0 +1,130 @@
+use serde::{ser::Error, Deserialize};
+use serde_json::Value;
+
+use crate::railway_model::Tags;
+use crate::types::EdgeId;
+
+/// A `route=train` relation, the ordered ways an OpenStreetMap train line runs along.
+///
+/// The ways are the ids of the edges the importer creates for them. Stops and platforms are
+/// left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteRelation {
+    /// The id of the relation.
+    pub id: i64,
+    /// The tags of the relation, e.g. `name`, `ref` and `from`/`to`.
+    pub tags: Tags,
+    /// The ids of the ways of the relation in the order of its members.
+    pub ways: Vec<EdgeId>,
+}
+
+#[derive(Deserialize)]
+struct RelationElement {
+    #[serde(rename = "type")]
+    element_type: String,
+    id: i64,
+    #[serde(default)]
+    tags: Tags,
+    #[serde(default)]
+    members: Vec<RelationMember>,
+}
+
+#[derive(Deserialize)]
+struct RelationMember {
+    #[serde(rename = "type")]
+    member_type: String,
+    #[serde(rename = "ref")]
+    id: i64,
+    #[serde(default)]
+    role: String,
+}
+
+impl RouteRelation {
+    /// Deserialize the `route=train` relations of a JSON value from the Overpass API.
+    ///
+    /// Other elements and relations are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_value` - A reference to a JSON value containing railway elements data.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of `RouteRelation` instances on success, or a `serde_json::Error` on failure.
+    pub fn from_json(json_value: &Value) -> Result<Vec<RouteRelation>, serde_json::Error> {
+        let relations = json_value["elements"]
+            .as_array()
+            .ok_or_else(|| serde_json::Error::custom("Elements parsing error"))?
+            .iter()
+            .filter_map(|elem| serde_json::from_value::<RelationElement>(elem.clone()).ok())
+            .filter(|relation| {
+                relation.element_type == "relation"
+                    && relation.tags.get("route").map(String::as_str) == Some("train")
+            })
+            .map(|relation| RouteRelation {
+                id: relation.id,
+                ways: relation
+                    .members
+                    .iter()
+                    .filter(|member| member.member_type == "way" && is_track_role(&member.role))
+                    .map(|member| member.id as EdgeId)
+                    .collect(),
+                tags: relation.tags,
+            })
+            .collect();
+        Ok(relations)
+    }
+
+    /// Returns the `name` of the relation, or its `ref` if it has no name.
+    pub fn name(&self) -> Option<&str> {
+        self.tags
+            .get("name")
+            .or_else(|| self.tags.get("ref"))
+            .map(String::as_str)
+    }
+}
+
+/// Returns `true` for the roles of the members the trains run along, rather than stops and
+/// platforms.
+fn is_track_role(role: &str) -> bool {
+    !(role.starts_with("stop") || role.starts_with("platform"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_route_relation_from_json() {
+        let json = json!({
+            "elements": [
+                { "type": "way", "id": 10, "nodes": [1, 2] },
+                {
+                    "type": "relation",
+                    "id": 100,
+                    "tags": { "type": "route", "route": "train", "ref": "RB 34" },
+                    "members": [
+                        { "type": "node", "ref": 1, "role": "stop" },
+                        { "type": "way", "ref": 20, "role": "platform" },
+                        { "type": "way", "ref": 10, "role": "" },
+                        { "type": "way", "ref": 11, "role": "forward" }
+                    ]
+                },
+                {
+                    "type": "relation",
+                    "id": 101,
+                    "tags": { "type": "route", "route": "bus" },
+                    "members": [{ "type": "way", "ref": 30, "role": "" }]
+                }
+            ]
+        });
+
+        let relations = RouteRelation::from_json(&json).unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].id, 100);
+        assert_eq!(relations[0].ways, vec![10, 11]);
+        assert_eq!(relations[0].name(), Some("RB 34"));
+        assert!(RouteRelation::from_json(&json!({})).is_err());
+    }
+}
@@ -10,6 +10,10 @@
 //! - railway_api_client: Contains the API client to fetch railway infrastructure data.
 //! - railway_model: Contains data structures and functions to work with the railway infrastructure data.
 //! - export: Provides functionality to export the railway data in different formats.
+//! - crs: Projects coordinates to UTM, Web Mercator or a local tangent plane in meters and back.
+//! - elevation: Reads elevation data from local DEM files and samples it along the railway edges.
+//! - map_matching: Matches recorded GPS traces from GPX or CSV files onto the railway tracks.
+//! - validation: Checks railway graphs for data problems and reports them with ids and coordinates.
 //! - simulation: Handles the simulation components, including agent decisions, environment, and execution.
 //! - ai: Contains modules for the AI components, including reinforcement learning train agents and their state representation.
 //! - app: Provides a web application for displaying and interacting with the data (only available when targeting WebAssembly).
@@ -20,6 +24,8 @@
 pub mod app;
 
 pub mod algorithms;
+pub mod crs;
+pub mod elevation;
 
 #[cfg(feature = "ai")]
 pub mod ai;
@@ -30,6 +36,7 @@ pub mod app3d;
 pub mod export;
 pub mod exporter;
 pub mod importer;
+pub mod map_matching;
 pub mod railway_algorithms;
 pub mod railway_api_client;
 pub mod railway_model;
@@ -37,6 +44,7 @@ pub mod railway_objects;
 pub mod simulation;
 pub mod statistics;
 pub mod types;
+pub mod validation;
 
 #[cfg(feature = "python")]
 pub mod python;
@@ -1,11 +1,32 @@
-use openrailwaymap_exporter::exporter::svg::generate_svg_string;
+use openrailwaymap_exporter::exporter::geojson::{
+    graph_diff_to_geojson, map_match_to_geojson, reachability_to_geojson,
+};
+use openrailwaymap_exporter::exporter::svg::{
+    generate_diff_svg_string, generate_heat_map_svg_string, generate_reachability_svg_string,
+    generate_svg_string,
+};
+use openrailwaymap_exporter::map_matching::{
+    read_trace, MapMatchingOptions, RailwayGraphMapMatching,
+};
 use openrailwaymap_exporter::prelude::{
-    generate_dot_string, OverpassApiClient, OverpassImporter, RailwayApiClient,
-    RailwayGraphImporter,
+    generate_dot_string, CantConfig, DiffOptions, OverpassApiClient, OverpassImporter,
+    RailwayApiClient, RailwayGraphDiff, RailwayGraphImporter,
+};
+use openrailwaymap_exporter::railway_algorithms::{
+    KShortestPathsOptions, PathFinding, RailwayGraphAlgos, RailwayGraphResilience,
+    RailwayGraphSpeedLimits, ReachabilityBudget, StationIndex, TrainProfile,
+};
+use openrailwaymap_exporter::statistics::{network_diameter, network_statistics};
+use openrailwaymap_exporter::types::NodeId;
+use openrailwaymap_exporter::validation::{
+    RailwayGraphValidation, RouteRelationComparison, ValidationOptions,
 };
 use std::fs::File;
 use std::io::Write;
+use std::path::PathBuf;
 use structopt::StructOpt;
+use uom::si::f64::{Length, Time, Velocity};
+use uom::si::{length::meter, time::second, velocity::kilometer_per_hour};
 
 #[derive(StructOpt, Debug)]
 #[structopt(
@@ -45,6 +66,114 @@ struct Opt {
         help = "Output filename"
     )]
     output: Option<String>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Check the railway graph for data problems and output a JSON report
+    Validate,
+    /// Output statistics of the railway network as JSON
+    Statistics {
+        /// Also calculate the diameter of the network, which is slow on large networks
+        #[structopt(long)]
+        diameter: bool,
+    },
+    /// Compare the route=train relations with the shortest paths between their terminals and
+    /// output the extra length, the edges unique to either path and the gaps of each relation
+    /// as JSON
+    Relations,
+    /// Output the shortest alternative routes between two nodes as JSON
+    Route {
+        /// The ID of the start node, or the name or ref of the start station, e.g.
+        /// "Frankfurt Hbf"
+        start: String,
+        /// The ID of the end node, or the name or ref of the end station
+        end: String,
+        /// The maximum number of routes
+        #[structopt(short, long, default_value = "3")]
+        k: usize,
+        /// The minimum share of a route's length, between 0 and 1, not used by a shorter route
+        #[structopt(long, default_value = "0")]
+        min_difference: f64,
+    },
+    /// Output the part of the network reachable from a node within a distance or running time
+    /// as GeoJSON, or as SVG with --svg
+    Reachable {
+        /// The ID of the start node
+        start: i64,
+        /// The maximum distance in meters
+        #[structopt(long, required_unless("time"))]
+        distance: Option<f64>,
+        /// The maximum running time in seconds, using the speed limits of the tracks
+        #[structopt(long, conflicts_with("distance"))]
+        time: Option<f64>,
+        /// The maximum speed of the train in km/h when limiting the running time
+        #[structopt(long, default_value = "120")]
+        max_speed: f64,
+    },
+    /// Output the shortest path distances in meters between origin and destination nodes as
+    /// JSON, or as CSV with --csv
+    Matrix {
+        /// The IDs of the origin nodes, separated by commas
+        #[structopt(long, use_delimiter = true)]
+        origins: Vec<i64>,
+        /// The IDs of the destination nodes, separated by commas. Defaults to the origins
+        #[structopt(long, use_delimiter = true)]
+        destinations: Vec<i64>,
+        /// Output CSV with a row per origin and a column per destination
+        #[structopt(long)]
+        csv: bool,
+    },
+    /// Output the bridges, articulation points and edge betweenness of the network and the
+    /// effect of closing tracks as JSON, or the edge betweenness as SVG heat map with --svg
+    Resilience {
+        /// The IDs of the edges to close, separated by commas
+        #[structopt(long, use_delimiter = true)]
+        close: Vec<i64>,
+        /// The IDs of the nodes whose connections are checked for the closure, separated by
+        /// commas. Defaults to all nodes
+        #[structopt(long, use_delimiter = true)]
+        nodes: Vec<i64>,
+    },
+    /// Match a GPS trace from a GPX or CSV file onto the tracks and output the used tracks and
+    /// the matched positions as GeoJSON
+    MapMatch {
+        /// The GPX or CSV file with the trace
+        #[structopt(parse(from_os_str))]
+        trace: PathBuf,
+        /// The maximum distance in meters between a fix and its track
+        #[structopt(long, default_value = "50")]
+        search_radius: f64,
+        /// The standard deviation of the GPS error in meters
+        #[structopt(long, default_value = "10")]
+        gps_accuracy: f64,
+    },
+    /// Compare an older snapshot, saved with --json, with the current network and output the
+    /// added, removed and modified nodes and edges and a summary of the length changes as
+    /// GeoJSON, or as SVG overlay with --svg
+    Diff {
+        /// The JSON file with the older snapshot
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+        /// The distance in meters a node or track must move to be reported
+        #[structopt(long, default_value = "1")]
+        tolerance: f64,
+        /// The change of length in meters of a track to be reported
+        #[structopt(long, default_value = "1")]
+        length_tolerance: f64,
+    },
+}
+
+/// Returns the node with the given ID, or the track node of the station best matching a name.
+fn find_node(stations: &StationIndex, query: &str) -> Result<NodeId, String> {
+    query.trim().parse().or_else(|_| {
+        stations
+            .find_node(query)
+            .ok_or_else(|| format!("no station matches \"{}\"", query))
+    })
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -65,12 +194,171 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let api_json_value = if let Some(area) = &opt.area {
         api_client.fetch_by_area_name(area).await?
     } else {
-        let bbox = opt.bbox.unwrap();
-        api_client.fetch_by_bbox(&bbox).await?
+        let bbox = opt.bbox.as_deref().unwrap();
+        api_client.fetch_by_bbox(bbox).await?
     };
 
     let graph = OverpassImporter::import(&api_json_value).unwrap();
 
+    if let Some(command) = &opt.command {
+        let tags = OverpassImporter::import_tags(&api_json_value)?;
+        let report_json = match command {
+            Command::Validate => {
+                serde_json::to_string_pretty(&graph.validate(&tags, &ValidationOptions::default()))?
+            }
+            Command::Statistics { diameter } => {
+                let mut statistics = network_statistics(&graph, &tags);
+                if *diameter {
+                    statistics.diameter = Some(network_diameter(&graph));
+                }
+                serde_json::to_string_pretty(&statistics)?
+            }
+            Command::Relations => {
+                let relations_json = if let Some(area) = &opt.area {
+                    api_client.fetch_route_relations_by_area_name(area).await?
+                } else {
+                    let bbox = opt.bbox.as_deref().unwrap();
+                    api_client.fetch_route_relations_by_bbox(bbox).await?
+                };
+                let relations = OverpassImporter::import_route_relations(&relations_json)?;
+                serde_json::to_string_pretty(&graph.compare_route_relations(&relations))?
+            }
+            Command::Route {
+                start,
+                end,
+                k,
+                min_difference,
+            } => {
+                let stations_json = if let Some(area) = &opt.area {
+                    api_client.fetch_stations_by_area_name(area).await?
+                } else {
+                    let bbox = opt.bbox.as_deref().unwrap();
+                    api_client.fetch_stations_by_bbox(bbox).await?
+                };
+                let mut tags = tags.clone();
+                tags.nodes
+                    .extend(OverpassImporter::import_tags(&stations_json)?.nodes);
+                let stations = StationIndex::new(
+                    &graph,
+                    &tags,
+                    &OverpassImporter::import_nodes(&stations_json)?,
+                );
+                let start = find_node(&stations, start)?;
+                let end = find_node(&stations, end)?;
+                let options = KShortestPathsOptions {
+                    k: *k,
+                    min_difference: *min_difference,
+                };
+                serde_json::to_string_pretty(&graph.k_shortest_paths(start, end, &options))?
+            }
+            Command::Reachable {
+                start,
+                distance,
+                time,
+                max_speed,
+            } => {
+                let speed_limits = graph.speed_limits(&tags, None, &CantConfig::default());
+                let budget = match (distance, time) {
+                    (Some(distance), _) => {
+                        ReachabilityBudget::Distance(Length::new::<meter>(*distance))
+                    }
+                    (None, time) => ReachabilityBudget::Time {
+                        time: Time::new::<second>(time.unwrap_or_default()),
+                        profile: TrainProfile {
+                            max_speed: Velocity::new::<kilometer_per_hour>(*max_speed),
+                            ..Default::default()
+                        },
+                        speed_limits: &speed_limits,
+                    },
+                };
+                let reachability = graph.reachable_within(*start, &budget);
+                if opt.svg {
+                    generate_reachability_svg_string(&graph, &reachability)?
+                } else {
+                    serde_json::to_string_pretty(&reachability_to_geojson(&graph, &reachability))?
+                }
+            }
+            Command::Matrix {
+                origins,
+                destinations,
+                csv,
+            } => {
+                let destinations = if destinations.is_empty() {
+                    origins
+                } else {
+                    destinations
+                };
+                let matrix = graph.distance_matrix(origins, destinations);
+                if *csv {
+                    matrix.to_csv()
+                } else {
+                    serde_json::to_string_pretty(&matrix)?
+                }
+            }
+            Command::Resilience { close, nodes } => {
+                let betweenness = graph.edge_betweenness();
+                if opt.svg {
+                    generate_heat_map_svg_string(&graph, &betweenness)?
+                } else {
+                    let nodes = if nodes.is_empty() {
+                        graph
+                            .physical_graph
+                            .graph
+                            .node_weights()
+                            .map(|node| node.id)
+                            .collect()
+                    } else {
+                        nodes.clone()
+                    };
+                    let closure = (!close.is_empty()).then(|| graph.closure_impact(close, &nodes));
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "bridges": graph.bridges(),
+                        "articulation_points": graph.articulation_points(),
+                        "edge_betweenness": betweenness,
+                        "closure": closure,
+                    }))?
+                }
+            }
+            Command::MapMatch {
+                trace,
+                search_radius,
+                gps_accuracy,
+            } => {
+                let options = MapMatchingOptions {
+                    search_radius: Length::new::<meter>(*search_radius),
+                    gps_accuracy: Length::new::<meter>(*gps_accuracy),
+                    ..Default::default()
+                };
+                let map_match = graph.match_trace(&read_trace(trace)?, &options);
+                serde_json::to_string_pretty(&map_match_to_geojson(&graph, &map_match))?
+            }
+            Command::Diff {
+                old,
+                tolerance,
+                length_tolerance,
+            } => {
+                let old_json: serde_json::Value = serde_json::from_reader(File::open(old)?)?;
+                let old_graph = OverpassImporter::import(&old_json)?;
+                let old_tags = OverpassImporter::import_tags(&old_json)?;
+                let options = DiffOptions {
+                    tolerance: Length::new::<meter>(*tolerance),
+                    length_tolerance: Length::new::<meter>(*length_tolerance),
+                };
+                let diff = old_graph.diff_with_tags(&old_tags, &graph, &tags, &options);
+                if opt.svg {
+                    generate_diff_svg_string(&graph, &diff)?
+                } else {
+                    serde_json::to_string_pretty(&graph_diff_to_geojson(&diff))?
+                }
+            }
+        };
+        match opt.output {
+            Some(file_path) => writeln!(File::create(file_path)?, "{}", report_json)?,
+            None => println!("{}", report_json),
+        }
+        return Ok(());
+    }
+
     println!(
         "Railway Graph: {:?}",
         &graph.physical_graph.graph.edge_count()
@@ -0,0 +1,587 @@
+//! Module `map_matching` finds the tracks a train used from a recorded GPS trace.
+//!
+//! The matching follows a hidden Markov model. The candidate states of every fix are the closest
+//! points of the edges within a search radius, found with an `EdgeSpatialIndex`. A candidate is
+//! the more likely the closer it is to the fix, and a transition between the candidates of two
+//! fixes is the more likely the less the distance along the network differs from the direct
+//! distance between the fixes. The Viterbi algorithm then picks the most likely sequence of
+//! positions, which is connected to a path of edges. Traces are read from GPX or CSV files with
+//! the functions of the `trace` module.
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use geo::Coord;
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+};
+use uom::si::{
+    f64::{Length, Velocity},
+    length::meter,
+    velocity::{kilometer_per_hour, meter_per_second},
+};
+
+use crate::algorithms::Distance;
+use crate::prelude::{RailwayGraph, TrackDirection, TrackPosition};
+use crate::railway_algorithms::{EdgeProjection, EdgeSpatialIndex, RailwayEdgeAlgos};
+use crate::types::EdgeId;
+
+pub mod trace;
+
+pub use trace::{parse_csv, parse_gpx, read_trace};
+
+/// A single position of a GPS trace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsFix {
+    /// The time of the fix in seconds since the Unix epoch, if recorded.
+    pub time: Option<f64>,
+    /// The recorded location.
+    pub location: Coord<f64>,
+}
+
+/// Parameters of the map matching.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapMatchingOptions {
+    /// The maximum distance between a fix and the edges it may be matched to.
+    pub search_radius: Length,
+    /// The standard deviation of the GPS measurement error.
+    pub gps_accuracy: Length,
+    /// How much the distance along the network between two fixes may differ from their direct
+    /// distance. Larger values make detours between fixes more likely.
+    pub transition_scale: Length,
+    /// The highest plausible speed. Routes between two timed fixes that would need a higher
+    /// speed are ruled out.
+    pub max_speed: Velocity,
+}
+
+impl Default for MapMatchingOptions {
+    fn default() -> Self {
+        Self {
+            search_radius: Length::new::<meter>(50.0),
+            gps_accuracy: Length::new::<meter>(10.0),
+            transition_scale: Length::new::<meter>(50.0),
+            max_speed: Velocity::new::<kilometer_per_hour>(350.0),
+        }
+    }
+}
+
+/// The position on the network a fix was matched to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchedFix {
+    /// The position on the matched edge, facing the direction the train moved in.
+    pub position: TrackPosition,
+    /// The coordinate of the position.
+    pub point: Coord<f64>,
+    /// The distance between the fix and the position.
+    pub distance: Length,
+}
+
+/// The result of matching a GPS trace onto a `RailwayGraph`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MapMatch {
+    /// The matched position of every fix, or `None` for fixes without an edge within the search
+    /// radius.
+    pub fixes: Vec<Option<MatchedFix>>,
+    /// The IDs of the edges the train used, in the order it used them.
+    ///
+    /// Where no route connects two consecutive fixes, the path has a gap and continues with the
+    /// edge of the later fix.
+    pub edges: Vec<EdgeId>,
+}
+
+/// Map matching of GPS traces onto railway graphs.
+pub trait RailwayGraphMapMatching {
+    /// Finds the most likely path of a train that recorded a GPS trace.
+    ///
+    /// # Arguments
+    ///
+    /// * `trace` - The fixes of the trace in the order they were recorded.
+    /// * `options` - The parameters of the matching.
+    ///
+    /// # Returns
+    ///
+    /// A `MapMatch` with the matched position of every fix and the path of edges.
+    fn match_trace(&self, trace: &[GpsFix], options: &MapMatchingOptions) -> MapMatch;
+}
+
+impl RailwayGraphMapMatching for RailwayGraph {
+    fn match_trace(&self, trace: &[GpsFix], options: &MapMatchingOptions) -> MapMatch {
+        let index = EdgeSpatialIndex::new(self);
+        MapMatcher {
+            graph: self,
+            index: &index,
+            options,
+        }
+        .match_trace(trace)
+    }
+}
+
+/// A candidate position of a fix.
+struct Candidate {
+    projection: EdgeProjection,
+    source: NodeIndex,
+    target: NodeIndex,
+    length: f64,
+}
+
+impl Candidate {
+    fn offset(&self) -> f64 {
+        self.projection.offset.get::<meter>()
+    }
+
+    /// The nodes of the edge with the distance to reach them from the candidate.
+    fn ends(&self) -> [(NodeIndex, f64); 2] {
+        [
+            (self.source, self.offset()),
+            (self.target, (self.length - self.offset()).max(0.0)),
+        ]
+    }
+}
+
+/// The fixes with candidates, with the Viterbi scores of their candidates.
+struct Step {
+    fix: usize,
+    candidates: Vec<Candidate>,
+    scores: Vec<f64>,
+    /// The candidate of the previous step on the most likely path to each candidate, or `None`
+    /// where the path starts anew.
+    previous: Vec<Option<usize>>,
+}
+
+/// The shortest distances from a node with the edge each node was reached along.
+type Search = HashMap<NodeIndex, (f64, Option<EdgeIndex>)>;
+
+/// The route between two candidates, with the nodes it leaves the first and enters the second
+/// edge at unless both lie on the same edge.
+struct Connection {
+    length: f64,
+    nodes: Option<(NodeIndex, NodeIndex)>,
+}
+
+struct MapMatcher<'a> {
+    graph: &'a RailwayGraph,
+    index: &'a EdgeSpatialIndex,
+    options: &'a MapMatchingOptions,
+}
+
+impl MapMatcher<'_> {
+    fn match_trace(&self, trace: &[GpsFix]) -> MapMatch {
+        let mut steps: Vec<Step> = Vec::new();
+        for (fix, gps_fix) in trace.iter().enumerate() {
+            let candidates = self.candidates(gps_fix.location);
+            if candidates.is_empty() {
+                continue;
+            }
+            let emissions: Vec<f64> = candidates
+                .iter()
+                .map(|candidate| self.emission(candidate))
+                .collect();
+            let mut scores = vec![f64::NEG_INFINITY; candidates.len()];
+            let mut previous = vec![None; candidates.len()];
+
+            if let Some(last) = steps.last() {
+                let searches = self.searches(last, &trace[last.fix], gps_fix);
+                let direct = trace[last.fix].location.distance(&gps_fix.location);
+                let limit = self.limit(&trace[last.fix], gps_fix);
+                for (to, candidate) in candidates.iter().enumerate() {
+                    for (from, last_candidate) in last.candidates.iter().enumerate() {
+                        let Some(connection) = connect(last_candidate, candidate, &searches, limit)
+                        else {
+                            continue;
+                        };
+                        let score = last.scores[from]
+                            + self.transition(connection.length, direct.get::<meter>())
+                            + emissions[to];
+                        if score > scores[to] {
+                            scores[to] = score;
+                            previous[to] = Some(from);
+                        }
+                    }
+                }
+            }
+            if scores.iter().all(|score| score.is_infinite()) {
+                scores = emissions;
+            }
+            steps.push(Step {
+                fix,
+                candidates,
+                scores,
+                previous,
+            });
+        }
+
+        let chosen = backtrack(&steps);
+        self.build_match(trace, &steps, &chosen)
+    }
+
+    fn candidates(&self, location: Coord<f64>) -> Vec<Candidate> {
+        let physical = &self.graph.physical_graph;
+        self.index
+            .edges_within(location, self.options.search_radius)
+            .into_iter()
+            .filter_map(|projection| {
+                let edge = self.index.edge(projection.edge_id)?;
+                Some(Candidate {
+                    projection,
+                    source: *physical.id_to_index(edge.source)?,
+                    target: *physical.id_to_index(edge.target)?,
+                    length: edge.path_length().get::<meter>(),
+                })
+            })
+            .collect()
+    }
+
+    fn emission(&self, candidate: &Candidate) -> f64 {
+        let deviation = candidate.projection.distance / self.options.gps_accuracy;
+        -0.5 * deviation.value * deviation.value
+    }
+
+    fn transition(&self, route: f64, direct: f64) -> f64 {
+        -(route - direct).abs() / self.options.transition_scale.get::<meter>()
+    }
+
+    /// The longest route considered between two fixes.
+    fn limit(&self, from: &GpsFix, to: &GpsFix) -> f64 {
+        let radius = self.options.search_radius.get::<meter>();
+        let reach = match (from.time, to.time) {
+            (Some(from_time), Some(to_time)) if to_time > from_time => {
+                self.options.max_speed.get::<meter_per_second>() * (to_time - from_time)
+            }
+            _ => 3.0 * from.location.distance(&to.location).get::<meter>(),
+        };
+        reach + 2.0 * radius
+    }
+
+    fn searches(&self, step: &Step, from: &GpsFix, to: &GpsFix) -> HashMap<NodeIndex, Search> {
+        let limit = self.limit(from, to);
+        let mut searches = HashMap::new();
+        for candidate in &step.candidates {
+            for (node, _) in candidate.ends() {
+                searches
+                    .entry(node)
+                    .or_insert_with(|| bounded_search(self.graph, node, limit));
+            }
+        }
+        searches
+    }
+
+    fn build_match(&self, trace: &[GpsFix], steps: &[Step], chosen: &[usize]) -> MapMatch {
+        let mut fixes: Vec<Option<MatchedFix>> = vec![None; trace.len()];
+        let mut edges: Vec<EdgeId> = Vec::new();
+        let push_edge = |edges: &mut Vec<EdgeId>, edge_id: EdgeId| {
+            if edges.last() != Some(&edge_id) {
+                edges.push(edge_id);
+            }
+        };
+
+        let mut arrival_direction = TrackDirection::Forward;
+        for (position, step) in steps.iter().enumerate() {
+            let candidate = &step.candidates[chosen[position]];
+            push_edge(&mut edges, candidate.projection.edge_id);
+
+            // The chosen candidates of consecutive steps are connected by construction; should
+            // the connection not be found again, the path is treated as interrupted there.
+            let next = steps
+                .get(position + 1)
+                .filter(|next| next.previous[chosen[position + 1]] == Some(chosen[position]))
+                .and_then(|next| {
+                    let next_candidate = &next.candidates[chosen[position + 1]];
+                    let searches = self.searches(step, &trace[step.fix], &trace[next.fix]);
+                    let limit = self.limit(&trace[step.fix], &trace[next.fix]);
+                    let connection = connect(candidate, next_candidate, &searches, limit)?;
+                    Some((next_candidate, searches, connection))
+                });
+            let direction = match next {
+                Some((next_candidate, searches, connection)) => match connection.nodes {
+                    None => {
+                        let forward = next_candidate.offset() >= candidate.offset();
+                        arrival_direction = direction_of(forward);
+                        arrival_direction
+                    }
+                    Some((exit, entry)) => {
+                        for edge in path_edges(self.graph, &searches[&exit], entry) {
+                            push_edge(&mut edges, edge);
+                        }
+                        arrival_direction = direction_of(entry == next_candidate.source);
+                        direction_of(exit == candidate.target)
+                    }
+                },
+                None => {
+                    let continues_path = step.previous[chosen[position]].is_some();
+                    let direction = if continues_path {
+                        arrival_direction
+                    } else {
+                        TrackDirection::Forward
+                    };
+                    arrival_direction = TrackDirection::Forward;
+                    direction
+                }
+            };
+
+            fixes[step.fix] = Some(MatchedFix {
+                position: TrackPosition::new(
+                    candidate.projection.edge_id,
+                    candidate.projection.offset,
+                    direction,
+                ),
+                point: candidate.projection.point,
+                distance: candidate.projection.distance,
+            });
+        }
+
+        MapMatch { fixes, edges }
+    }
+}
+
+fn direction_of(forward: bool) -> TrackDirection {
+    if forward {
+        TrackDirection::Forward
+    } else {
+        TrackDirection::Backward
+    }
+}
+
+/// Picks the most likely candidate of every step, going back from the last step.
+fn backtrack(steps: &[Step]) -> Vec<usize> {
+    let best = |scores: &[f64]| {
+        scores
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(candidate, _)| candidate)
+            .unwrap_or_default()
+    };
+    let mut chosen = vec![0; steps.len()];
+    let mut link = None;
+    for (position, step) in steps.iter().enumerate().rev() {
+        let candidate = link.unwrap_or_else(|| best(&step.scores));
+        chosen[position] = candidate;
+        link = step.previous[candidate];
+    }
+    chosen
+}
+
+/// Finds the shortest route between two candidates no longer than `limit`.
+fn connect(
+    from: &Candidate,
+    to: &Candidate,
+    searches: &HashMap<NodeIndex, Search>,
+    limit: f64,
+) -> Option<Connection> {
+    if from.projection.edge_id == to.projection.edge_id {
+        return Some(Connection {
+            length: (to.offset() - from.offset()).abs(),
+            nodes: None,
+        });
+    }
+    let mut best: Option<Connection> = None;
+    for (exit, exit_distance) in from.ends() {
+        let Some(search) = searches.get(&exit) else {
+            continue;
+        };
+        for (entry, entry_distance) in to.ends() {
+            let Some(&(between, _)) = search.get(&entry) else {
+                continue;
+            };
+            let length = exit_distance + between + entry_distance;
+            if length <= limit && best.as_ref().is_none_or(|best| length < best.length) {
+                best = Some(Connection {
+                    length,
+                    nodes: Some((exit, entry)),
+                });
+            }
+        }
+    }
+    best
+}
+
+/// Runs Dijkstra's algorithm on the physical graph up to the distance `limit`.
+fn bounded_search(graph: &RailwayGraph, start: NodeIndex, limit: f64) -> Search {
+    let physical = &graph.physical_graph.graph;
+    let mut search: Search = HashMap::from([(start, (0.0, None))]);
+    let mut heap = BinaryHeap::from([State(0.0, start)]);
+    while let Some(State(distance, node)) = heap.pop() {
+        if distance > search[&node].0 {
+            continue;
+        }
+        for edge in physical.edges(node) {
+            let next = if edge.source() == node {
+                edge.target()
+            } else {
+                edge.source()
+            };
+            let next_distance = distance + edge.weight().length;
+            if next_distance > limit
+                || search
+                    .get(&next)
+                    .is_some_and(|&(known, _)| known <= next_distance)
+            {
+                continue;
+            }
+            search.insert(next, (next_distance, Some(edge.id())));
+            heap.push(State(next_distance, next));
+        }
+    }
+    search
+}
+
+/// Follows the edges a search reached `end` along back to its start.
+fn path_edges(graph: &RailwayGraph, search: &Search, end: NodeIndex) -> Vec<EdgeId> {
+    let physical = &graph.physical_graph.graph;
+    let mut edges = Vec::new();
+    let mut node = end;
+    while let Some(&(_, Some(edge))) = search.get(&node) {
+        edges.push(physical[edge].id);
+        let (source, target) = physical.edge_endpoints(edge).unwrap();
+        node = if source == node { target } else { source };
+    }
+    edges.reverse();
+    edges
+}
+
+#[derive(PartialEq)]
+struct State(f64, NodeIndex);
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::RailwayGraphExt;
+    use crate::railway_algorithms::{tests::loop_elements, PathFinding};
+    use crate::tests::test_graph_vilbel;
+    use crate::types::NodeId;
+
+    /// Samples a fix every `spacing` meters along a path of edges starting at `start`, shifted
+    /// sideways by a few meters in alternating directions, one second apart.
+    fn synthetic_trace(
+        graph: &RailwayGraph,
+        start: NodeId,
+        edges: &[EdgeId],
+        spacing: f64,
+    ) -> Vec<GpsFix> {
+        let mut trace = Vec::new();
+        let mut node = start;
+        for &edge_id in edges {
+            let edge = graph.get_edge_by_id(edge_id).unwrap();
+            let length = edge.path_length().get::<meter>();
+            let mut distance = spacing / 2.0;
+            while distance < length {
+                let offset = if node == edge.source {
+                    distance
+                } else {
+                    length - distance
+                };
+                let coord = edge.coordinate_at(Length::new::<meter>(offset));
+                let shift = if trace.len() % 2 == 0 {
+                    0.00004
+                } else {
+                    -0.00003
+                };
+                trace.push(GpsFix {
+                    time: Some(trace.len() as f64),
+                    location: Coord {
+                        x: coord.x + shift,
+                        y: coord.y - shift,
+                    },
+                });
+                distance += spacing;
+            }
+            node = if node == edge.source {
+                edge.target
+            } else {
+                edge.source
+            };
+        }
+        trace
+    }
+
+    #[test]
+    fn test_match_trace_follows_detour() {
+        let graph = from_railway_elements(&loop_elements());
+        // From node 4 over node 1 and the loop through node 3 to node 2.
+        let path = [10, 12, 13];
+        let trace = synthetic_trace(&graph, 4, &path, 100.0);
+
+        let matched = graph.match_trace(&trace, &MapMatchingOptions::default());
+
+        assert_eq!(matched.edges, path);
+        assert_eq!(matched.fixes.len(), trace.len());
+        for fix in &matched.fixes {
+            let fix = fix.unwrap();
+            assert!(path.contains(&fix.position.edge_id));
+            assert!(fix.distance < Length::new::<meter>(10.0));
+        }
+        // Edge 10 runs from node 4 to node 1 and edge 13 from node 3 to node 2.
+        let first = matched.fixes[0].unwrap().position;
+        assert_eq!(first.edge_id, 10);
+        assert_eq!(first.direction, TrackDirection::Forward);
+        let last = matched.fixes.last().unwrap().unwrap().position;
+        assert_eq!(last.edge_id, 13);
+        assert_eq!(last.direction, TrackDirection::Forward);
+    }
+
+    #[test]
+    fn test_match_trace_on_vilbel() {
+        let graph = test_graph_vilbel();
+        let nodes: Vec<NodeId> = graph
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .collect();
+        let (start, path) = nodes
+            .iter()
+            .flat_map(|&start| nodes.iter().map(move |&end| (start, end)))
+            .filter_map(|(start, end)| Some((start, graph.shortest_path_edges(start, end)?)))
+            .find(|(_, path)| path.len() >= 5)
+            .unwrap();
+        let trace = synthetic_trace(&graph, start, &path, 25.0);
+
+        let matched = graph.match_trace(&trace, &MapMatchingOptions::default());
+
+        assert!(matched.fixes.iter().all(Option::is_some));
+        let first = path
+            .iter()
+            .position(|edge| *edge == matched.edges[0])
+            .unwrap();
+        assert_eq!(matched.edges, path[first..first + matched.edges.len()]);
+        assert!(matched.edges.len() >= path.len() - 2);
+    }
+
+    #[test]
+    fn test_match_trace_skips_distant_fixes() {
+        let graph = from_railway_elements(&loop_elements());
+        let mut trace = synthetic_trace(&graph, 1, &[11], 200.0);
+        trace.insert(
+            1,
+            GpsFix {
+                time: None,
+                location: Coord { x: 9.0, y: 51.0 },
+            },
+        );
+
+        let matched = graph.match_trace(&trace, &MapMatchingOptions::default());
+
+        assert_eq!(matched.fixes.len(), trace.len());
+        assert!(matched.fixes[1].is_none());
+        assert!(matched.fixes[0].is_some());
+        assert_eq!(matched.edges, vec![11]);
+        assert!(graph.match_trace(&[], &Default::default()).edges.is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+//! Reading GPS traces from GPX and CSV files.
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use geo::Coord;
+
+use super::GpsFix;
+
+/// Reads a GPS trace from a `.gpx` or `.csv` file, chosen by the file extension.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// The fixes of the trace in file order, or an error if the file could not be read or parsed.
+pub fn read_trace(path: impl AsRef<Path>) -> Result<Vec<GpsFix>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("gpx") => parse_gpx(&content),
+        Some("csv") => parse_csv(&content),
+        _ => Err(anyhow!("unknown trace format {}", path.display())),
+    }
+}
+
+/// Parses the track points of a GPX document.
+///
+/// Every `trkpt` element becomes a fix with its `lat` and `lon` attributes and the time of its
+/// `time` child element, if present.
+pub fn parse_gpx(gpx: &str) -> Result<Vec<GpsFix>> {
+    let mut fixes = Vec::new();
+    let mut rest = gpx;
+    while let Some(start) = rest.find("<trkpt") {
+        rest = &rest[start + "<trkpt".len()..];
+        let tag_end = rest
+            .find('>')
+            .ok_or_else(|| anyhow!("unterminated trkpt element"))?;
+        let attributes = &rest[..tag_end];
+        let latitude = attribute(attributes, "lat")?;
+        let longitude = attribute(attributes, "lon")?;
+
+        let mut time = None;
+        if !attributes.ends_with('/') {
+            let body_end = rest
+                .find("</trkpt>")
+                .ok_or_else(|| anyhow!("unterminated trkpt element"))?;
+            let body = &rest[tag_end + 1..body_end];
+            if let Some(time_start) = body.find("<time>") {
+                let value = &body[time_start + "<time>".len()..];
+                let time_end = value
+                    .find("</time>")
+                    .ok_or_else(|| anyhow!("unterminated time element"))?;
+                time = Some(parse_time(value[..time_end].trim())?);
+            }
+        }
+        fixes.push(GpsFix {
+            time,
+            location: Coord {
+                x: longitude,
+                y: latitude,
+            },
+        });
+    }
+    Ok(fixes)
+}
+
+/// Parses a CSV trace with a header row.
+///
+/// The columns are found by their header: `lat` or `latitude`, `lon`, `lng` or `longitude` and
+/// optionally `time` or `timestamp`. Times are either seconds or ISO 8601 date times.
+pub fn parse_csv(csv: &str) -> Result<Vec<GpsFix>> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or_else(|| anyhow!("missing CSV header"))?
+        .split(',')
+        .map(|column| column.trim().to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|column| names.contains(&&**column));
+    let latitude_column =
+        column(&["lat", "latitude"]).ok_or_else(|| anyhow!("missing latitude column"))?;
+    let longitude_column =
+        column(&["lon", "lng", "longitude"]).ok_or_else(|| anyhow!("missing longitude column"))?;
+    let time_column = column(&["time", "timestamp"]);
+
+    lines
+        .enumerate()
+        .map(|(row, line)| {
+            let values: Vec<&str> = line.split(',').map(str::trim).collect();
+            let value = |column: usize| {
+                values
+                    .get(column)
+                    .copied()
+                    .ok_or_else(|| anyhow!("missing value in row {}", row + 1))
+            };
+            let time = match time_column {
+                Some(column) => Some(parse_time(value(column)?)?),
+                None => None,
+            };
+            Ok(GpsFix {
+                time,
+                location: Coord {
+                    x: value(longitude_column)?.parse()?,
+                    y: value(latitude_column)?.parse()?,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Returns the numeric value of an attribute. The attribute follows any whitespace and may have
+/// whitespace around its `=`, as XML allows.
+fn attribute(attributes: &str, name: &str) -> Result<f64> {
+    let mut rest = attributes;
+    while let Some(start) = rest.find(name) {
+        let follows_whitespace = rest[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_whitespace);
+        rest = &rest[start + name.len()..];
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        if !follows_whitespace {
+            continue;
+        }
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|quote| ['"', '\''].contains(quote))
+            .ok_or_else(|| anyhow!("unquoted {} attribute", name))?;
+        let value = &value[1..];
+        let end = value
+            .find(quote)
+            .ok_or_else(|| anyhow!("unterminated {} attribute", name))?;
+        return Ok(value[..end].trim().parse()?);
+    }
+    Err(anyhow!("missing {} attribute", name))
+}
+
+/// Parses seconds or an ISO 8601 date time such as `2023-05-01T12:30:00.5+02:00` into seconds
+/// since the Unix epoch. Date times without an offset are taken as UTC.
+fn parse_time(value: &str) -> Result<f64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Ok(seconds);
+    }
+    let invalid = || anyhow!("invalid time {}", value);
+    let (date, time) = value.split_once(['T', ' ']).ok_or_else(invalid)?;
+    let mut date_parts = date.splitn(3, '-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (date_parts.next(), date_parts.next(), date_parts.next())
+    else {
+        return Err(invalid());
+    };
+
+    let (time, offset) = if let Some(time) = time.strip_suffix('Z') {
+        (time, 0.0)
+    } else if let Some(sign_position) = time.rfind(['+', '-']) {
+        let (hours, minutes) = time[sign_position + 1..]
+            .split_once(':')
+            .ok_or_else(invalid)?;
+        let offset = hours.parse::<f64>()? * 3600.0 + minutes.parse::<f64>()? * 60.0;
+        let sign = if time[sign_position..].starts_with('-') {
+            -1.0
+        } else {
+            1.0
+        };
+        (&time[..sign_position], sign * offset)
+    } else {
+        (time, 0.0)
+    };
+    let mut time_parts = time.splitn(3, ':').map(str::parse::<f64>);
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds))) =
+        (time_parts.next(), time_parts.next(), time_parts.next())
+    else {
+        return Err(invalid());
+    };
+
+    let days = days_from_civil(year, month, day) as f64;
+    Ok(days * 86400.0 + hours * 3600.0 + minutes * 60.0 + seconds - offset)
+}
+
+/// Returns the number of days between the Unix epoch and a date of the proleptic Gregorian
+/// calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("12.5").unwrap(), 12.5);
+        assert_eq!(parse_time("1970-01-01T00:00:00Z").unwrap(), 0.0);
+        assert_eq!(parse_time("2000-03-01T00:00:00Z").unwrap(), 951_868_800.0);
+        assert_eq!(parse_time("2023-05-01T12:30:00Z").unwrap(), 1_682_944_200.0);
+        assert_eq!(
+            parse_time("2023-05-01T14:30:00.5+02:00").unwrap(),
+            1_682_944_200.5
+        );
+        assert_eq!(
+            parse_time("2023-05-01 10:00:00-02:30").unwrap(),
+            1_682_944_200.0
+        );
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_gpx() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test">
+  <trk><name>Test</name><trkseg>
+    <trkpt lat="50.1" lon="8.6"><ele>110</ele><time>2023-05-01T12:30:00Z</time></trkpt>
+    <trkpt lon='8.61' lat='50.2'>
+      <time>2023-05-01T12:30:10Z</time>
+    </trkpt>
+    <trkpt lat="50.3" lon="8.62"/>
+    <trkpt
+	lat = "50.4"	lon
+=	'8.63' />
+  </trkseg></trk>
+</gpx>"#;
+        let fixes = parse_gpx(gpx).unwrap();
+        assert_eq!(fixes.len(), 4);
+        assert_eq!(fixes[0].location, Coord { x: 8.6, y: 50.1 });
+        assert_eq!(fixes[0].time, Some(1_682_944_200.0));
+        assert_eq!(fixes[1].location, Coord { x: 8.61, y: 50.2 });
+        assert_eq!(fixes[1].time, Some(1_682_944_210.0));
+        assert_eq!(fixes[2].time, None);
+        assert_eq!(fixes[3].location, Coord { x: 8.63, y: 50.4 });
+
+        assert!(parse_gpx(r#"<trkpt lat="50.1"/>"#).is_err());
+        assert!(parse_gpx(r#"<trkpt lat="50.1" xlon="8.6"/>"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "Timestamp, Longitude, Latitude\n0,8.6,50.1\n\n2023-05-01T12:30:00Z,8.61,50.2\n";
+        let fixes = parse_csv(csv).unwrap();
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fixes[0].time, Some(0.0));
+        assert_eq!(fixes[0].location, Coord { x: 8.6, y: 50.1 });
+        assert_eq!(fixes[1].time, Some(1_682_944_200.0));
+
+        let fixes = parse_csv("lat,lng\n50.1,8.6\n").unwrap();
+        assert_eq!(fixes[0].time, None);
+        assert_eq!(fixes[0].location, Coord { x: 8.6, y: 50.1 });
+
+        assert!(parse_csv("time,lat\n0,50.1\n").is_err());
+        assert!(parse_csv("lat,lon\n50.1\n").is_err());
+    }
+}
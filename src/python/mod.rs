@@ -6,8 +6,10 @@ use pythonize::pythonize;
 
 use crate::importer::overpass_importer::OverpassImporter;
 use crate::importer::RailwayGraphImporter;
+use crate::railway_algorithms::{KShortestPathsOptions, PathFinding, StationIndex};
 use crate::railway_model::railway_graph::RailwayGraphExt;
-use crate::railway_model::RailwayGraph;
+use crate::railway_model::{RailwayGraph, RailwayTags};
+use crate::statistics::{network_diameter, network_statistics};
 use crate::types::{EdgeId, NodeId};
 
 mod overpass_api_client;
@@ -39,9 +41,15 @@ impl PyOverpassImporter {
 
         let railway_graph = OverpassImporter::import(&json_value)
             .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", err)))?;
+        let tags = OverpassImporter::import_tags(&json_value)
+            .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", err)))?;
+        let stations = OverpassImporter::import_nodes(&json_value)
+            .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", err)))?;
 
         Ok(PyRailwayGraph {
+            stations: StationIndex::new(&railway_graph, &tags, &stations),
             inner: railway_graph,
+            tags,
         })
     }
 }
@@ -67,6 +75,8 @@ pub fn export_svg(graph: &PyRailwayGraph) -> PyResult<String> {
 #[pyclass]
 pub struct PyRailwayGraph {
     inner: RailwayGraph,
+    tags: RailwayTags,
+    stations: StationIndex,
 }
 
 #[pymethods]
@@ -122,6 +132,131 @@ impl PyRailwayGraph {
                 .unbind()
         })))
     }
+
+    /// Search the nodes by the name or ref of their station.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The name or the beginning of the name to search for, e.g. `Frankfurt Hbf`.
+    ///   Small typos are matched.
+    /// * `limit` - The maximum number of matches.
+    ///
+    /// # Returns
+    ///
+    /// * A list of dictionaries with the `node_id`, the `track_node_id` to route from or to,
+    ///   the matched `name` and the `score` of each match, the best match first.
+    #[pyo3(signature = (query, limit=10))]
+    fn search_stations(&self, query: &str, limit: usize) -> PyResult<Py<PyAny>> {
+        let matches = self.stations.search(query, limit);
+        Python::attach(|py| {
+            pythonize(py, &matches)
+                .map(|value| value.unbind())
+                .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", err)))
+        })
+    }
+
+    /// Find the track node of the station best matching a name.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The name or ref of the station.
+    ///
+    /// # Returns
+    ///
+    /// * The ID of the node to route from or to, or `None` if no station matches.
+    fn find_station(&self, query: &str) -> Option<NodeId> {
+        self.stations.find_node(query)
+    }
+
+    /// Get statistics of the railway network.
+    ///
+    /// # Arguments
+    ///
+    /// * `diameter` - Also calculate the diameter of the network, which is slow on large
+    ///   networks.
+    ///
+    /// # Returns
+    ///
+    /// * A dictionary with the track length in total and per type, the number of switches,
+    ///   buffer stops and crossings, the node degree distribution, the connected components,
+    ///   the diameter, `None` unless requested, and edge length percentiles.
+    #[pyo3(signature = (diameter=false))]
+    fn statistics(&self, diameter: bool) -> PyResult<Py<PyAny>> {
+        let mut statistics = network_statistics(&self.inner, &self.tags);
+        if diameter {
+            statistics.diameter = Some(network_diameter(&self.inner));
+        }
+        Python::attach(|py| {
+            pythonize(py, &statistics)
+                .map(|value| value.unbind())
+                .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", err)))
+        })
+    }
+
+    /// Find the shortest alternative routes between two nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The ID of the start node.
+    /// * `end` - The ID of the end node.
+    /// * `k` - The maximum number of routes.
+    /// * `min_difference` - The minimum share of a route's length, between 0 and 1, that is not
+    ///   on the edges of a shorter route.
+    ///
+    /// # Returns
+    ///
+    /// * A list of dictionaries with the `nodes`, `edges` and `length` of each route, the
+    ///   shortest first.
+    #[pyo3(signature = (start, end, k=3, min_difference=0.0))]
+    fn k_shortest_paths(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        k: usize,
+        min_difference: f64,
+    ) -> PyResult<Py<PyAny>> {
+        let options = KShortestPathsOptions { k, min_difference };
+        let routes = self.inner.k_shortest_paths(start, end, &options);
+        Python::attach(|py| {
+            pythonize(py, &routes)
+                .map(|value| value.unbind())
+                .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{}", err)))
+        })
+    }
+
+    /// Calculate the shortest path distances between origin and destination nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `origins` - The IDs of the origin nodes.
+    /// * `destinations` - The IDs of the destination nodes, or `None` to use the origins.
+    ///
+    /// # Returns
+    ///
+    /// * A numpy array of the distances in meters with a row per origin and a column per
+    ///   destination. Unreachable destinations are `nan`.
+    #[pyo3(signature = (origins, destinations=None))]
+    fn distance_matrix(
+        &self,
+        origins: Vec<NodeId>,
+        destinations: Option<Vec<NodeId>>,
+    ) -> PyResult<Py<PyAny>> {
+        let destinations = destinations.unwrap_or_else(|| origins.clone());
+        let matrix = self.inner.distance_matrix(&origins, &destinations);
+        let rows: Vec<Vec<f64>> = matrix
+            .distances
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|distance| distance.unwrap_or(f64::NAN))
+                    .collect()
+            })
+            .collect();
+        Python::attach(|py| {
+            let numpy = py.import("numpy")?;
+            Ok(numpy.call_method1("array", (rows,))?.unbind())
+        })
+    }
 }
 
 /// Initialize the openrailwaymap_exporter Python module.
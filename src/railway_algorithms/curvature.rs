@@ -0,0 +1,161 @@
+use geo::Coord;
+use uom::si::{f64::Length, length::meter};
+
+use crate::{
+    algorithms::Distance,
+    crs::Crs,
+    prelude::{CurvatureProfile, CurvatureSample, RailwayCurvature, RailwayEdge, RailwayGraph},
+};
+
+/// Curvatures below this value, radii above 1000 km, are treated as straight track.
+const STRAIGHT_CURVATURE: f64 = 1e-6;
+
+/// Options for the curvature analysis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurvatureOptions {
+    /// The minimum distance of the neighbouring points used to compute the radius at a vertex.
+    ///
+    /// OpenStreetMap geometries often contain vertices only a few meters apart, whose small
+    /// errors would result in unrealistically tight radii. Closer vertices are skipped.
+    pub min_chord: Length,
+}
+
+impl Default for CurvatureOptions {
+    fn default() -> Self {
+        Self {
+            min_chord: Length::new::<meter>(10.0),
+        }
+    }
+}
+
+/// Horizontal curvature analysis of railway graphs.
+pub trait RailwayGraphCurvature {
+    /// Computes the curvature profile of every edge of the graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Options for the analysis.
+    ///
+    /// # Returns
+    ///
+    /// A `RailwayCurvature` with the curvature profiles of the edges.
+    fn curvature_profiles(&self, options: &CurvatureOptions) -> RailwayCurvature;
+}
+
+impl RailwayGraphCurvature for RailwayGraph {
+    fn curvature_profiles(&self, options: &CurvatureOptions) -> RailwayCurvature {
+        let edges = self
+            .physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| (edge.id, curvature_profile(edge, options)))
+            .collect();
+        RailwayCurvature { edges }
+    }
+}
+
+/// Computes the curvature at every interior vertex of an edge's geometry.
+///
+/// The geometry is projected onto a plane tangent at its first vertex and the radius at each
+/// vertex is that of the circle through the vertex and its neighbours.
+pub fn curvature_profile(edge: &RailwayEdge, options: &CurvatureOptions) -> CurvatureProfile {
+    let coords = &edge.path.0;
+    let Some(origin) = coords.first() else {
+        return CurvatureProfile::default();
+    };
+    let crs = Crs::LocalTangentPlane { origin: *origin };
+    let points: Vec<Coord<f64>> = coords.iter().map(|c| crs.project(*c)).collect();
+    let mut offsets = vec![0.0];
+    for pair in coords.windows(2) {
+        offsets.push(offsets.last().unwrap() + pair[0].distance(&pair[1]).get::<meter>());
+    }
+
+    let half_chord = options.min_chord.get::<meter>() / 2.0;
+    let samples = (1..points.len().saturating_sub(1))
+        .map(|index| {
+            let previous = (0..index)
+                .rev()
+                .find(|i| offsets[index] - offsets[*i] >= half_chord)
+                .unwrap_or(0);
+            let next = (index + 1..points.len())
+                .find(|i| offsets[*i] - offsets[index] >= half_chord)
+                .unwrap_or(points.len() - 1);
+            CurvatureSample {
+                offset: offsets[index],
+                curvature: curvature(points[previous], points[index], points[next]),
+            }
+        })
+        .collect();
+    CurvatureProfile { samples }
+}
+
+/// Returns the signed curvature of the circle through three points, positive for left turns.
+fn curvature(a: Coord<f64>, b: Coord<f64>, c: Coord<f64>) -> f64 {
+    let length = |p: Coord<f64>, q: Coord<f64>| ((q.x - p.x).powi(2) + (q.y - p.y).powi(2)).sqrt();
+    let cross = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    let lengths = length(a, b) * length(b, c) * length(a, c);
+    if lengths == 0.0 {
+        return 0.0;
+    }
+    let curvature = 2.0 * cross / lengths;
+    if curvature.abs() < STRAIGHT_CURVATURE {
+        0.0
+    } else {
+        curvature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::CantConfig;
+    use crate::railway_algorithms::tests::test_elements;
+    use approx::assert_relative_eq;
+    use geo::LineString;
+
+    /// Builds an edge along a left hand arc with the given radius, with a vertex every 20 m.
+    fn arc_edge(radius: f64) -> RailwayEdge {
+        let crs = Crs::LocalTangentPlane {
+            origin: Coord { x: 8.68, y: 50.11 },
+        };
+        let coords: Vec<Coord<f64>> = (0..20)
+            .map(|i| {
+                let angle = i as f64 * 20.0 / radius;
+                crs.unproject(Coord {
+                    x: radius * angle.sin(),
+                    y: radius * (1.0 - angle.cos()),
+                })
+            })
+            .collect();
+        RailwayEdge {
+            id: 1,
+            length: 380.0,
+            path: LineString::from(coords),
+            source: 1,
+            target: 2,
+        }
+    }
+
+    #[test]
+    fn test_curvature_profile_of_arc() {
+        let profile = curvature_profile(&arc_edge(500.0), &CurvatureOptions::default());
+
+        assert_eq!(profile.samples.len(), 18);
+        for sample in &profile.samples {
+            assert!(sample.curvature > 0.0);
+            assert_relative_eq!(sample.radius().unwrap(), 500.0, max_relative = 0.01);
+        }
+        assert_relative_eq!(profile.min_radius().unwrap(), 500.0, max_relative = 0.01);
+    }
+
+    #[test]
+    fn test_curvature_profiles() {
+        let graph = from_railway_elements(&test_elements());
+        let curvature = graph.curvature_profiles(&CurvatureOptions::default());
+
+        // Both edges are straight lines between two nodes.
+        assert_eq!(curvature.min_radius(4), None);
+        assert_eq!(curvature.permissible_speed(5, &CantConfig::default()), None);
+    }
+}
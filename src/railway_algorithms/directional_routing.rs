@@ -0,0 +1,722 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use petgraph::{graph::EdgeIndex, visit::EdgeRef};
+use serde::{Deserialize, Serialize};
+use uom::si::{f64::Length, length::meter};
+
+use crate::{
+    prelude::{RailwayGraph, RoutingConstraints, TrackDirection},
+    types::{EdgeId, NodeId},
+};
+
+/// Rules for where trains may reverse their direction of travel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReversalOptions {
+    /// Nodes where trains may always reverse, e.g. terminal platforms or headshunts.
+    pub reversal_points: HashSet<NodeId>,
+    /// The length of the train. Away from dead ends and reversal points, a train may only
+    /// reverse once its whole length has cleared the last switch behind it.
+    pub train_length: Length,
+    /// The cost of a reversal as an equivalent travel distance, accounting for the time needed
+    /// to stop and change ends.
+    pub reversal_penalty: Length,
+}
+
+impl Default for ReversalOptions {
+    fn default() -> Self {
+        Self {
+            reversal_points: HashSet::new(),
+            train_length: Length::new::<meter>(200.0),
+            reversal_penalty: Length::new::<meter>(2000.0),
+        }
+    }
+}
+
+/// A route found by the direction-aware search.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirectionalRoute {
+    /// The IDs of the nodes along the route, including the start and end node. Nodes are
+    /// repeated where the train turns back.
+    pub nodes: Vec<NodeId>,
+    /// The indices into `nodes` of the nodes where the train reverses.
+    pub reversals: Vec<usize>,
+    /// The distance travelled in meters.
+    pub length: f64,
+    /// The distance travelled plus the reversal penalties in meters, with track run against its
+    /// direction weighted by the `DirectionOptions` of the constraints, if any.
+    pub cost: f64,
+}
+
+/// Path finding where the search state is a node together with the direction of travel.
+///
+/// Trains may only reverse at dead ends, at reversal points or once they have cleared a switch.
+pub trait DirectionalPathFinding {
+    /// Finds the cheapest route between two nodes for a train that may only reverse where the
+    /// `ReversalOptions` allow it.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The ID of the start node.
+    /// * `previous` - The ID of the node the train arrived at `start` from, or `None` if it may
+    ///   depart in either direction.
+    /// * `end` - The ID of the end node.
+    /// * `options` - Where trains may reverse and what a reversal costs.
+    ///
+    /// # Returns
+    ///
+    /// The `DirectionalRoute` with the lowest cost, or `None` if the end node cannot be
+    /// reached.
+    fn directional_path(
+        &self,
+        start: NodeId,
+        previous: Option<NodeId>,
+        end: NodeId,
+        options: &ReversalOptions,
+    ) -> Option<DirectionalRoute>;
+
+    /// Returns the next node on the direction-aware route to a target.
+    ///
+    /// The next node is `previous` if the train has to turn back at `current`.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The ID of the current node.
+    /// * `previous` - The ID of the node the train arrived from, or `None` if it may depart in
+    ///   either direction.
+    /// * `target` - The ID of the target node.
+    /// * `options` - Where trains may reverse and what a reversal costs.
+    fn next_directional_node(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+        options: &ReversalOptions,
+    ) -> Option<NodeId>;
+
+    /// Finds the cheapest route between two nodes like `directional_path`, over the edges a
+    /// vehicle may use.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The ID of the start node.
+    /// * `previous` - The ID of the node the train arrived at `start` from, or `None` if it may
+    ///   depart in either direction.
+    /// * `end` - The ID of the end node.
+    /// * `options` - Where trains may reverse and what a reversal costs.
+    /// * `constraints` - The vehicle and the attributes of the edges.
+    fn compatible_directional_path(
+        &self,
+        start: NodeId,
+        previous: Option<NodeId>,
+        end: NodeId,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Option<DirectionalRoute>;
+
+    /// Returns the next node on the direction-aware route to a target over the edges a
+    /// vehicle may use.
+    ///
+    /// The arguments are the same as for `next_directional_node`, with the vehicle and the
+    /// attributes of the edges as `constraints`.
+    fn next_compatible_node(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Option<NodeId>;
+
+    /// Finds the nodes a train can reach over the edges a vehicle may use, turning back only
+    /// where the `ReversalOptions` allow it.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The ID of the start node.
+    /// * `previous` - The ID of the node the train arrived at `start` from, or `None` if it may
+    ///   depart in either direction.
+    /// * `options` - Where trains may reverse.
+    /// * `constraints` - The vehicle and the attributes of the edges.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the reachable nodes other than `start`, in the order they are found. Empty
+    /// if the start node is not found in the graph.
+    fn compatible_reachable_nodes(
+        &self,
+        start: NodeId,
+        previous: Option<NodeId>,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Vec<NodeId>;
+}
+
+impl DirectionalPathFinding for RailwayGraph {
+    fn directional_path(
+        &self,
+        start: NodeId,
+        previous: Option<NodeId>,
+        end: NodeId,
+        options: &ReversalOptions,
+    ) -> Option<DirectionalRoute> {
+        directional_route(self, start, previous, end, options, None)
+    }
+
+    fn next_directional_node(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+        options: &ReversalOptions,
+    ) -> Option<NodeId> {
+        let route = self.directional_path(current, previous, target, options)?;
+        route.nodes.get(1).copied()
+    }
+
+    fn compatible_directional_path(
+        &self,
+        start: NodeId,
+        previous: Option<NodeId>,
+        end: NodeId,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Option<DirectionalRoute> {
+        directional_route(self, start, previous, end, options, Some(constraints))
+    }
+
+    fn next_compatible_node(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Option<NodeId> {
+        let route =
+            self.compatible_directional_path(current, previous, target, options, constraints)?;
+        route.nodes.get(1).copied()
+    }
+
+    fn compatible_reachable_nodes(
+        &self,
+        start: NodeId,
+        previous: Option<NodeId>,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Vec<NodeId> {
+        let Some(&(start_1, start_2)) = self.topology_graph.id_to_index(start) else {
+            return Vec::new();
+        };
+        let search = DirectionalSearch::new(self, options, Some(constraints));
+        let topology = &self.topology_graph.graph;
+
+        let mut queue: Vec<EdgeIndex> =
+            match previous.and_then(|previous| search.arrival_edge(previous, start)) {
+                Some(arrival) => search
+                    .successors(arrival)
+                    .into_iter()
+                    .map(|(edge, _, _)| edge)
+                    .collect(),
+                None => topology
+                    .edges(start_1)
+                    .chain(topology.edges(start_2))
+                    .map(|edge| edge.id())
+                    .filter(|&edge| search.cost(edge).is_some())
+                    .collect(),
+            };
+        let mut visited: HashSet<EdgeIndex> = queue.iter().copied().collect();
+        let mut seen = HashSet::from([start]);
+        let mut nodes = Vec::new();
+        while let Some(edge) = queue.pop() {
+            if let Some(node) = search.node_ahead(edge) {
+                if seen.insert(node) {
+                    nodes.push(node);
+                }
+            }
+            for (next, _, _) in search.successors(edge) {
+                if visited.insert(next) {
+                    queue.push(next);
+                }
+            }
+        }
+        nodes
+    }
+}
+
+/// Runs the direction-aware search, over the edges allowed by `constraints` if given.
+fn directional_route(
+    graph: &RailwayGraph,
+    start: NodeId,
+    previous: Option<NodeId>,
+    end: NodeId,
+    options: &ReversalOptions,
+    constraints: Option<RoutingConstraints>,
+) -> Option<DirectionalRoute> {
+    let &(start_1, start_2) = graph.topology_graph.id_to_index(start)?;
+    graph.topology_graph.id_to_index(end)?;
+    if start == end {
+        return Some(DirectionalRoute {
+            nodes: vec![start],
+            ..Default::default()
+        });
+    }
+
+    let search = DirectionalSearch::new(graph, options, constraints);
+    let topology = &graph.topology_graph.graph;
+
+    // The search runs over the topology edges a train has last travelled along, as the
+    // reversal rules depend on the track behind the train.
+    let mut costs: HashMap<EdgeIndex, f64> = HashMap::new();
+    let mut parents: HashMap<EdgeIndex, (EdgeIndex, bool)> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    let arrival = previous.and_then(|previous| search.arrival_edge(previous, start));
+    match arrival {
+        Some(arrival) => {
+            costs.insert(arrival, 0.0);
+            heap.push(State(0.0, arrival));
+        }
+        None => {
+            for edge in topology.edges(start_1).chain(topology.edges(start_2)) {
+                let Some(cost) = search.cost(edge.id()) else {
+                    continue;
+                };
+                if costs.get(&edge.id()).is_none_or(|&known| cost < known) {
+                    costs.insert(edge.id(), cost);
+                    heap.push(State(cost, edge.id()));
+                }
+            }
+        }
+    }
+
+    let mut goal = None;
+    while let Some(State(cost, edge)) = heap.pop() {
+        if cost > costs[&edge] {
+            continue;
+        }
+        let node = search.node_ahead(edge);
+        if node == Some(end) && Some(edge) != arrival {
+            goal = Some(edge);
+            break;
+        }
+
+        for (next_edge, next_cost, reversal) in search.successors(edge) {
+            let next_cost = cost + next_cost;
+            if costs.get(&next_edge).is_none_or(|&known| next_cost < known) {
+                costs.insert(next_edge, next_cost);
+                parents.insert(next_edge, (edge, reversal));
+                heap.push(State(next_cost, next_edge));
+            }
+        }
+    }
+
+    let goal = goal?;
+    let cost = costs[&goal];
+    let mut edges = vec![(goal, false)];
+    while let Some(&(parent, reversal)) = parents.get(&edges.last().unwrap().0) {
+        edges.last_mut().unwrap().1 = reversal;
+        edges.push((parent, false));
+    }
+    if arrival.is_some() {
+        edges.pop();
+    }
+    edges.reverse();
+
+    let mut route = DirectionalRoute {
+        nodes: vec![start],
+        ..Default::default()
+    };
+    for (edge, reversal) in edges {
+        if reversal {
+            route.reversals.push(route.nodes.len() - 1);
+        }
+        route.nodes.push(search.node_ahead(edge)?);
+        route.length += search.length(edge);
+    }
+    route.cost = cost;
+    Some(route)
+}
+
+pub(super) struct DirectionalSearch<'a> {
+    graph: &'a RailwayGraph,
+    options: &'a ReversalOptions,
+    constraints: Option<RoutingConstraints<'a>>,
+    lengths: HashMap<EdgeId, f64>,
+    sources: HashMap<EdgeId, NodeId>,
+}
+
+impl<'a> DirectionalSearch<'a> {
+    pub(super) fn new(
+        graph: &'a RailwayGraph,
+        options: &'a ReversalOptions,
+        constraints: Option<RoutingConstraints<'a>>,
+    ) -> Self {
+        let lengths = graph
+            .physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| (edge.id, edge.length))
+            .collect();
+        let sources = graph
+            .physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| (edge.id, edge.source))
+            .collect();
+        Self {
+            graph,
+            options,
+            constraints,
+            lengths,
+            sources,
+        }
+    }
+
+    /// Returns the cost of running along a topology edge, or `None` if the constraints do not
+    /// allow it.
+    pub(super) fn cost(&self, edge: EdgeIndex) -> Option<f64> {
+        let length = self.length(edge);
+        let Some(constraints) = self.constraints else {
+            return Some(length);
+        };
+        let edge_id = self.graph.topology_graph.graph[edge].edge_id;
+        let direction = if self.sources.get(&edge_id).copied() == self.node_behind(edge) {
+            TrackDirection::Forward
+        } else {
+            TrackDirection::Backward
+        };
+        constraints.cost(edge_id, direction, length)
+    }
+
+    /// Returns the topology edges a train may continue on after running along `edge`, with
+    /// their costs including the reversal penalty, and whether the train turns back onto them.
+    pub(super) fn successors(&self, edge: EdgeIndex) -> Vec<(EdgeIndex, f64, bool)> {
+        let topology = &self.graph.topology_graph.graph;
+        let Some((_, port)) = topology.edge_endpoints(edge) else {
+            return Vec::new();
+        };
+        let mut next: Vec<(EdgeIndex, f64, bool)> = topology
+            .edges(port)
+            .filter_map(|next| Some((next.id(), self.cost(next.id())?, false)))
+            .collect();
+        if let Some(reverse) = self.reversal(edge) {
+            if let Some(cost) = self.cost(reverse) {
+                let penalty = self.options.reversal_penalty.get::<meter>();
+                next.push((reverse, cost + penalty, true));
+            }
+        }
+        next
+    }
+
+    pub(super) fn length(&self, edge: EdgeIndex) -> f64 {
+        let edge_id = self.graph.topology_graph.graph[edge].edge_id;
+        self.lengths.get(&edge_id).copied().unwrap_or(0.0)
+    }
+
+    pub(super) fn node_ahead(&self, edge: EdgeIndex) -> Option<NodeId> {
+        let (_, port) = self.graph.topology_graph.graph.edge_endpoints(edge)?;
+        self.graph.topology_graph.index_to_id(port).copied()
+    }
+
+    fn node_behind(&self, edge: EdgeIndex) -> Option<NodeId> {
+        let (port, _) = self.graph.topology_graph.graph.edge_endpoints(edge)?;
+        self.graph.topology_graph.index_to_id(port).copied()
+    }
+
+    fn degree(&self, node: NodeId) -> usize {
+        self.graph
+            .physical_graph
+            .id_to_index(node)
+            .map_or(0, |&index| {
+                self.graph.physical_graph.graph.edges(index).count()
+            })
+    }
+
+    /// Returns the topology edge a train arriving at `node` from `previous` has travelled along.
+    pub(super) fn arrival_edge(&self, previous: NodeId, node: NodeId) -> Option<EdgeIndex> {
+        let &(port_1, port_2) = self.graph.topology_graph.id_to_index(previous)?;
+        let topology = &self.graph.topology_graph.graph;
+        topology
+            .edges(port_1)
+            .chain(topology.edges(port_2))
+            .find(|edge| self.graph.topology_graph.index_to_id(edge.target()) == Some(&node))
+            .map(|edge| edge.id())
+    }
+
+    /// Returns the topology edge leading back along `edge` if the train may reverse at its end.
+    ///
+    /// A train standing at a switch it has just passed occupies the track it came from, so it
+    /// can only reverse onto that track.
+    pub(super) fn reversal(&self, edge: EdgeIndex) -> Option<EdgeIndex> {
+        let node = self.node_ahead(edge)?;
+        let allowed = self.degree(node) <= 1
+            || self.options.reversal_points.contains(&node)
+            || self.clearance(edge) >= self.options.train_length.get::<meter>();
+        if !allowed {
+            return None;
+        }
+
+        let topology = &self.graph.topology_graph;
+        let (behind, ahead) = topology.graph.edge_endpoints(edge)?;
+        let &(port_1, port_2) = topology.id_to_index(node)?;
+        let other = if ahead == port_1 { port_2 } else { port_1 };
+        let edge_id = topology.graph[edge].edge_id;
+        let previous = topology.index_to_id(behind)?;
+        topology
+            .graph
+            .edges(other)
+            .find(|reverse| {
+                reverse.weight().edge_id == edge_id
+                    && topology.index_to_id(reverse.target()) == Some(previous)
+            })
+            .map(|reverse| reverse.id())
+    }
+
+    /// Returns the distance from the end of `edge` back to the nearest switch behind it, or
+    /// infinity if the track behind ends without a switch.
+    ///
+    /// The search stops once the distance exceeds the train length.
+    fn clearance(&self, edge: EdgeIndex) -> f64 {
+        let physical = &self.graph.physical_graph;
+        let train_length = self.options.train_length.get::<meter>();
+        let mut edge_id = self.graph.topology_graph.graph[edge].edge_id;
+        let mut distance = self.length(edge);
+        let Some(mut node) = self.node_behind(edge) else {
+            return 0.0;
+        };
+
+        while distance < train_length {
+            match self.degree(node) {
+                0 | 1 => return f64::INFINITY,
+                2 => {}
+                _ => return distance,
+            }
+            let Some(&index) = physical.id_to_index(node) else {
+                return distance;
+            };
+            let Some(behind) = physical
+                .graph
+                .edges(index)
+                .map(|edge| edge.weight())
+                .find(|edge| edge.id != edge_id)
+            else {
+                return distance;
+            };
+            edge_id = behind.id;
+            distance += behind.length;
+            node = if behind.source == node {
+                behind.target
+            } else {
+                behind.source
+            };
+        }
+        distance
+    }
+}
+
+#[derive(PartialEq)]
+struct State(f64, EdgeIndex);
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::{tests::siding_elements, PathFinding};
+    use crate::railway_model::RailwayGraphExt;
+
+    fn options(train_length: f64) -> ReversalOptions {
+        ReversalOptions {
+            train_length: Length::new::<meter>(train_length),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reversal_after_clearing_switch() {
+        let graph = from_railway_elements(&siding_elements());
+        let length_11_12 = graph.railway_edge(11, 12).unwrap().length;
+
+        let route = graph
+            .directional_path(13, None, 14, &options(length_11_12 - 1.0))
+            .unwrap();
+        assert_eq!(route.nodes, vec![13, 12, 11, 12, 14]);
+        assert_eq!(route.reversals, vec![2]);
+        let edges_length: f64 = route
+            .nodes
+            .windows(2)
+            .map(|pair| graph.railway_edge(pair[0], pair[1]).unwrap().length)
+            .sum();
+        approx::assert_relative_eq!(route.length, edges_length);
+        approx::assert_relative_eq!(
+            route.cost,
+            route.length + ReversalOptions::default().reversal_penalty.get::<meter>()
+        );
+    }
+
+    #[test]
+    fn test_reversal_at_dead_end_or_reversal_point() {
+        let graph = from_railway_elements(&siding_elements());
+        let length_11_12 = graph.railway_edge(11, 12).unwrap().length;
+
+        let long_train = options(length_11_12 + 1.0);
+        let route = graph.directional_path(13, None, 14, &long_train).unwrap();
+        assert_eq!(route.nodes, vec![13, 12, 11, 10, 11, 12, 14]);
+        assert_eq!(route.reversals, vec![3]);
+
+        let with_reversal_point = ReversalOptions {
+            reversal_points: HashSet::from([11]),
+            ..long_train
+        };
+        let route = graph
+            .directional_path(13, None, 14, &with_reversal_point)
+            .unwrap();
+        assert_eq!(route.nodes, vec![13, 12, 11, 12, 14]);
+    }
+
+    #[test]
+    fn test_directional_path_heading() {
+        let graph = from_railway_elements(&siding_elements());
+        let options = ReversalOptions::default();
+
+        // Arriving at 11 from 10, the train continues without reversing.
+        let route = graph.directional_path(11, Some(10), 12, &options).unwrap();
+        assert_eq!(route.nodes, vec![11, 12]);
+        assert!(route.reversals.is_empty());
+
+        // Arriving at 11 from 12, the train has cleared the switch and turns back.
+        let route = graph.directional_path(11, Some(12), 13, &options).unwrap();
+        assert_eq!(route.nodes, vec![11, 12, 13]);
+        assert_eq!(route.reversals, vec![0]);
+        assert_eq!(
+            graph.next_directional_node(11, Some(12), 13, &options),
+            Some(12)
+        );
+
+        assert_eq!(graph.directional_path(13, None, 99, &options), None);
+        assert_eq!(
+            graph
+                .directional_path(13, None, 13, &options)
+                .unwrap()
+                .nodes,
+            vec![13]
+        );
+    }
+
+    #[test]
+    fn test_branch_to_branch_needs_reversal() {
+        let graph = from_railway_elements(&siding_elements());
+
+        assert_eq!(graph.shortest_path_nodes(13, 14), None);
+        assert_eq!(
+            graph.next_directional_node(13, None, 14, &ReversalOptions::default()),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_compatible_directional_path() {
+        use crate::prelude::{RailwayTrackAttributes, TrackAttributes, Traction, VehicleProfile};
+
+        let graph = from_railway_elements(&siding_elements());
+        let length_11_12 = graph.railway_edge(11, 12).unwrap().length;
+        let long_train = options(length_11_12 + 1.0);
+        let mut attributes = RailwayTrackAttributes::default();
+        attributes.edges.insert(
+            20,
+            TrackAttributes {
+                electrified: Some(false),
+                ..Default::default()
+            },
+        );
+        let electric = VehicleProfile {
+            traction: Traction::Electric { voltages: vec![] },
+            ..Default::default()
+        };
+        let constraints = RoutingConstraints {
+            vehicle: Some(&electric),
+            ..RoutingConstraints::new(&attributes)
+        };
+
+        // The electric train cannot run onto the unelectrified track to the dead end 10 to
+        // reverse.
+        assert_eq!(
+            graph.compatible_directional_path(13, None, 14, &long_train, constraints),
+            None
+        );
+        assert_eq!(
+            graph.next_compatible_node(13, None, 14, &long_train, constraints),
+            None
+        );
+
+        let with_reversal_point = ReversalOptions {
+            reversal_points: HashSet::from([11]),
+            ..long_train
+        };
+        let route = graph
+            .compatible_directional_path(13, None, 14, &with_reversal_point, constraints)
+            .unwrap();
+        assert_eq!(route.nodes, vec![13, 12, 11, 12, 14]);
+
+        let diesel = VehicleProfile::default();
+        let constraints = RoutingConstraints {
+            vehicle: Some(&diesel),
+            ..RoutingConstraints::new(&attributes)
+        };
+        assert_eq!(
+            graph.next_compatible_node(13, None, 10, &long_train, constraints),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_compatible_reachable_nodes() {
+        use crate::prelude::{RailwayTrackAttributes, TrackAttributes, Traction, VehicleProfile};
+
+        let graph = from_railway_elements(&siding_elements());
+        let long_train = options(graph.railway_edge(11, 12).unwrap().length + 1.0);
+        let mut attributes = RailwayTrackAttributes::default();
+        attributes.edges.insert(
+            20,
+            TrackAttributes {
+                electrified: Some(false),
+                ..Default::default()
+            },
+        );
+        let reachable = |vehicle: &VehicleProfile| {
+            let constraints = RoutingConstraints {
+                vehicle: Some(vehicle),
+                ..RoutingConstraints::new(&attributes)
+            };
+            let mut nodes = graph.compatible_reachable_nodes(13, None, &long_train, constraints);
+            nodes.sort_unstable();
+            nodes
+        };
+
+        // Only the diesel train can reverse at the dead end 10 to reach the branch to 14.
+        assert_eq!(reachable(&VehicleProfile::default()), vec![10, 11, 12, 14]);
+        let electric = VehicleProfile {
+            traction: Traction::Electric { voltages: vec![] },
+            ..Default::default()
+        };
+        assert_eq!(reachable(&electric), vec![11, 12]);
+        assert!(graph
+            .compatible_reachable_nodes(99, None, &long_train, RoutingConstraints::new(&attributes))
+            .is_empty());
+    }
+}
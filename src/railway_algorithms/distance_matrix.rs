@@ -0,0 +1,213 @@
+use std::{collections::HashSet, fmt::Write, num::NonZeroUsize, thread};
+
+use petgraph::{algo::dijkstra, visit::EdgeFiltered};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::RailwayGraph,
+    types::{EdgeId, NodeId},
+};
+
+/// The shortest path distances between a set of origin and a set of destination nodes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DistanceMatrix {
+    /// The IDs of the origin nodes, one per row.
+    pub origins: Vec<NodeId>,
+    /// The IDs of the destination nodes, one per column.
+    pub destinations: Vec<NodeId>,
+    /// The distances in meters with a row per origin and a column per destination, or `None`
+    /// where the destination cannot be reached or a node does not exist.
+    pub distances: Vec<Vec<Option<f64>>>,
+}
+
+impl DistanceMatrix {
+    /// Returns the distance in meters from an origin to a destination node.
+    ///
+    /// # Returns
+    ///
+    /// The distance, or `None` if the destination cannot be reached or either node is not part
+    /// of the matrix.
+    pub fn get(&self, origin: NodeId, destination: NodeId) -> Option<f64> {
+        let row = self.origins.iter().position(|&id| id == origin)?;
+        let column = self.destinations.iter().position(|&id| id == destination)?;
+        self.distances[row][column]
+    }
+
+    /// Writes the matrix as CSV.
+    ///
+    /// The header holds `origin` followed by the destination IDs and every row starts with the
+    /// origin ID. Unreachable destinations are left empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("origin");
+        for destination in &self.destinations {
+            write!(csv, ",{}", destination).unwrap();
+        }
+        csv.push('\n');
+        for (origin, row) in self.origins.iter().zip(&self.distances) {
+            write!(csv, "{}", origin).unwrap();
+            for distance in row {
+                csv.push(',');
+                if let Some(distance) = distance {
+                    write!(csv, "{}", distance).unwrap();
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Computes the distance matrix with one shortest path search per origin.
+///
+/// The origins are split between as many threads as the machine has cores, each running a full
+/// Dijkstra search on the physical graph per origin. The edges in `closed` are left out.
+pub(super) fn distance_matrix(
+    graph: &RailwayGraph,
+    origins: &[NodeId],
+    destinations: &[NodeId],
+    closed: &HashSet<EdgeId>,
+) -> DistanceMatrix {
+    let distances = map_chunks(origins, |chunk| {
+        chunk
+            .iter()
+            .map(|&origin| distance_row(graph, origin, destinations, closed))
+            .collect::<Vec<_>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+
+    DistanceMatrix {
+        origins: origins.to_vec(),
+        destinations: destinations.to_vec(),
+        distances,
+    }
+}
+
+/// Splits `items` into a chunk per core and maps each chunk on its own thread, keeping their
+/// order.
+///
+/// A single chunk is mapped on the current thread, as are all items on `wasm32`, where threads
+/// cannot be spawned.
+pub(super) fn map_chunks<T: Sync, R: Send>(items: &[T], map: impl Fn(&[T]) -> R + Sync) -> Vec<R> {
+    let threads = if cfg!(target_arch = "wasm32") {
+        1
+    } else {
+        thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1)
+    };
+    let chunk_size = items.len().div_ceil(threads).max(1);
+    if items.len() <= chunk_size {
+        return vec![map(items)];
+    }
+
+    let map = &map;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || map(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn distance_row(
+    graph: &RailwayGraph,
+    origin: NodeId,
+    destinations: &[NodeId],
+    closed: &HashSet<EdgeId>,
+) -> Vec<Option<f64>> {
+    let physical = &graph.physical_graph;
+    let Some(&origin_index) = physical.id_to_index(origin) else {
+        return vec![None; destinations.len()];
+    };
+    let open = EdgeFiltered::from_fn(&physical.graph, |edge| !closed.contains(&edge.weight().id));
+    let distances = dijkstra(&open, origin_index, None, |edge| edge.weight().length);
+    destinations
+        .iter()
+        .map(|&destination| {
+            let index = physical.id_to_index(destination)?;
+            distances.get(index).copied()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::{tests::siding_elements, PathFinding};
+    use crate::tests::test_graph_vilbel;
+
+    #[test]
+    fn test_map_chunks() {
+        let items: Vec<u32> = (1..=100).collect();
+        let sums = map_chunks(&items, |chunk| chunk.iter().sum::<u32>());
+        assert_eq!(sums.iter().sum::<u32>(), 5050);
+        let firsts = map_chunks(&items, |chunk| chunk[0]);
+        assert!(firsts.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(map_chunks(&[7], |chunk| chunk.len()), vec![1]);
+        assert_eq!(map_chunks(&[] as &[u32], |chunk| chunk.len()), vec![0]);
+    }
+
+    #[test]
+    fn test_distance_matrix() {
+        let graph = from_railway_elements(&siding_elements());
+        let matrix = graph.distance_matrix(&[10, 13, 99], &[14, 10, 12]);
+
+        assert_eq!(matrix.origins, vec![10, 13, 99]);
+        assert_eq!(matrix.destinations, vec![14, 10, 12]);
+        assert_eq!(matrix.distances.len(), 3);
+        for &origin in &[10, 13] {
+            for &destination in &[14, 10, 12] {
+                assert_eq!(
+                    matrix.get(origin, destination),
+                    graph.shortest_path_distance(origin, destination)
+                );
+            }
+        }
+        assert_eq!(matrix.get(10, 10), Some(0.0));
+        assert_eq!(matrix.distances[2], vec![None, None, None]);
+        assert_eq!(matrix.get(10, 11), None);
+
+        let json = serde_json::to_value(&matrix).unwrap();
+        assert_eq!(json["distances"][2][0], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_distance_matrix_matches_single_searches() {
+        let graph = test_graph_vilbel();
+        let nodes: Vec<NodeId> = graph
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .step_by(17)
+            .collect();
+        let matrix = graph.distance_matrix(&nodes, &nodes);
+
+        for &origin in &nodes {
+            for &destination in &nodes {
+                assert_eq!(
+                    matrix.get(origin, destination),
+                    graph.shortest_path_distance(origin, destination)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let matrix = DistanceMatrix {
+            origins: vec![1, 2],
+            destinations: vec![3, 4],
+            distances: vec![vec![Some(10.5), None], vec![Some(0.0), Some(7.0)]],
+        };
+        assert_eq!(matrix.to_csv(), "origin,3,4\n1,10.5,\n2,0,7\n");
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::RailwayGraph,
+    types::{EdgeId, NodeId},
+};
+
+/// Candidate routes rejected for being too similar to an accepted route before the search for
+/// further alternatives gives up.
+const MAX_REJECTED_ROUTES: usize = 100;
+
+/// A route through the railway network.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    /// The IDs of the nodes along the route, including the start and end node.
+    pub nodes: Vec<NodeId>,
+    /// The IDs of the edges along the route.
+    pub edges: Vec<EdgeId>,
+    /// The length of the route in meters.
+    pub length: f64,
+}
+
+/// Options for the search of alternative routes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KShortestPathsOptions {
+    /// The maximum number of routes to return.
+    pub k: usize,
+    /// The minimum share, between 0 and 1, of a route's length that has to run on edges not used
+    /// by any shorter route returned. With 0 every loopless route qualifies.
+    pub min_difference: f64,
+}
+
+impl Default for KShortestPathsOptions {
+    fn default() -> Self {
+        Self {
+            k: 3,
+            min_difference: 0.0,
+        }
+    }
+}
+
+/// A path in the topology graph, with the port of every node it visits.
+#[derive(Debug, Clone, PartialEq)]
+struct TopologyPath {
+    nodes: Vec<NodeIndex>,
+    edges: Vec<EdgeIndex>,
+    length: f64,
+}
+
+#[derive(PartialEq)]
+struct Candidate(TopologyPath);
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.length.total_cmp(&self.0.length)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds the shortest loopless routes between two nodes with Yen's algorithm.
+///
+/// Routes follow the allowed transitions of the topology graph, so they never reverse at a
+/// switch, and visit every node at most once. They are ordered by length.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the `RailwayGraph` instance.
+/// * `start` - The ID of the start node.
+/// * `end` - The ID of the end node.
+/// * `options` - The number of routes and how much they have to differ.
+///
+/// # Returns
+///
+/// Up to `options.k` routes, the shortest first. The vector is empty if the nodes are not
+/// connected.
+pub(super) fn k_shortest_paths(
+    graph: &RailwayGraph,
+    start: NodeId,
+    end: NodeId,
+    options: &KShortestPathsOptions,
+) -> Vec<Route> {
+    let topology = &graph.topology_graph;
+    let (Some(&(start_1, start_2)), Some(&(end_1, end_2))) =
+        (topology.id_to_index(start), topology.id_to_index(end))
+    else {
+        return Vec::new();
+    };
+    if options.k == 0 {
+        return Vec::new();
+    }
+
+    let lengths: HashMap<EdgeId, f64> = graph
+        .physical_graph
+        .graph
+        .edge_weights()
+        .map(|edge| (edge.id, edge.length))
+        .collect();
+    let search = PathSearch {
+        graph,
+        lengths: &lengths,
+        goals: [end_1, end_2],
+    };
+
+    let Some(shortest) = search.shortest(&[start_1, start_2], &HashSet::new(), &HashSet::new())
+    else {
+        return Vec::new();
+    };
+    let mut found = vec![shortest];
+    let mut routes = Vec::new();
+    let mut candidates = BinaryHeap::new();
+    let mut rejected = 0;
+
+    loop {
+        let path = found.last().unwrap();
+        let route = search.route(path);
+        if routes
+            .iter()
+            .all(|other| difference(&route, other, &lengths) >= options.min_difference)
+        {
+            routes.push(route);
+            if routes.len() == options.k {
+                break;
+            }
+        } else {
+            rejected += 1;
+            if rejected > MAX_REJECTED_ROUTES {
+                break;
+            }
+        }
+
+        for (index, &spur_node) in path.nodes[..path.nodes.len() - 1].iter().enumerate() {
+            let root_nodes = &path.nodes[..=index];
+            // Routes may leave the start node through either of its ports, so the first spur
+            // searches from both and deviates from every route found so far.
+            let sources = if index == 0 {
+                vec![start_1, start_2]
+            } else {
+                vec![spur_node]
+            };
+            let blocked_edges: HashSet<EdgeIndex> = found
+                .iter()
+                .filter(|other| {
+                    other.nodes.len() > index
+                        && (index == 0 || other.nodes[..=index] == *root_nodes)
+                })
+                .map(|other| other.edges[index])
+                .collect();
+            let blocked_nodes: HashSet<NodeId> = root_nodes
+                .iter()
+                .filter_map(|node| topology.index_to_id(*node).copied())
+                .collect();
+
+            if let Some(spur) = search.shortest(&sources, &blocked_edges, &blocked_nodes) {
+                let root_edges = &path.edges[..index];
+                let candidate = TopologyPath {
+                    nodes: [&root_nodes[..index], &spur.nodes[..]].concat(),
+                    edges: [root_edges, &spur.edges].concat(),
+                    length: root_edges
+                        .iter()
+                        .map(|edge| search.length(*edge))
+                        .sum::<f64>()
+                        + spur.length,
+                };
+                let known = found.iter().any(|other| other.edges == candidate.edges)
+                    || candidates
+                        .iter()
+                        .any(|Candidate(other)| other.edges == candidate.edges);
+                if !known {
+                    candidates.push(Candidate(candidate));
+                }
+            }
+        }
+
+        match candidates.pop() {
+            Some(Candidate(candidate)) => found.push(candidate),
+            None => break,
+        }
+    }
+
+    routes
+}
+
+/// Returns the share of the length of `route` that is not on the edges of `other`.
+fn difference(route: &Route, other: &Route, lengths: &HashMap<EdgeId, f64>) -> f64 {
+    if route.length <= 0.0 {
+        return 0.0;
+    }
+    let shared: f64 = route
+        .edges
+        .iter()
+        .filter(|edge| other.edges.contains(edge))
+        .filter_map(|edge| lengths.get(edge))
+        .sum();
+    1.0 - shared / route.length
+}
+
+struct PathSearch<'a> {
+    graph: &'a RailwayGraph,
+    lengths: &'a HashMap<EdgeId, f64>,
+    goals: [NodeIndex; 2],
+}
+
+impl PathSearch<'_> {
+    fn length(&self, edge: EdgeIndex) -> f64 {
+        let edge_id = self.graph.topology_graph.graph[edge].edge_id;
+        self.lengths.get(&edge_id).copied().unwrap_or(0.0)
+    }
+
+    /// Runs Dijkstra's algorithm on the topology graph from the given ports to either port of
+    /// the end node, skipping the blocked edges and the nodes with a blocked ID.
+    fn shortest(
+        &self,
+        sources: &[NodeIndex],
+        blocked_edges: &HashSet<EdgeIndex>,
+        blocked_nodes: &HashSet<NodeId>,
+    ) -> Option<TopologyPath> {
+        let topology = &self.graph.topology_graph;
+        let mut distances: HashMap<NodeIndex, f64> = HashMap::new();
+        let mut previous: HashMap<NodeIndex, EdgeIndex> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        for &source in sources {
+            distances.insert(source, 0.0);
+            heap.push(Visit(0.0, source));
+        }
+
+        while let Some(Visit(distance, node)) = heap.pop() {
+            if distance > distances[&node] {
+                continue;
+            }
+            if self.goals.contains(&node) {
+                let mut nodes = vec![node];
+                let mut edges = Vec::new();
+                while let Some(&edge) = previous.get(nodes.last().unwrap()) {
+                    edges.push(edge);
+                    nodes.push(topology.graph.edge_endpoints(edge)?.0);
+                }
+                nodes.reverse();
+                edges.reverse();
+                return Some(TopologyPath {
+                    nodes,
+                    edges,
+                    length: distance,
+                });
+            }
+            for edge in topology.graph.edges(node) {
+                let target = edge.target();
+                if blocked_edges.contains(&edge.id())
+                    || topology
+                        .index_to_id(target)
+                        .is_some_and(|id| blocked_nodes.contains(id))
+                {
+                    continue;
+                }
+                let next = distance + self.length(edge.id());
+                if distances.get(&target).is_none_or(|&known| next < known) {
+                    distances.insert(target, next);
+                    previous.insert(target, edge.id());
+                    heap.push(Visit(next, target));
+                }
+            }
+        }
+        None
+    }
+
+    fn route(&self, path: &TopologyPath) -> Route {
+        let topology = &self.graph.topology_graph;
+        Route {
+            nodes: path
+                .nodes
+                .iter()
+                .filter_map(|node| topology.index_to_id(*node).copied())
+                .collect(),
+            edges: path
+                .edges
+                .iter()
+                .map(|edge| topology.graph[*edge].edge_id)
+                .collect(),
+            length: path.length,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+struct Visit(f64, NodeIndex);
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::{
+        tests::{loop_elements, node, way},
+        PathFinding,
+    };
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let graph = from_railway_elements(&loop_elements());
+        let routes = graph.k_shortest_paths(4, 2, &KShortestPathsOptions::default());
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].nodes, vec![4, 1, 2]);
+        assert_eq!(routes[0].edges, vec![10, 11]);
+        assert_relative_eq!(
+            routes[0].length,
+            graph.shortest_path_distance(4, 2).unwrap()
+        );
+        assert_eq!(routes[1].nodes, vec![4, 1, 3, 2]);
+        assert_eq!(routes[1].edges, vec![10, 12, 13]);
+        assert!(routes[1].length > routes[0].length);
+
+        let options = KShortestPathsOptions {
+            k: 1,
+            ..Default::default()
+        };
+        assert_eq!(graph.k_shortest_paths(4, 2, &options).len(), 1);
+        assert!(graph.k_shortest_paths(4, 5, &options).is_empty());
+    }
+
+    #[test]
+    fn test_k_shortest_paths_ring() {
+        // A ring of four nodes, where the routes between opposite nodes leave through different
+        // ports of the start node.
+        let (n1, n2, n3, n4) = (
+            (1, 8.0, 50.0),
+            (2, 8.01, 50.0),
+            (3, 8.01, 50.01),
+            (4, 7.999, 50.01),
+        );
+        let graph = from_railway_elements(&[
+            node(n1.0, n1.1, n1.2),
+            node(n2.0, n2.1, n2.2),
+            node(n3.0, n3.1, n3.2),
+            node(n4.0, n4.1, n4.2),
+            way(11, &[n1, n2]),
+            way(12, &[n2, n3]),
+            way(13, &[n3, n4]),
+            way(14, &[n4, n1]),
+        ]);
+
+        let routes = graph.k_shortest_paths(1, 3, &KShortestPathsOptions::default());
+        let nodes: Vec<_> = routes.iter().map(|route| route.nodes.clone()).collect();
+        assert_eq!(nodes, vec![vec![1, 2, 3], vec![1, 4, 3]]);
+
+        for (start, end) in [(3, 1), (2, 4), (4, 2)] {
+            let routes = graph.k_shortest_paths(start, end, &KShortestPathsOptions::default());
+            assert_eq!(routes.len(), 2);
+            assert_ne!(routes[0].nodes[1], routes[1].nodes[1]);
+        }
+    }
+
+    #[test]
+    fn test_k_shortest_paths_min_difference() {
+        let graph = from_railway_elements(&loop_elements());
+        let routes = graph.k_shortest_paths(4, 2, &KShortestPathsOptions::default());
+        let lengths = graph
+            .physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| (edge.id, edge.length))
+            .collect();
+        let share = difference(&routes[1], &routes[0], &lengths);
+        assert!(share > 0.0 && share < 1.0);
+
+        let options = |min_difference| KShortestPathsOptions {
+            k: 3,
+            min_difference,
+        };
+        assert_eq!(
+            graph.k_shortest_paths(4, 2, &options(share - 0.01)).len(),
+            2
+        );
+        assert_eq!(
+            graph.k_shortest_paths(4, 2, &options(share + 0.01)).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_k_shortest_paths_vilbel() {
+        let graph = crate::tests::test_graph_vilbel();
+        let routes = graph.k_shortest_paths(
+            6204567489,
+            6204567501,
+            &KShortestPathsOptions {
+                k: 5,
+                min_difference: 0.0,
+            },
+        );
+
+        assert!(!routes.is_empty());
+        assert_eq!(
+            Some(routes[0].nodes.clone()),
+            graph.shortest_path_nodes(6204567489, 6204567501)
+        );
+        for pair in routes.windows(2) {
+            assert!(pair[0].length <= pair[1].length + 1e-9);
+        }
+        for route in &routes {
+            let unique: HashSet<_> = route.nodes.iter().collect();
+            assert_eq!(unique.len(), route.nodes.len());
+            assert_eq!(route.edges.len() + 1, route.nodes.len());
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use geo::Coord;
+use uom::si::{f64::Length, length::meter};
+
+use super::{railway_edge_algos::project_on_path, EdgeProjection, PathFinding, RailwayEdgeAlgos};
+use crate::{
+    prelude::{RailwayEdge, RailwayGraph, RailwayGraphExt, TrackPosition},
+    types::NodeId,
+};
+
+/// The result of moving a `TrackPosition` along the railway network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackMovement {
+    /// The position after the movement.
+    pub position: TrackPosition,
+    /// The nodes passed during the movement, in order.
+    pub passed_nodes: Vec<NodeId>,
+    /// The node the movement stopped at because no next node was given, if any.
+    pub stopped_at: Option<NodeId>,
+    /// The part of the distance that could not be travelled because the movement stopped.
+    pub remaining: Length,
+}
+
+/// Linear referencing on railway graphs, based on `TrackPosition`.
+///
+/// Converts between coordinates and positions on the track and moves positions along the
+/// network.
+pub trait LinearReferencing {
+    /// Snaps a coordinate onto the closest edge of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - A `Coord<f64>` near the network.
+    ///
+    /// # Returns
+    ///
+    /// An `EdgeProjection` with the closest edge, the projected point, its offset from the
+    /// source node and the perpendicular distance to `location`, or `None` if the graph has no
+    /// edges.
+    fn snap_to_edge(&self, location: Coord<f64>) -> Option<EdgeProjection>;
+
+    /// Finds the position on the network closest to a coordinate.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - A `Coord<f64>` near the network.
+    ///
+    /// # Returns
+    ///
+    /// A `TrackPosition` facing forward on the closest edge, or `None` if the graph has no edges.
+    fn track_position(&self, location: Coord<f64>) -> Option<TrackPosition>;
+
+    /// Returns the coordinate of a position.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A `TrackPosition` on the network.
+    ///
+    /// # Returns
+    ///
+    /// A `Coord<f64>`, or `None` if the edge of the position does not exist.
+    fn track_coordinate(&self, position: &TrackPosition) -> Option<Coord<f64>>;
+
+    /// Moves a position along the network, crossing nodes where necessary.
+    ///
+    /// Whenever the end of an edge is reached, `next_node` is called with the node reached and
+    /// returns the node to continue towards. The movement stops at the node if it returns `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The start position.
+    /// * `distance` - The distance to travel in the direction of `position`.
+    /// * `next_node` - Chooses the next node at each node reached.
+    ///
+    /// # Returns
+    ///
+    /// A `TrackMovement`, or `None` if the edge of the position does not exist.
+    fn move_along(
+        &self,
+        position: &TrackPosition,
+        distance: Length,
+        next_node: impl FnMut(NodeId) -> Option<NodeId>,
+    ) -> Option<TrackMovement>;
+
+    /// Calculates the shortest distance along the network between two positions.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The first position.
+    /// * `to` - The second position.
+    ///
+    /// # Returns
+    ///
+    /// A `Length`, or `None` if the positions are not connected.
+    fn track_distance(&self, from: &TrackPosition, to: &TrackPosition) -> Option<Length>;
+}
+
+impl LinearReferencing for RailwayGraph {
+    fn snap_to_edge(&self, location: Coord<f64>) -> Option<EdgeProjection> {
+        self.physical_graph
+            .graph
+            .edge_weights()
+            .filter_map(|edge| {
+                project_on_path(&edge.path, location).map(|(offset, point, distance)| {
+                    EdgeProjection {
+                        edge_id: edge.id,
+                        offset,
+                        point,
+                        distance,
+                    }
+                })
+            })
+            .min_by(|a, b| a.distance.value.total_cmp(&b.distance.value))
+    }
+
+    fn track_position(&self, location: Coord<f64>) -> Option<TrackPosition> {
+        self.snap_to_edge(location)
+            .map(|snapped| TrackPosition::new(snapped.edge_id, snapped.offset, Default::default()))
+    }
+
+    fn track_coordinate(&self, position: &TrackPosition) -> Option<Coord<f64>> {
+        let edge = self.get_edge_by_id(position.edge_id)?;
+        Some(edge.coordinate_at(position.offset))
+    }
+
+    fn move_along(
+        &self,
+        position: &TrackPosition,
+        distance: Length,
+        mut next_node: impl FnMut(NodeId) -> Option<NodeId>,
+    ) -> Option<TrackMovement> {
+        let mut edge = self.get_edge_by_id(position.edge_id)?;
+        let mut position = *position;
+        let mut remaining = distance.max(Length::new::<meter>(0.0));
+        let mut passed_nodes = Vec::new();
+        let mut stopped_at = None;
+
+        loop {
+            let distance_to_end = edge.distance_to_end(&position);
+            if remaining < distance_to_end {
+                position = edge.position_on_edge(&position, remaining);
+                remaining = Length::new::<meter>(0.0);
+                break;
+            }
+            position = edge.position_on_edge(&position, distance_to_end);
+            remaining -= distance_to_end;
+
+            let node_id = edge.node_ahead(position.direction);
+            passed_nodes.push(node_id);
+            let next_edge = next_node(node_id)
+                .and_then(|next_node_id| self.railway_edge(node_id, next_node_id))
+                .cloned();
+            match next_edge.as_ref().and_then(|e| e.start_position(node_id)) {
+                Some(start) => {
+                    edge = next_edge.unwrap();
+                    position = start;
+                }
+                None => {
+                    stopped_at = Some(node_id);
+                    break;
+                }
+            }
+        }
+
+        Some(TrackMovement {
+            position,
+            passed_nodes,
+            stopped_at,
+            remaining,
+        })
+    }
+
+    fn track_distance(&self, from: &TrackPosition, to: &TrackPosition) -> Option<Length> {
+        let from_edge = self.get_edge_by_id(from.edge_id)?;
+        let to_edge = self.get_edge_by_id(to.edge_id)?;
+        if from.edge_id == to.edge_id {
+            return Some((from.offset - to.offset).abs());
+        }
+
+        let ends = |edge: &RailwayEdge, offset: Length| {
+            [
+                (edge.source, offset),
+                (edge.target, edge.path_length() - offset),
+            ]
+        };
+        let mut shortest: Option<Length> = None;
+        for (from_node, from_distance) in ends(&from_edge, from.offset) {
+            for (to_node, to_distance) in ends(&to_edge, to.offset) {
+                let between = if from_node == to_node {
+                    Some(0.0)
+                } else {
+                    self.shortest_path_distance(from_node, to_node)
+                };
+                if let Some(between) = between {
+                    let total = from_distance + Length::new::<meter>(between) + to_distance;
+                    if shortest.is_none_or(|shortest| total < shortest) {
+                        shortest = Some(total);
+                    }
+                }
+            }
+        }
+        shortest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::TrackDirection;
+    use crate::railway_algorithms::tests::{siding_elements, test_elements};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_track_position_round_trip() {
+        let graph = from_railway_elements(&test_elements());
+        let edge = graph.get_edge_by_id(4).unwrap();
+        let position = TrackPosition::new(4, edge.path_length() / 2.0, TrackDirection::Forward);
+
+        let location = graph.track_coordinate(&position).unwrap();
+        let found = graph.track_position(location).unwrap();
+
+        assert_eq!(found.edge_id, 4);
+        assert_relative_eq!(
+            found.offset.get::<meter>(),
+            position.offset.get::<meter>(),
+            epsilon = 0.01
+        );
+    }
+
+    #[test]
+    fn test_snap_to_edge() {
+        let graph = from_railway_elements(&siding_elements());
+        // About 22 m south of the middle of edge 21, which runs along 50° latitude.
+        let location = Coord {
+            x: 8.015,
+            y: 49.9998,
+        };
+
+        let snapped = graph.snap_to_edge(location).unwrap();
+        assert_eq!(snapped.edge_id, 21);
+        assert_relative_eq!(snapped.point.x, 8.015, epsilon = 1e-9);
+        assert_relative_eq!(snapped.point.y, 50.0, epsilon = 1e-9);
+        assert_relative_eq!(snapped.distance.get::<meter>(), 22.26, epsilon = 0.1);
+        let edge = graph.get_edge_by_id(21).unwrap();
+        assert_relative_eq!(
+            snapped.offset.get::<meter>(),
+            edge.path_length().get::<meter>() / 2.0,
+            epsilon = 0.01
+        );
+
+        let empty = from_railway_elements(&[]);
+        assert!(empty.snap_to_edge(location).is_none());
+    }
+
+    #[test]
+    fn test_move_along_crosses_nodes() {
+        let graph = from_railway_elements(&test_elements());
+        let first = graph.get_edge_by_id(4).unwrap();
+        let second = graph.get_edge_by_id(5).unwrap();
+        let start = first.start_position(1).unwrap();
+        let distance = first.path_length() + Length::new::<meter>(10.0);
+
+        let movement = graph
+            .move_along(&start, distance, |node| (node == 2).then_some(3))
+            .unwrap();
+
+        assert_eq!(movement.passed_nodes, vec![2]);
+        assert_eq!(movement.stopped_at, None);
+        assert_eq!(movement.position.edge_id, 5);
+        assert_relative_eq!(
+            second.distance_to_end(&movement.position).get::<meter>(),
+            second.path_length().get::<meter>() - 10.0,
+            epsilon = 0.001
+        );
+        assert_relative_eq!(
+            graph
+                .track_distance(&start, &movement.position)
+                .unwrap()
+                .get::<meter>(),
+            distance.get::<meter>(),
+            epsilon = 0.01
+        );
+
+        let stopped = graph.move_along(&start, distance, |_| None).unwrap();
+        assert_eq!(stopped.position.edge_id, 4);
+        assert_eq!(stopped.stopped_at, Some(2));
+        assert_relative_eq!(stopped.remaining.get::<meter>(), 10.0, epsilon = 0.001);
+    }
+}
@@ -4,10 +4,23 @@
 //! shortest path distance, the shortest path as a list of node IDs, and the shortest
 //! path as a list of edge IDs for railway networks.
 
+mod curvature;
+mod directional_routing;
+mod distance_matrix;
+mod k_shortest_paths;
+mod linear_referencing;
 /// The `PathFinding` trait is implemented for the `RailwayGraph` type, allowing users
 /// to perform pathfinding operations on railway graphs.
 mod path_finding;
 mod railway_edge_algos;
+mod reachability;
+mod resilience;
+mod routing_table;
+mod spatial_index;
+mod speed_limits;
+mod station_index;
+mod travel_time;
+mod waypoint_routing;
 
 use crate::{
     prelude::RailwayGraph,
@@ -16,9 +29,24 @@ use crate::{
 pub use path_finding::PathFinding;
 use petgraph::visit::Bfs;
 
+pub use curvature::{curvature_profile, CurvatureOptions, RailwayGraphCurvature};
+pub use directional_routing::{DirectionalPathFinding, DirectionalRoute, ReversalOptions};
+pub use distance_matrix::DistanceMatrix;
+pub use k_shortest_paths::{KShortestPathsOptions, Route};
+pub use linear_referencing::{LinearReferencing, TrackMovement};
 pub use railway_edge_algos::RailwayEdgeAlgos;
+pub use reachability::{Reachability, ReachabilityBudget, ReachedEdge, ReachedNode};
+pub use resilience::{ClosureImpact, Detour, RailwayGraphResilience};
+pub use routing_table::RoutingTable;
+pub use spatial_index::{EdgeProjection, EdgeSpatialIndex};
+pub use speed_limits::{parse_maxspeed, RailwayGraphSpeedLimits, SpeedLimitAhead};
+pub use station_index::{StationIndex, StationMatch};
+pub use travel_time::{EdgeTravelTime, RoutingCost, TimedRoute, TrainProfile};
+pub use waypoint_routing::{WaypointRoute, WaypointRouting};
 
 /// `RailwayGraphAlgos` trait provides algorithms for railway graphs.
+///
+/// It finds the nodes and edges reachable from a node, also within a distance or time budget.
 pub trait RailwayGraphAlgos {
     /// Find all reachable nodes from the given start node in the railway graph.
     ///
@@ -50,7 +78,27 @@ pub trait RailwayGraphAlgos {
     /// If the start node ID is not found in the graph, an empty vector is returned.
     fn reachable_edges(&self, start_node_id: NodeId) -> Vec<EdgeId>;
 
-    /// Returns the next reachable node on the shortest path
+    /// Find the nodes and edges reachable from the given start node within a distance or time.
+    ///
+    /// The search follows the allowed transitions of the topology graph. Edges on which the
+    /// budget runs out are cut at the point where it is used up, which makes the result an
+    /// isochrone of the network around the start node.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_node_id` - The ID of the start node.
+    /// * `budget` - The distance, or the time together with the train and speed limits, to
+    ///   travel at most.
+    ///
+    /// # Returns
+    ///
+    /// A `Reachability` with the reached nodes and the reached edges or parts of edges. If the
+    /// start node ID is not found in the graph, it contains no nodes.
+    fn reachable_within(&self, start_node_id: NodeId, budget: &ReachabilityBudget) -> Reachability;
+
+    /// Returns the next reachable node on the shortest path.
+    ///
+    /// The path may turn back where the default `ReversalOptions` allow a train to reverse.
     fn get_next_node(&self, current: NodeId, target: NodeId) -> Option<NodeId>;
 }
 
@@ -103,9 +151,12 @@ impl RailwayGraphAlgos for RailwayGraph {
         }
     }
 
+    fn reachable_within(&self, start_node_id: NodeId, budget: &ReachabilityBudget) -> Reachability {
+        reachability::reachable_within(self, start_node_id, budget)
+    }
+
     fn get_next_node(&self, current: NodeId, target: NodeId) -> Option<NodeId> {
-        let path = self.shortest_path_nodes(current, target)?;
-        path.get(1).copied()
+        self.next_directional_node(current, None, target, &ReversalOptions::default())
     }
 }
 
@@ -119,10 +170,11 @@ pub mod tests {
     use uom::si::{f64::Length, length::meter};
 
     use crate::{
+        algorithms::Distance,
         importer::overpass_importer::{
             from_railway_elements, Coordinate, ElementType, RailwayElement,
         },
-        prelude::RailwayEdge,
+        prelude::{RailwayEdge, TrackDirection, TrackPosition},
     };
     use std::collections::HashMap;
 
@@ -140,25 +192,29 @@ pub mod tests {
             target: 2,
         };
 
-        let current_position1 = coord! { x: 13.377054, y: 52.516250 }; // Brandenburg Gate, Berlin
-        let direction1 = coord! { x: 13.378685, y: 52.520165 }; // Reichstag Building, Berlin
-        let distance_to_end1 = edge.distance_to_end(current_position1, direction1);
-        let expected_distance1 = Length::new::<meter>(1796.0); // Approx. distance between Brandenburg Gate and Berlin Central Station
+        let start = edge.start_position(1).unwrap();
+        let distance_to_end1 = edge.distance_to_end(&start);
+        let expected_distance1 = Length::new::<meter>(930.0); // Approx. distance from Brandenburg Gate via Reichstag Building to Berlin Central Station
         assert_relative_eq!(
             distance_to_end1.get::<meter>(),
             expected_distance1.get::<meter>(),
             epsilon = 10.0
         );
 
-        let current_position2 = coord! { x: 13.378685, y: 52.520165 }; // Reichstag Building, Berlin
-        let direction2 = coord! { x: 13.384733, y: 52.522464 }; // Berlin Central Station
-        let distance_to_end2 = edge.distance_to_end(current_position2, direction2);
+        let reichstag = coord! { x: 13.378685, y: 52.520165 }; // Reichstag Building, Berlin
+        let position2 = TrackPosition::new(1, edge.project(reichstag), TrackDirection::Forward);
+        let distance_to_end2 = edge.distance_to_end(&position2);
         let expected_distance2 = Length::new::<meter>(480.0); // Approx. distance between Reichstag Building and Berlin Central Station
         assert_relative_eq!(
             distance_to_end2.get::<meter>(),
             expected_distance2.get::<meter>(),
             epsilon = 10.0
         );
+        assert_relative_eq!(
+            edge.distance_to_end(&position2.reversed()).get::<meter>(),
+            (expected_distance1 - expected_distance2).get::<meter>(),
+            epsilon = 10.0
+        );
     }
 
     #[test]
@@ -176,38 +232,49 @@ pub mod tests {
             target: 2,
         };
 
-        let current_position1 = coord! { x: 8.6821, y: 50.1109 };
-        let current_position2 = coord! { x: 8.6825, y: 50.1112 };
+        let start = edge.start_position(1).unwrap();
         let distance1 = Length::new::<meter>(15.0);
-        let direction1 = coord! { x: 8.6825, y: 50.1112 };
-        let direction2 = coord! { x: 8.6835, y: 50.1118 };
 
-        let new_position1 = edge.position_on_edge(current_position1, distance1, direction1);
-        let new_position2 = edge.position_on_edge(current_position2, distance1, direction2);
-
-        assert_relative_eq!(new_position1.x, 8.6823, epsilon = 0.001);
-        assert_relative_eq!(new_position1.y, 50.1111, epsilon = 0.001);
-        assert_relative_eq!(new_position2.x, 8.6830, epsilon = 0.001);
-        assert_relative_eq!(new_position2.y, 50.1115, epsilon = 0.001);
-
-        let current_position3 = coord! { x: 8.6830, y: 50.1115 };
+        let new_position1 = edge.position_on_edge(&start, distance1);
+        assert_relative_eq!(new_position1.offset.get::<meter>(), 15.0);
+        let new_location1 = edge.coordinate_at(new_position1.offset);
+        assert_relative_eq!(new_location1.x, 8.6823, epsilon = 0.001);
+        assert_relative_eq!(new_location1.y, 50.1111, epsilon = 0.001);
+        assert_relative_eq!(
+            edge.path.0[0].distance(&new_location1).get::<meter>(),
+            15.0,
+            epsilon = 0.01
+        );
 
+        let current_location = coord! { x: 8.6830, y: 50.1115 };
+        let current_offset = edge.project(current_location);
         let distance2 = Length::new::<meter>(25.0);
-        let new_position3 = edge.position_on_edge(current_position3, distance2, direction1);
-        let new_position4 = edge.position_on_edge(current_position3, distance2, direction2);
+        let forward = TrackPosition::new(1, current_offset, TrackDirection::Forward);
+        let new_position2 = edge.position_on_edge(&forward, distance2);
+        let new_position3 = edge.position_on_edge(&forward.reversed(), distance2);
 
-        assert_relative_eq!(new_position3.x, 8.6827, epsilon = 0.0001);
-        assert_relative_eq!(new_position3.y, 50.1113, epsilon = 0.0001);
-        assert_relative_eq!(new_position4.x, 8.6833, epsilon = 0.0001);
-        assert_relative_eq!(new_position4.y, 50.1116, epsilon = 0.0001);
+        assert_relative_eq!(
+            (new_position2.offset - current_offset).get::<meter>(),
+            25.0,
+            epsilon = 0.001
+        );
+        assert_relative_eq!(
+            (current_offset - new_position3.offset).get::<meter>(),
+            25.0,
+            epsilon = 0.001
+        );
+        let new_location3 = edge.coordinate_at(new_position3.offset);
+        assert_relative_eq!(new_location3.x, 8.6827, epsilon = 0.0001);
+        assert_relative_eq!(new_location3.y, 50.1113, epsilon = 0.0001);
 
-        let current_position4 = coord! { x: 8.6830, y: 50.1115 };
         let distance3 = Length::new::<meter>(100.0);
+        let new_position4 = edge.position_on_edge(&forward, distance3);
 
-        let new_position5 = edge.position_on_edge(current_position4, distance3, direction2);
-
-        assert_relative_eq!(new_position5.x, 8.6840, epsilon = 0.0001);
-        assert_relative_eq!(new_position5.y, 50.1121, epsilon = 0.0001);
+        assert_eq!(new_position4.offset, edge.path_length());
+        assert_eq!(
+            edge.coordinate_at(new_position4.offset),
+            coord! { x: 8.6835, y: 50.1118 }
+        );
     }
 
     /// Builds a small, fixed set of `RailwayElement`s usable as fixtures in tests.
@@ -278,6 +345,114 @@ pub mod tests {
             },
         ]
     }
+
+    /// Builds a line from the dead end 10 over node 11 to the switch 12, which branches to 13
+    /// and 14.
+    ///
+    /// ```text
+    ///                        / 13
+    /// 10 ----- 11 ----- 12 <
+    ///                        \ 14
+    /// ```
+    pub fn siding_elements() -> Vec<RailwayElement> {
+        let nodes = [
+            (10, 8.00, 50.0),
+            (11, 8.01, 50.0),
+            (12, 8.02, 50.0),
+            (13, 8.03, 50.001),
+            (14, 8.03, 49.999),
+        ];
+        let mut elements: Vec<RailwayElement> = nodes
+            .iter()
+            .map(|&(id, lon, lat)| RailwayElement {
+                id,
+                tags: None,
+                element_type: ElementType::Node,
+                nodes: None,
+                geometry: None,
+                lat: Some(lat),
+                lon: Some(lon),
+            })
+            .collect();
+        for (id, source, target) in [(20, 0, 1), (21, 1, 2), (22, 2, 3), (23, 2, 4)] {
+            let (source, target) = (nodes[source], nodes[target]);
+            elements.push(RailwayElement {
+                id,
+                tags: None,
+                element_type: ElementType::Way,
+                nodes: Some(vec![source.0, target.0]),
+                geometry: Some(
+                    [source, target]
+                        .iter()
+                        .map(|&(_, lon, lat)| Coordinate { lat, lon })
+                        .collect(),
+                ),
+                lat: None,
+                lon: None,
+            });
+        }
+        elements
+    }
+
+    /// A node element at the given location.
+    pub fn node(id: i64, lon: f64, lat: f64) -> RailwayElement {
+        RailwayElement {
+            id,
+            tags: None,
+            element_type: ElementType::Node,
+            nodes: None,
+            geometry: None,
+            lat: Some(lat),
+            lon: Some(lon),
+        }
+    }
+
+    /// A way element through the given nodes.
+    pub fn way(id: i64, nodes: &[(i64, f64, f64)]) -> RailwayElement {
+        RailwayElement {
+            id,
+            tags: None,
+            element_type: ElementType::Way,
+            nodes: Some(nodes.iter().map(|(id, _, _)| *id).collect()),
+            geometry: Some(
+                nodes
+                    .iter()
+                    .map(|(_, lon, lat)| Coordinate {
+                        lat: *lat,
+                        lon: *lon,
+                    })
+                    .collect(),
+            ),
+            lat: None,
+            lon: None,
+        }
+    }
+
+    /// Two stations connected by a direct line, a longer loop through node 3 and a shared
+    /// approach from node 4.
+    ///
+    /// ```text
+    ///              3
+    ///            /   \
+    /// 4 ----- 1 ------- 2
+    /// ```
+    pub fn loop_elements() -> Vec<RailwayElement> {
+        let n1 = (1, 8.00, 50.0);
+        let n2 = (2, 8.02, 50.0);
+        let n3 = (3, 8.01, 50.005);
+        let n4 = (4, 7.99, 50.0);
+        vec![
+            node(1, n1.1, n1.2),
+            node(2, n2.1, n2.2),
+            node(3, n3.1, n3.2),
+            node(4, n4.1, n4.2),
+            way(10, &[n4, n1]),
+            way(11, &[n1, n2]),
+            way(12, &[n1, n3]),
+            way(13, &[n3, n2]),
+        ]
+    }
+
     #[test]
     fn test_reachable_nodes() {
         let railway_graph = from_railway_elements(&test_elements());
@@ -305,6 +480,11 @@ pub mod tests {
         assert_eq!(railway_graph.get_next_node(1, 2), Some(2));
         assert_eq!(railway_graph.get_next_node(1, 3), Some(2));
         assert_eq!(railway_graph.get_next_node(2, 3), Some(3));
-        // TODO: assert_eq!(railway_graph.get_next_node(1, 4), None);
+        assert_eq!(railway_graph.get_next_node(1, 4), None);
+
+        // Reaching the other branch of a switch needs a reversal behind it.
+        let railway_graph = from_railway_elements(&siding_elements());
+        assert_eq!(railway_graph.get_next_node(13, 14), Some(12));
+        assert_eq!(railway_graph.get_next_node(12, 14), Some(14));
     }
 }
@@ -1,12 +1,31 @@
+use std::collections::HashSet;
+
 use crate::{
-    prelude::RailwayGraphExt,
+    algorithms::Distance,
+    prelude::{
+        RailwayEdge, RailwayGraphExt, RailwaySpeedLimits, RoutingConstraints, TrackDirection,
+    },
     railway_model::RailwayGraph,
     types::{EdgeId, NodeId},
 };
-use petgraph::algo::dijkstra;
+
+use super::{
+    distance_matrix, k_shortest_paths, travel_time, DistanceMatrix, KShortestPathsOptions, Route,
+    RoutingCost, TimedRoute, TrainProfile,
+};
+use petgraph::{algo::astar, algo::dijkstra, graph::NodeIndex, visit::EdgeRef};
 use transit_grid::algorithms::ShortestPath;
+use uom::si::length::meter;
+
+/// Scales the great-circle distance used as the A* lower bound, so that rounded edge lengths and
+/// node locations slightly off their way geometries cannot make the bound overestimate.
+const ASTAR_HEURISTIC_FACTOR: f64 = 0.999;
 
 /// `PathFinding` trait provides pathfinding algorithms for railway networks.
+///
+/// Besides the shortest path between two nodes it offers distance matrices between sets of
+/// nodes, A* for point-to-point queries, the k shortest alternative routes, the shortest or
+/// fastest route for a `TrainProfile` and the shortest route suited to a `VehicleProfile`.
 pub trait PathFinding {
     /// Calculate the shortest path distance between two nodes.
     ///
@@ -18,6 +37,20 @@ pub trait PathFinding {
     /// Returns the distance of the shortest path between the source and target nodes if it exists.
     fn shortest_path_distance(&self, source: NodeId, target: NodeId) -> Option<f64>;
 
+    /// Calculate the shortest path distances from every origin to every destination node.
+    ///
+    /// One search runs per origin, spread over all available cores, instead of one search per
+    /// pair of nodes. The distances are the same as from `shortest_path_distance`.
+    ///
+    /// # Arguments
+    /// * `origins` - The IDs of the origin nodes.
+    /// * `destinations` - The IDs of the destination nodes.
+    ///
+    /// # Returns
+    /// Returns a `DistanceMatrix` with a row per origin and a column per destination, in the
+    /// given order.
+    fn distance_matrix(&self, origins: &[NodeId], destinations: &[NodeId]) -> DistanceMatrix;
+
     /// Calculate the shortest path between two nodes as a list of node IDs.
     ///
     /// # Arguments
@@ -38,6 +71,106 @@ pub trait PathFinding {
     /// # Returns
     /// Returns a `Vec<i64>` containing the IDs of the edges in the shortest path if it exists.
     fn shortest_path_edges(&self, start: NodeId, end: NodeId) -> Option<Vec<EdgeId>>;
+
+    /// Calculate the shortest path between two nodes as a list of node IDs using A*.
+    ///
+    /// The search follows the allowed transitions of the topology graph and uses the
+    /// great-circle distance to the end node as lower bound, so it visits far fewer nodes than
+    /// Dijkstra's algorithm on point-to-point queries in large networks.
+    ///
+    /// # Arguments
+    /// * `start` - The ID of the start node.
+    /// * `end` - The ID of the end node.
+    ///
+    /// # Returns
+    /// Returns a `Vec<i64>` containing the IDs of the nodes in the shortest path if it exists.
+    /// The returned vector includes the start and end node IDs.
+    fn astar_path_nodes(&self, start: NodeId, end: NodeId) -> Option<Vec<NodeId>>;
+
+    /// Calculate the shortest path distance between two nodes using A*.
+    ///
+    /// Unlike `shortest_path_distance`, the distance respects the allowed transitions of the
+    /// topology graph.
+    ///
+    /// # Arguments
+    /// * `start` - The ID of the start node.
+    /// * `end` - The ID of the end node.
+    ///
+    /// # Returns
+    /// Returns the length in meters of the shortest path between the start and end nodes if it
+    /// exists.
+    fn astar_path_distance(&self, start: NodeId, end: NodeId) -> Option<f64>;
+
+    /// Calculate the shortest route between two nodes over the edges a vehicle may use.
+    ///
+    /// The route follows the allowed transitions of the topology graph and leaves out edges
+    /// whose gauge, electrification, usage, access or axle load limit does not suit the
+    /// vehicle. Track operated in one direction is run against its direction only at the cost
+    /// given by the `DirectionOptions` of the constraints, or not at all.
+    ///
+    /// # Arguments
+    /// * `start` - The ID of the start node.
+    /// * `end` - The ID of the end node.
+    /// * `constraints` - The vehicle, the attributes of the edges and how direction
+    ///   restrictions are treated.
+    ///
+    /// # Returns
+    /// Returns the `Route` with its node IDs, edge IDs and length if the vehicle can reach the
+    /// end node.
+    fn compatible_route(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        constraints: RoutingConstraints,
+    ) -> Option<Route>;
+
+    /// Calculate the shortest loopless alternative routes between two nodes.
+    ///
+    /// The routes follow the allowed transitions of the topology graph and are found with Yen's
+    /// algorithm.
+    ///
+    /// # Arguments
+    /// * `start` - The ID of the start node.
+    /// * `end` - The ID of the end node.
+    /// * `options` - The number of routes and the minimum share of a route's length that has
+    ///   to differ from the shorter routes.
+    ///
+    /// # Returns
+    /// Returns up to `options.k` routes with their node IDs, edge IDs and lengths, ordered by
+    /// length. The vector is empty if there is no route.
+    fn k_shortest_paths(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        options: &KShortestPathsOptions,
+    ) -> Vec<Route>;
+
+    /// Calculate the route with the lowest distance or running time between two nodes.
+    ///
+    /// The running time of an edge is estimated from the lower of the train's maximum speed and
+    /// the speed limits, plus the time lost accelerating and braking wherever the speed changes,
+    /// starting and ending at standstill. The route follows the allowed transitions of the
+    /// topology graph.
+    ///
+    /// # Arguments
+    /// * `start` - The ID of the start node.
+    /// * `end` - The ID of the end node.
+    /// * `cost` - Whether to minimise the distance or the running time.
+    /// * `profile` - The maximum speed, acceleration and deceleration of the train.
+    /// * `speed_limits` - The speed limits of the edges. Edges without a limit are run at the
+    ///   train's maximum speed.
+    ///
+    /// # Returns
+    /// Returns the `TimedRoute` with its node IDs, length, running time and the running time
+    /// along each edge if a route exists.
+    fn timed_route(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        cost: RoutingCost,
+        profile: &TrainProfile,
+        speed_limits: &RailwaySpeedLimits,
+    ) -> Option<TimedRoute>;
 }
 
 impl PathFinding for RailwayGraph {
@@ -59,6 +192,10 @@ impl PathFinding for RailwayGraph {
         }
     }
 
+    fn distance_matrix(&self, origins: &[NodeId], destinations: &[NodeId]) -> DistanceMatrix {
+        distance_matrix::distance_matrix(self, origins, destinations, &HashSet::new())
+    }
+
     fn shortest_path_nodes(&self, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
         self.find_shortest_path(start, end)
     }
@@ -78,6 +215,141 @@ impl PathFinding for RailwayGraph {
             .collect::<Vec<EdgeId>>()
             .into()
     }
+
+    fn astar_path_nodes(&self, start: NodeId, end: NodeId) -> Option<Vec<NodeId>> {
+        astar_path(self, start, end, |edge, _| Some(edge.length)).map(|(_, path)| path)
+    }
+
+    fn astar_path_distance(&self, start: NodeId, end: NodeId) -> Option<f64> {
+        astar_path(self, start, end, |edge, _| Some(edge.length)).map(|(distance, _)| distance)
+    }
+
+    fn compatible_route(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        constraints: RoutingConstraints,
+    ) -> Option<Route> {
+        let edge_cost =
+            |edge: &RailwayEdge, direction| constraints.cost(edge.id, direction, edge.length);
+        let (_, nodes) = astar_path(self, start, end, edge_cost)?;
+        let physical = &self.physical_graph;
+        let edges = nodes
+            .windows(2)
+            .map(|pair| {
+                let &from = physical.id_to_index(pair[0])?;
+                let &to = physical.id_to_index(pair[1])?;
+                physical
+                    .graph
+                    .edges_connecting(from, to)
+                    .map(|edge| edge.weight())
+                    .filter_map(|edge| {
+                        let direction = if edge.source == pair[0] {
+                            TrackDirection::Forward
+                        } else {
+                            TrackDirection::Backward
+                        };
+                        edge_cost(edge, direction).map(|cost| (edge, cost))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(edge, _)| edge)
+            })
+            .collect::<Option<Vec<&RailwayEdge>>>()?;
+        Some(Route {
+            nodes,
+            length: edges.iter().map(|edge| edge.length).sum(),
+            edges: edges.iter().map(|edge| edge.id).collect(),
+        })
+    }
+
+    fn k_shortest_paths(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        options: &KShortestPathsOptions,
+    ) -> Vec<Route> {
+        k_shortest_paths::k_shortest_paths(self, start, end, options)
+    }
+
+    fn timed_route(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        cost: RoutingCost,
+        profile: &TrainProfile,
+        speed_limits: &RailwaySpeedLimits,
+    ) -> Option<TimedRoute> {
+        travel_time::timed_route(self, start, end, cost, profile, speed_limits)
+    }
+}
+
+/// Runs A* on the topology graph from both sides of the start node to either side of the end
+/// node and returns the cost and node IDs of the cheaper path.
+///
+/// `edge_cost` returns the cost of running along an edge in a direction, at least its length,
+/// or `None` if the edge may not be used in that direction.
+fn astar_path(
+    graph: &RailwayGraph,
+    start: NodeId,
+    end: NodeId,
+    edge_cost: impl Fn(&RailwayEdge, TrackDirection) -> Option<f64>,
+) -> Option<(f64, Vec<NodeId>)> {
+    let &(start_1, start_2) = graph.topology_graph.id_to_index(start)?;
+    let &(end_1, end_2) = graph.topology_graph.id_to_index(end)?;
+    let end_location = graph.get_node_by_id(end)?.location;
+    let physical = &graph.physical_graph;
+
+    let node_index = |index: NodeIndex| -> Option<NodeIndex> {
+        let id = graph.topology_graph.index_to_id(index)?;
+        physical.id_to_index(*id).copied()
+    };
+    let estimate = |index: NodeIndex| {
+        node_index(index).map_or(0.0, |index| {
+            physical.graph[index]
+                .location
+                .distance(&end_location)
+                .get::<meter>()
+                * ASTAR_HEURISTIC_FACTOR
+        })
+    };
+    let edge_length = |index: NodeIndex, edge_id: EdgeId| {
+        node_index(index)
+            .and_then(|index| {
+                let edge = physical
+                    .graph
+                    .edges(index)
+                    .find(|edge| edge.weight().id == edge_id)?
+                    .weight();
+                let direction = if edge.source == physical.graph[index].id {
+                    TrackDirection::Forward
+                } else {
+                    TrackDirection::Backward
+                };
+                edge_cost(edge, direction)
+            })
+            .unwrap_or(f64::INFINITY)
+    };
+
+    [start_1, start_2]
+        .into_iter()
+        .filter_map(|start_index| {
+            astar(
+                &graph.topology_graph.graph,
+                start_index,
+                |index| index == end_1 || index == end_2,
+                |edge| edge_length(edge.source(), edge.weight().edge_id),
+                estimate,
+            )
+        })
+        .filter(|(distance, _)| distance.is_finite())
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(distance, path)| {
+            let nodes = path
+                .into_iter()
+                .filter_map(|index| graph.topology_graph.index_to_id(index).copied())
+                .collect();
+            (distance, nodes)
+        })
 }
 
 #[cfg(test)]
@@ -87,7 +359,9 @@ mod tests {
 
     use crate::{
         importer::overpass_importer::from_railway_elements,
+        prelude::RailwayGraphExt,
         railway_algorithms::{tests::test_elements, PathFinding},
+        tests::test_graph_vilbel,
     };
 
     #[test]
@@ -134,4 +408,162 @@ mod tests {
         assert_eq!(railway_graph.shortest_path_edges(2, 3), Some(vec![5]));
         assert_eq!(railway_graph.shortest_path_edges(1, 4), None);
     }
+
+    #[test]
+    fn test_astar_path() {
+        let railway_graph = from_railway_elements(&test_elements());
+
+        assert_eq!(railway_graph.astar_path_nodes(1, 3), Some(vec![1, 2, 3]));
+        assert_eq!(railway_graph.astar_path_nodes(3, 1), Some(vec![3, 2, 1]));
+        assert_eq!(railway_graph.astar_path_nodes(1, 4), None);
+        assert_relative_eq!(
+            railway_graph.astar_path_distance(1, 3).unwrap(),
+            railway_graph.shortest_path_distance(1, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_astar_path_matches_dijkstra() {
+        let railway_graph = test_graph_vilbel();
+        let node_ids: Vec<_> = railway_graph
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .step_by(11)
+            .collect();
+
+        for &start in &node_ids {
+            for &end in &node_ids {
+                let expected = railway_graph.shortest_path_nodes(start, end).map(|path| {
+                    path.windows(2)
+                        .map(|pair| railway_graph.railway_edge(pair[0], pair[1]).unwrap().length)
+                        .sum::<f64>()
+                });
+                match (expected, railway_graph.astar_path_distance(start, end)) {
+                    (Some(expected), Some(distance)) => {
+                        assert_relative_eq!(distance, expected, epsilon = 1e-6)
+                    }
+                    (expected, distance) => assert_eq!(expected, distance),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compatible_route() {
+        use crate::prelude::{
+            RailwayTrackAttributes, RoutingConstraints, TrackAttributes, VehicleProfile,
+        };
+        use crate::railway_algorithms::tests::loop_elements;
+
+        let railway_graph = from_railway_elements(&loop_elements());
+        let mut attributes = RailwayTrackAttributes::default();
+        attributes.edges.insert(
+            11,
+            TrackAttributes {
+                gauges: vec![1000],
+                ..Default::default()
+            },
+        );
+        let standard_gauge = VehicleProfile {
+            gauge: Some(1435),
+            ..Default::default()
+        };
+        let metre_gauge = VehicleProfile {
+            gauge: Some(1000),
+            ..Default::default()
+        };
+
+        let constraints = RoutingConstraints {
+            vehicle: Some(&metre_gauge),
+            ..RoutingConstraints::new(&attributes)
+        };
+        let route = railway_graph.compatible_route(1, 2, constraints).unwrap();
+        assert_eq!(route.nodes, vec![1, 2]);
+        assert_eq!(route.edges, vec![11]);
+
+        let constraints = RoutingConstraints {
+            vehicle: Some(&standard_gauge),
+            ..RoutingConstraints::new(&attributes)
+        };
+        let route = railway_graph.compatible_route(4, 2, constraints).unwrap();
+        assert_eq!(route.nodes, vec![4, 1, 3, 2]);
+        assert_eq!(route.edges, vec![10, 12, 13]);
+        let loop_length: f64 = route
+            .edges
+            .iter()
+            .map(|&id| railway_graph.get_edge_by_id(id).unwrap().length)
+            .sum();
+        assert_relative_eq!(route.length, loop_length, epsilon = 1e-6);
+
+        attributes.edges.insert(
+            13,
+            TrackAttributes {
+                access: Some("no".to_string()),
+                ..Default::default()
+            },
+        );
+        let constraints = RoutingConstraints {
+            vehicle: Some(&standard_gauge),
+            ..RoutingConstraints::new(&attributes)
+        };
+        assert_eq!(railway_graph.compatible_route(4, 2, constraints), None);
+    }
+
+    #[test]
+    fn test_route_honours_direction_restrictions() {
+        use crate::prelude::{
+            DirectionOptions, DirectionRestriction, RailwayTrackAttributes, RoutingConstraints,
+            TrackAttributes, TrackDirection,
+        };
+        use crate::railway_algorithms::tests::loop_elements;
+
+        let railway_graph = from_railway_elements(&loop_elements());
+        let mut attributes = RailwayTrackAttributes::default();
+        // Way 11 runs from node 1 to node 2, so a train from 4 to 2 runs along it forwards.
+        let restrict = |attributes: &mut RailwayTrackAttributes, hard| {
+            attributes.edges.insert(
+                11,
+                TrackAttributes {
+                    direction: Some(DirectionRestriction {
+                        direction: TrackDirection::Backward,
+                        hard,
+                    }),
+                    ..Default::default()
+                },
+            );
+        };
+
+        let route = railway_graph
+            .compatible_route(4, 2, RoutingConstraints::new(&attributes))
+            .unwrap();
+        assert_eq!(route.nodes, vec![4, 1, 2]);
+
+        restrict(&mut attributes, true);
+        let route = railway_graph
+            .compatible_route(4, 2, RoutingConstraints::new(&attributes))
+            .unwrap();
+        assert_eq!(route.nodes, vec![4, 1, 3, 2]);
+        let route = railway_graph
+            .compatible_route(2, 4, RoutingConstraints::new(&attributes))
+            .unwrap();
+        assert_eq!(route.nodes, vec![2, 1, 4]);
+
+        let wrong_line = RoutingConstraints {
+            directions: DirectionOptions {
+                wrong_line: Some(1.1),
+                ..Default::default()
+            },
+            ..RoutingConstraints::new(&attributes)
+        };
+        let route = railway_graph.compatible_route(4, 2, wrong_line).unwrap();
+        assert_eq!(route.nodes, vec![4, 1, 2]);
+
+        restrict(&mut attributes, false);
+        let route = railway_graph
+            .compatible_route(4, 2, RoutingConstraints::new(&attributes))
+            .unwrap();
+        assert_eq!(route.nodes, vec![4, 1, 3, 2]);
+    }
 }
@@ -1,106 +1,244 @@
-use geo::{Coord, Distance as GeoDistance, Haversine, Point};
+use geo::{Coord, LineString};
 use uom::si::{f64::Length, length::meter};
 
 use crate::{
-    algorithms::{points_in_front, Distance},
-    prelude::RailwayEdge,
+    algorithms::Distance,
+    crs::Crs,
+    prelude::{RailwayEdge, TrackDirection, TrackPosition},
+    types::NodeId,
 };
 
 /// Algorithms for railway edges.
 pub trait RailwayEdgeAlgos {
-    /// Calculates the distance between the current location and the last coordinate in the linestring.
+    /// Returns the length of the edge's geometry.
+    fn path_length(&self) -> Length;
+
+    /// Returns the coordinate at the given offset along the edge's geometry.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The distance from the source node, clamped to the length of the geometry.
+    ///
+    /// # Returns
+    ///
+    /// A `Coord<f64>` on the edge's geometry.
+    ///
+    fn coordinate_at(&self, offset: Length) -> Coord<f64>;
+
+    /// Projects a coordinate onto the edge's geometry.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - A `Coord<f64>` near the edge.
+    ///
+    /// # Returns
+    ///
+    /// The offset along the edge of the point closest to `location`.
+    ///
+    fn project(&self, location: Coord<f64>) -> Length;
+
+    /// Returns the part of the edge's geometry between two offsets.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The distance from the source node where the part starts.
+    /// * `end` - The distance from the source node where the part ends. If it is smaller than
+    ///   `start`, the part runs towards the source node.
+    ///
+    /// # Returns
+    ///
+    /// A `LineString<f64>` from the coordinate at `start` to the coordinate at `end`.
+    ///
+    fn sub_path(&self, start: Length, end: Length) -> LineString<f64>;
+
+    /// Returns the node reached when travelling along the edge in the given direction.
+    fn node_ahead(&self, direction: TrackDirection) -> NodeId;
+
+    /// Returns the position at the given end node of the edge, facing into the edge.
     ///
     /// # Arguments
     ///
-    /// * `current_location` - A `Coord<f64>` representing the current location on the edge.
-    /// * `direction_coord` - A `Coord<f64>` representing the target direction along the edge.
+    /// * `node_id` - The source or target node of the edge.
     ///
     /// # Returns
     ///
-    /// A `f64` representing the distance to the last coordinate in the linestring.
+    /// A `TrackPosition` at `node_id`, or `None` if the node is not an end of the edge.
     ///
-    fn distance_to_end(&self, current_location: Coord<f64>, direction_coord: Coord<f64>) -> Length;
+    fn start_position(&self, node_id: NodeId) -> Option<TrackPosition>;
+
+    /// Calculates the distance from the given position to the end of the edge in its direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A `TrackPosition` on this edge.
+    ///
+    /// # Returns
+    ///
+    /// A `Length` representing the distance to the node ahead.
+    ///
+    fn distance_to_end(&self, position: &TrackPosition) -> Length;
 
     /// Calculates a new position on the edge based on the given parameters.
     ///
     /// # Arguments
     ///
-    /// * `current_location` - A `Coord<f64>` representing the current location on the edge.
-    /// * `distance_to_travel` - A `f64` representing the distance to travel along the edge from the current location.
-    /// * `direction_coord` - A `Coord<f64>` representing the target direction along the edge.
+    /// * `position` - A `TrackPosition` on this edge.
+    /// * `distance_to_travel` - The distance to travel in the direction of `position`.
     ///
     /// # Returns
     ///
-    /// A `Coord<f64>` representing the new position on the edge after traveling the specified distance in the given direction.
+    /// A `TrackPosition` after traveling the specified distance, clamped to the ends of the edge.
     ///
     fn position_on_edge(
         &self,
-        current_location: Coord<f64>,
+        position: &TrackPosition,
         distance_to_travel: Length,
-        direction_coord: Coord<f64>,
-    ) -> Coord<f64>;
+    ) -> TrackPosition;
 }
 
 impl RailwayEdgeAlgos for RailwayEdge {
-    fn distance_to_end(&self, current_location: Coord<f64>, direction_coord: Coord<f64>) -> Length {
-        // Get the points in front of the current_location in the direction of direction_coord
-        let points_in_front = points_in_front(&self.path, current_location, direction_coord);
+    fn path_length(&self) -> Length {
+        self.path
+            .0
+            .windows(2)
+            .map(|pair| pair[0].distance(&pair[1]))
+            .fold(Length::new::<meter>(0.0), |total, length| total + length)
+    }
 
-        // If there are no points in front, return 0.0
-        if points_in_front.is_empty() {
-            return Length::new::<meter>(0.0);
+    fn coordinate_at(&self, offset: Length) -> Coord<f64> {
+        let mut remaining_distance = offset.get::<meter>().max(0.0);
+
+        for pair in self.path.0.windows(2) {
+            let segment_distance = pair[0].distance(&pair[1]).get::<meter>();
+            if remaining_distance <= segment_distance && segment_distance > 0.0 {
+                let ratio = remaining_distance / segment_distance;
+                return Coord {
+                    x: pair[0].x + ratio * (pair[1].x - pair[0].x),
+                    y: pair[0].y + ratio * (pair[1].y - pair[0].y),
+                };
+            }
+            remaining_distance -= segment_distance;
         }
+        self.path.0.last().copied().unwrap_or_default()
+    }
 
-        let mut total_distance = Length::new::<meter>(0.0);
-        let mut current_point = current_location;
+    fn project(&self, location: Coord<f64>) -> Length {
+        project_on_path(&self.path, location)
+            .map(|(offset, _, _)| offset)
+            .unwrap_or_default()
+    }
 
-        for next_point in points_in_front {
-            let segment_distance = current_point.distance(&next_point);
-            total_distance += segment_distance;
+    fn sub_path(&self, start: Length, end: Length) -> LineString<f64> {
+        if end < start {
+            let mut path = self.sub_path(end, start);
+            path.0.reverse();
+            return path;
+        }
+        let (start_offset, end_offset) = (start.get::<meter>(), end.get::<meter>());
+        let mut coords = vec![self.coordinate_at(start)];
+        let mut offset = 0.0;
+        for pair in self.path.0.windows(2) {
+            offset += pair[0].distance(&pair[1]).get::<meter>();
+            if offset > start_offset && offset < end_offset {
+                coords.push(pair[1]);
+            }
+        }
+        coords.push(self.coordinate_at(end));
+        LineString::from(coords)
+    }
+
+    fn node_ahead(&self, direction: TrackDirection) -> NodeId {
+        match direction {
+            TrackDirection::Forward => self.target,
+            TrackDirection::Backward => self.source,
+        }
+    }
+
+    fn start_position(&self, node_id: NodeId) -> Option<TrackPosition> {
+        if node_id == self.source {
+            Some(TrackPosition::new(
+                self.id,
+                Length::new::<meter>(0.0),
+                TrackDirection::Forward,
+            ))
+        } else if node_id == self.target {
+            Some(TrackPosition::new(
+                self.id,
+                self.path_length(),
+                TrackDirection::Backward,
+            ))
+        } else {
+            None
+        }
+    }
 
-            current_point = next_point;
+    fn distance_to_end(&self, position: &TrackPosition) -> Length {
+        let offset = position
+            .offset
+            .max(Length::new::<meter>(0.0))
+            .min(self.path_length());
+        match position.direction {
+            TrackDirection::Forward => self.path_length() - offset,
+            TrackDirection::Backward => offset,
         }
-        total_distance
     }
 
     fn position_on_edge(
         &self,
-        current_location: Coord<f64>,
+        position: &TrackPosition,
         distance_to_travel: Length,
-        direction_coord: Coord<f64>,
-    ) -> Coord<f64> {
-        // Get the points in front of the current_location in the direction of direction_coord
-        let points_in_front = points_in_front(&self.path, current_location, direction_coord);
-
-        // If there are no points in front, return the current_location
-        if points_in_front.is_empty() {
-            return current_location;
+    ) -> TrackPosition {
+        let offset = match position.direction {
+            TrackDirection::Forward => position.offset + distance_to_travel,
+            TrackDirection::Backward => position.offset - distance_to_travel,
+        };
+        TrackPosition {
+            offset: offset
+                .max(Length::new::<meter>(0.0))
+                .min(self.path_length()),
+            ..*position
         }
+    }
+}
 
-        // Calculate the remaining distance to travel
-        let mut remaining_distance = distance_to_travel.get::<meter>();
-
-        // Iterate through the points in front and find the point where the remaining_distance is reached
-        let mut current_point = current_location;
-        let mut new_position = current_location;
-
-        for next_point in points_in_front {
-            let current_point_geo = Point::new(current_point.x, current_point.y);
-            let next_point_geo = Point::new(next_point.x, next_point.y);
-
-            // Use haversine_distance instead of euclidean_distance
-            let segment_distance = Haversine.distance(current_point_geo, next_point_geo);
+/// Projects `location` onto `path`.
+///
+/// Each segment is treated as straight on a plane tangent at its start, which is accurate for the
+/// short segments of railway geometries.
+///
+/// # Returns
+///
+/// The offset along the path of the closest point, the closest point itself and its distance to
+/// `location`, or `None` if the path is empty.
+pub(crate) fn project_on_path(
+    path: &LineString<f64>,
+    location: Coord<f64>,
+) -> Option<(Length, Coord<f64>, Length)> {
+    let first = *path.0.first()?;
+    let mut best = (Length::new::<meter>(0.0), first, first.distance(&location));
+    let mut offset = Length::new::<meter>(0.0);
 
-            let ratio = remaining_distance / segment_distance;
-            new_position.x = current_point.x + ratio * (next_point.x - current_point.x);
-            new_position.y = current_point.y + ratio * (next_point.y - current_point.y);
-            if remaining_distance < segment_distance {
-                break;
-            } else {
-                current_point = next_point;
-                remaining_distance -= segment_distance;
-            }
+    for pair in path.0.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let crs = Crs::LocalTangentPlane { origin: start };
+        let Coord { x: dx, y: dy } = crs.project(end);
+        let Coord { x: px, y: py } = crs.project(location);
+        let segment_squared = dx * dx + dy * dy;
+        let ratio = if segment_squared > 0.0 {
+            ((px * dx + py * dy) / segment_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let point = Coord {
+            x: start.x + ratio * (end.x - start.x),
+            y: start.y + ratio * (end.y - start.y),
+        };
+        let segment_length = start.distance(&end);
+        let distance = point.distance(&location);
+        if distance < best.2 {
+            best = (offset + segment_length * ratio, point, distance);
         }
-        new_position
+        offset += segment_length;
     }
+    Some(best)
 }
@@ -0,0 +1,392 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use geo::LineString;
+use petgraph::{graph::EdgeIndex, visit::EdgeRef};
+use serde::{Deserialize, Serialize};
+use uom::si::{
+    f64::{Length, Time},
+    length::meter,
+    time::second,
+};
+
+use super::{
+    travel_time::{EdgeRun, EdgeRuns},
+    RailwayEdgeAlgos, TrainProfile,
+};
+use crate::{
+    prelude::{RailwayGraph, RailwayGraphExt, RailwaySpeedLimits, TrackDirection},
+    types::{EdgeId, NodeId},
+};
+
+/// How far a train may travel from the start node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReachabilityBudget<'a> {
+    /// The track distance the train may travel.
+    Distance(Length),
+    /// The time the train may run, starting at standstill. The running time is estimated as by
+    /// `PathFinding::timed_route`, without braking at the end.
+    Time {
+        /// The running time.
+        time: Time,
+        /// The maximum speed, acceleration and deceleration of the train.
+        profile: TrainProfile,
+        /// The speed limits of the edges.
+        speed_limits: &'a RailwaySpeedLimits,
+    },
+}
+
+/// A node reached within the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReachedNode {
+    /// The ID of the node.
+    pub node_id: NodeId,
+    /// The distance in meters or the time in seconds needed to reach the node.
+    pub cost: f64,
+}
+
+/// An edge, or the part of an edge, reached within the budget.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReachedEdge {
+    /// The ID of the edge.
+    pub edge_id: EdgeId,
+    /// The ID of the node the edge is entered from.
+    pub from: NodeId,
+    /// The distance in meters or the time in seconds needed to reach the `from` node.
+    pub cost: f64,
+    /// The reached length in meters, measured from the `from` node.
+    pub length: f64,
+    /// `true` if the whole edge is reached, `false` if the budget runs out on the edge.
+    pub complete: bool,
+    /// The geometry of the reached part, starting at the `from` node.
+    pub path: LineString<f64>,
+}
+
+/// The part of a railway network reachable from a node within a budget.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reachability {
+    /// The ID of the start node.
+    pub start: NodeId,
+    /// The nodes reached, including the start node, ordered by cost.
+    pub nodes: Vec<ReachedNode>,
+    /// The edges reached completely, each once, and the parts of edges where the budget runs
+    /// out. An edge reached partially from both ends has a part for each end.
+    pub edges: Vec<ReachedEdge>,
+}
+
+/// Finds the nodes and edge parts reachable from a start node within a budget.
+///
+/// The search follows the allowed transitions of the topology graph. Edges the budget runs out
+/// on are cut at the point where it is used up.
+pub(super) fn reachable_within(
+    graph: &RailwayGraph,
+    start: NodeId,
+    budget: &ReachabilityBudget,
+) -> Reachability {
+    let mut reachability = Reachability {
+        start,
+        ..Default::default()
+    };
+    let Some(&(start_1, start_2)) = graph.topology_graph.id_to_index(start) else {
+        return reachability;
+    };
+
+    let default_limits = RailwaySpeedLimits::default();
+    let (limit, profile, speed_limits) = match budget {
+        ReachabilityBudget::Distance(distance) => (
+            distance.get::<meter>(),
+            TrainProfile::default(),
+            &default_limits,
+        ),
+        ReachabilityBudget::Time {
+            time,
+            profile,
+            speed_limits,
+        } => (time.get::<second>(), *profile, *speed_limits),
+    };
+    let search = BudgetSearch {
+        by_time: matches!(budget, ReachabilityBudget::Time { .. }),
+        limit,
+        profile: &profile,
+    };
+    let topology = &graph.topology_graph.graph;
+    let mut runs = EdgeRuns::new(graph, &profile, speed_limits);
+
+    // Topology edges are entered at a cost with the speed the train had before entering them.
+    let mut entries: Vec<(EdgeIndex, f64, f64)> = topology
+        .edges(start_1)
+        .chain(topology.edges(start_2))
+        .map(|edge| (edge.id(), 0.0, 0.0))
+        .collect();
+    // The cost at the end of each fully reached topology edge and the cost it was entered at.
+    let mut costs: HashMap<EdgeIndex, (f64, f64)> = HashMap::new();
+    let mut partial: HashMap<EdgeIndex, (f64, f64)> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    let mut node_costs: HashMap<NodeId, f64> = HashMap::from([(start, 0.0)]);
+
+    loop {
+        for (edge, cost, speed) in entries.drain(..) {
+            let Some(run) = runs.get(edge) else {
+                continue;
+            };
+            let total = cost + search.edge_cost(&run, speed);
+            if total <= limit {
+                if costs.get(&edge).is_none_or(|&(known, _)| total < known) {
+                    costs.insert(edge, (total, cost));
+                    heap.push(State(total, edge));
+                }
+            } else if partial.get(&edge).is_none_or(|&(known, _)| cost < known) {
+                partial.insert(edge, (cost, speed));
+            }
+        }
+
+        let Some(State(cost, edge)) = heap.pop() else {
+            break;
+        };
+        if cost > costs[&edge].0 {
+            continue;
+        }
+        let Some(run) = runs.get(edge) else {
+            continue;
+        };
+        let (_, port) = topology.edge_endpoints(edge).unwrap();
+        if let Some(&node) = graph.topology_graph.index_to_id(port) {
+            let known = node_costs.entry(node).or_insert(cost);
+            *known = known.min(cost);
+        }
+        entries.extend(
+            topology
+                .edges(port)
+                .map(|next| (next.id(), cost, run.exit_speed)),
+        );
+    }
+
+    let mut complete: HashSet<EdgeId> = HashSet::new();
+    let mut edge_costs: Vec<(EdgeIndex, f64)> = costs
+        .into_iter()
+        .map(|(edge, (_, entry))| (edge, entry))
+        .collect();
+    edge_costs.sort_by(|a, b| a.1.total_cmp(&b.1));
+    for (edge, cost) in edge_costs {
+        if !complete.insert(topology[edge].edge_id) {
+            continue;
+        }
+        if let Some(run) = runs.get(edge) {
+            reachability
+                .edges
+                .extend(reached_edge(graph, edge, &run, cost, run.length));
+        }
+    }
+    let mut partial: Vec<(EdgeIndex, (f64, f64))> = partial
+        .into_iter()
+        .filter(|(edge, _)| !complete.contains(&topology[*edge].edge_id))
+        .collect();
+    partial.sort_by(|a, b| (a.1).0.total_cmp(&(b.1).0));
+    for (edge, (cost, speed)) in partial {
+        if let Some(run) = runs.get(edge) {
+            let length = search.reached_length(&run, cost, speed);
+            if length > 0.0 {
+                reachability
+                    .edges
+                    .extend(reached_edge(graph, edge, &run, cost, length));
+            }
+        }
+    }
+
+    reachability.nodes = node_costs
+        .into_iter()
+        .map(|(node_id, cost)| ReachedNode { node_id, cost })
+        .collect();
+    reachability
+        .nodes
+        .sort_by(|a, b| a.cost.total_cmp(&b.cost).then(a.node_id.cmp(&b.node_id)));
+    reachability
+}
+
+/// Builds the `ReachedEdge` for the first `length` meters of a topology edge.
+fn reached_edge(
+    graph: &RailwayGraph,
+    edge: EdgeIndex,
+    run: &EdgeRun,
+    cost: f64,
+    length: f64,
+) -> Option<ReachedEdge> {
+    let topology = &graph.topology_graph;
+    let (from, _) = topology.graph.edge_endpoints(edge)?;
+    let from = *topology.index_to_id(from)?;
+    let railway_edge = graph.get_edge_by_id(topology.graph[edge].edge_id)?;
+
+    // The reached length is measured along the edge, while the geometry may be slightly longer
+    // or shorter.
+    let path_length = railway_edge.path_length();
+    let reached = if run.length > 0.0 {
+        path_length * (length / run.length).min(1.0)
+    } else {
+        path_length
+    };
+    let path = match run.direction {
+        TrackDirection::Forward => railway_edge.sub_path(Length::new::<meter>(0.0), reached),
+        TrackDirection::Backward => railway_edge.sub_path(path_length, path_length - reached),
+    };
+    Some(ReachedEdge {
+        edge_id: railway_edge.id,
+        from,
+        cost,
+        length,
+        complete: length >= run.length,
+        path,
+    })
+}
+
+/// Measures edges against the budget in meters or seconds.
+struct BudgetSearch<'a> {
+    by_time: bool,
+    limit: f64,
+    profile: &'a TrainProfile,
+}
+
+impl BudgetSearch<'_> {
+    /// Returns the cost of running along a whole edge, entered at the given speed.
+    fn edge_cost(&self, run: &EdgeRun, speed: f64) -> f64 {
+        if self.by_time {
+            run.time + self.profile.speed_change(speed, run.entry_speed)
+        } else {
+            run.length
+        }
+    }
+
+    /// Returns how many meters of an edge entered at `cost` with `speed` are reached before the
+    /// budget runs out. Speed changes are accounted for where the speed limit changes.
+    fn reached_length(&self, run: &EdgeRun, cost: f64, speed: f64) -> f64 {
+        let mut remaining = self.limit - cost;
+        if !self.by_time {
+            return remaining.clamp(0.0, run.length);
+        }
+        let mut reached = 0.0;
+        let mut speed = speed;
+        for &(length, part_speed) in &run.parts {
+            remaining -= self.profile.speed_change(speed, part_speed);
+            if remaining <= 0.0 {
+                break;
+            }
+            let time = length / part_speed;
+            if time >= remaining {
+                return reached + remaining * part_speed;
+            }
+            remaining -= time;
+            reached += length;
+            speed = part_speed;
+        }
+        reached
+    }
+}
+
+#[derive(PartialEq)]
+struct State(f64, EdgeIndex);
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::Distance;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::{tests::siding_elements, RailwayGraphAlgos};
+    use approx::assert_relative_eq;
+    use uom::si::velocity::meter_per_second;
+
+    fn reached_ids(reachability: &Reachability) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = reachability.nodes.iter().map(|node| node.node_id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_reachable_within_distance() {
+        let graph = from_railway_elements(&siding_elements());
+        let length_20 = graph.get_edge_by_id(20).unwrap().length;
+        let length_21 = graph.get_edge_by_id(21).unwrap().length;
+        let budget = length_20 + length_21 / 2.0;
+
+        let reachability = graph.reachable_within(
+            10,
+            &ReachabilityBudget::Distance(Length::new::<meter>(budget)),
+        );
+        assert_eq!(reached_ids(&reachability), vec![10, 11]);
+        assert_eq!(reachability.nodes[0].node_id, 10);
+        assert_relative_eq!(reachability.nodes[1].cost, length_20);
+
+        let edge_20 = reachability.edges.iter().find(|e| e.edge_id == 20).unwrap();
+        assert!(edge_20.complete);
+        let edge_21 = reachability.edges.iter().find(|e| e.edge_id == 21).unwrap();
+        assert!(!edge_21.complete);
+        assert_eq!(edge_21.from, 11);
+        assert_relative_eq!(edge_21.cost, length_20);
+        assert_relative_eq!(edge_21.length, length_21 / 2.0);
+        // The cut lies halfway between nodes 11 and 12.
+        let end = *edge_21.path.0.last().unwrap();
+        assert_relative_eq!(end.x, 8.015, epsilon = 1e-6);
+        assert_relative_eq!(edge_21.path.0[0].x, 8.01, epsilon = 1e-9);
+        assert_eq!(reachability.edges.len(), 2);
+    }
+
+    #[test]
+    fn test_reachable_within_from_switch() {
+        let graph = from_railway_elements(&siding_elements());
+        let reachability =
+            graph.reachable_within(12, &ReachabilityBudget::Distance(Length::new::<meter>(1e6)));
+
+        assert_eq!(reached_ids(&reachability), vec![10, 11, 12, 13, 14]);
+        assert!(reachability.edges.iter().all(|edge| edge.complete));
+        assert_eq!(reachability.edges.len(), 4);
+        let mut all = graph.reachable_nodes(12);
+        all.push(12);
+        all.sort();
+        all.dedup();
+        assert_eq!(reached_ids(&reachability), all);
+
+        let unknown =
+            graph.reachable_within(99, &ReachabilityBudget::Distance(Length::new::<meter>(1e6)));
+        assert!(unknown.nodes.is_empty());
+    }
+
+    #[test]
+    fn test_reachable_within_time() {
+        let graph = from_railway_elements(&siding_elements());
+        let profile = TrainProfile {
+            max_speed: uom::si::f64::Velocity::new::<meter_per_second>(10.0),
+            ..Default::default()
+        };
+        let speed_limits = RailwaySpeedLimits::default();
+        // Starting takes 20 s at 0.5 m/s² and loses 10 s, so after 60 s the train has run
+        // 50 s at 10 m/s.
+        let reachability = graph.reachable_within(
+            10,
+            &ReachabilityBudget::Time {
+                time: Time::new::<second>(60.0),
+                profile,
+                speed_limits: &speed_limits,
+            },
+        );
+        assert_eq!(reached_ids(&reachability), vec![10]);
+        let edge = &reachability.edges[0];
+        assert_eq!(edge.edge_id, 20);
+        assert_relative_eq!(edge.length, 500.0, epsilon = 1e-9);
+        let travelled = edge.path.0[0].distance(edge.path.0.last().unwrap());
+        assert_relative_eq!(travelled.get::<meter>(), 500.0, max_relative = 0.01);
+    }
+}
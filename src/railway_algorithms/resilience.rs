@@ -0,0 +1,418 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+};
+use serde::{Deserialize, Serialize};
+
+use super::distance_matrix::{distance_matrix, map_chunks};
+use crate::{
+    prelude::RailwayGraph,
+    types::{EdgeId, NodeId},
+};
+
+/// Distances closer than this share of their length count as equal when counting shortest paths.
+const EQUAL_DISTANCE_TOLERANCE: f64 = 1e-9;
+
+/// A pair of nodes whose shortest route gets longer when edges are closed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Detour {
+    /// The ID of the first node.
+    pub from: NodeId,
+    /// The ID of the second node.
+    pub to: NodeId,
+    /// The shortest path distance in meters with all edges open.
+    pub distance: f64,
+    /// The shortest path distance in meters with the edges closed.
+    pub closed_distance: f64,
+}
+
+/// The effect of closing a set of edges on the connections between nodes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClosureImpact {
+    /// The IDs of the closed edges.
+    pub closed_edges: Vec<EdgeId>,
+    /// The pairs of nodes that are connected with all edges open but not with the edges closed.
+    pub disconnected: Vec<(NodeId, NodeId)>,
+    /// The pairs of nodes that stay connected on a longer route, the largest detour first.
+    pub detours: Vec<Detour>,
+}
+
+/// Analyses of how a railway network depends on single tracks and junctions.
+///
+/// Finds the bridges, the articulation points and the edge betweenness of the network and
+/// reports the effect of closing tracks.
+pub trait RailwayGraphResilience {
+    /// Finds the bridges of the network, the edges whose closure splits it in two.
+    ///
+    /// Parallel edges between the same two nodes are never bridges, as either one keeps the
+    /// nodes connected.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the bridge edges in ascending order.
+    fn bridges(&self) -> Vec<EdgeId>;
+
+    /// Finds the articulation points of the network, the nodes whose removal splits it.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the articulation nodes in ascending order.
+    fn articulation_points(&self) -> Vec<NodeId>;
+
+    /// Computes the edge betweenness centrality of every edge.
+    ///
+    /// The betweenness of an edge is the number of node pairs whose shortest path runs along it.
+    /// Pairs with several shortest paths of equal length count in equal shares for each path.
+    /// The searches from the nodes are spread over all available cores.
+    ///
+    /// # Returns
+    ///
+    /// The betweenness of every edge, keyed by edge ID.
+    fn edge_betweenness(&self) -> HashMap<EdgeId, f64>;
+
+    /// Finds out which connections between nodes a closure of edges breaks or lengthens.
+    ///
+    /// # Arguments
+    ///
+    /// * `closed_edges` - The IDs of the edges to close.
+    /// * `nodes` - The IDs of the nodes whose connections are compared, e.g. the stations. Every
+    ///   pair of them is checked, so passing all nodes of a large network takes a while.
+    ///
+    /// # Returns
+    ///
+    /// A `ClosureImpact` with the disconnected node pairs and the pairs with a longer route.
+    fn closure_impact(&self, closed_edges: &[EdgeId], nodes: &[NodeId]) -> ClosureImpact;
+}
+
+impl RailwayGraphResilience for RailwayGraph {
+    fn bridges(&self) -> Vec<EdgeId> {
+        cut_elements(self).0
+    }
+
+    fn articulation_points(&self) -> Vec<NodeId> {
+        cut_elements(self).1
+    }
+
+    fn edge_betweenness(&self) -> HashMap<EdgeId, f64> {
+        let physical = &self.physical_graph.graph;
+        let sources: Vec<NodeIndex> = physical.node_indices().collect();
+        let partial_scores = map_chunks(&sources, |chunk| {
+            let mut scores: HashMap<EdgeIndex, f64> = HashMap::new();
+            for &source in chunk {
+                accumulate_betweenness(self, source, &mut scores);
+            }
+            scores
+        });
+
+        let mut betweenness: HashMap<EdgeId, f64> =
+            physical.edge_weights().map(|edge| (edge.id, 0.0)).collect();
+        for scores in partial_scores {
+            for (edge, score) in scores {
+                // Every pair is counted from both of its nodes.
+                *betweenness.get_mut(&physical[edge].id).unwrap() += score / 2.0;
+            }
+        }
+        betweenness
+    }
+
+    fn closure_impact(&self, closed_edges: &[EdgeId], nodes: &[NodeId]) -> ClosureImpact {
+        let closed: HashSet<EdgeId> = closed_edges.iter().copied().collect();
+        let open = distance_matrix(self, nodes, nodes, &HashSet::new());
+        let with_closure = distance_matrix(self, nodes, nodes, &closed);
+
+        let mut impact = ClosureImpact {
+            closed_edges: closed_edges.to_vec(),
+            ..Default::default()
+        };
+        for (row, &from) in nodes.iter().enumerate() {
+            for (column, &to) in nodes.iter().enumerate().skip(row + 1) {
+                let Some(distance) = open.distances[row][column] else {
+                    continue;
+                };
+                match with_closure.distances[row][column] {
+                    None => impact.disconnected.push((from, to)),
+                    Some(closed_distance)
+                        if closed_distance > distance * (1.0 + EQUAL_DISTANCE_TOLERANCE) =>
+                    {
+                        impact.detours.push(Detour {
+                            from,
+                            to,
+                            distance,
+                            closed_distance,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        impact.detours.sort_by(|a, b| {
+            (b.closed_distance - b.distance).total_cmp(&(a.closed_distance - a.distance))
+        });
+        impact
+    }
+}
+
+/// A node on the search stack, the edge it was reached along and its edges still to visit.
+type SearchFrame = (NodeIndex, Option<EdgeIndex>, Vec<(NodeIndex, EdgeIndex)>);
+
+/// Finds the bridges and articulation points with an iterative depth-first search tracking the
+/// lowest discovery time reachable from each subtree.
+///
+/// The search skips only the edge it arrived along, not all edges back to the parent node, so
+/// parallel edges are recognised as a cycle.
+fn cut_elements(graph: &RailwayGraph) -> (Vec<EdgeId>, Vec<NodeId>) {
+    let physical = &graph.physical_graph.graph;
+    let mut discovery = vec![usize::MAX; physical.node_count()];
+    let mut low = vec![usize::MAX; physical.node_count()];
+    let mut time = 0;
+    let mut bridges = Vec::new();
+    let mut articulation: HashSet<NodeIndex> = HashSet::new();
+
+    for root in physical.node_indices() {
+        if discovery[root.index()] != usize::MAX {
+            continue;
+        }
+        discovery[root.index()] = time;
+        low[root.index()] = time;
+        time += 1;
+        let mut root_children = 0;
+        let mut stack: Vec<SearchFrame> = vec![(root, None, neighbours(graph, root))];
+
+        while let Some((node, parent_edge, remaining)) = stack.last_mut() {
+            let node = *node;
+            if let Some((next, edge)) = remaining.pop() {
+                if Some(edge) == *parent_edge {
+                    continue;
+                }
+                if discovery[next.index()] == usize::MAX {
+                    discovery[next.index()] = time;
+                    low[next.index()] = time;
+                    time += 1;
+                    if node == root {
+                        root_children += 1;
+                    }
+                    stack.push((next, Some(edge), neighbours(graph, next)));
+                } else {
+                    low[node.index()] = low[node.index()].min(discovery[next.index()]);
+                }
+                continue;
+            }
+
+            let parent_edge = *parent_edge;
+            stack.pop();
+            if let (Some((parent, _, _)), Some(edge)) = (stack.last(), parent_edge) {
+                let parent = *parent;
+                low[parent.index()] = low[parent.index()].min(low[node.index()]);
+                if low[node.index()] > discovery[parent.index()] {
+                    bridges.push(physical[edge].id);
+                }
+                if parent != root && low[node.index()] >= discovery[parent.index()] {
+                    articulation.insert(parent);
+                }
+            }
+        }
+        if root_children > 1 {
+            articulation.insert(root);
+        }
+    }
+
+    let mut articulation: Vec<NodeId> = articulation
+        .into_iter()
+        .map(|index| physical[index].id)
+        .collect();
+    bridges.sort_unstable();
+    articulation.sort_unstable();
+    (bridges, articulation)
+}
+
+fn neighbours(graph: &RailwayGraph, node: NodeIndex) -> Vec<(NodeIndex, EdgeIndex)> {
+    graph
+        .physical_graph
+        .graph
+        .edges(node)
+        .map(|edge| {
+            let other = if edge.source() == node {
+                edge.target()
+            } else {
+                edge.source()
+            };
+            (other, edge.id())
+        })
+        .collect()
+}
+
+/// Adds the shares of the shortest paths from `source` to the edge scores, following Brandes'
+/// algorithm for weighted graphs.
+fn accumulate_betweenness(
+    graph: &RailwayGraph,
+    source: NodeIndex,
+    scores: &mut HashMap<EdgeIndex, f64>,
+) {
+    let physical = &graph.physical_graph.graph;
+    let mut distances: HashMap<NodeIndex, f64> = HashMap::from([(source, 0.0)]);
+    let mut path_counts: HashMap<NodeIndex, f64> = HashMap::from([(source, 1.0)]);
+    let mut predecessors: HashMap<NodeIndex, Vec<(NodeIndex, EdgeIndex)>> = HashMap::new();
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    let mut heap = BinaryHeap::from([State(0.0, source)]);
+
+    while let Some(State(distance, node)) = heap.pop() {
+        if !visited.insert(node) {
+            continue;
+        }
+        order.push(node);
+        for (next, edge) in neighbours(graph, node) {
+            if next == node || visited.contains(&next) {
+                continue;
+            }
+            let next_distance = distance + physical[edge].length;
+            let count = path_counts[&node];
+            match distances.get(&next) {
+                Some(&known)
+                    if (next_distance - known).abs()
+                        <= EQUAL_DISTANCE_TOLERANCE * known.max(1.0) =>
+                {
+                    *path_counts.get_mut(&next).unwrap() += count;
+                    predecessors.entry(next).or_default().push((node, edge));
+                }
+                Some(&known) if next_distance > known => {}
+                _ => {
+                    distances.insert(next, next_distance);
+                    path_counts.insert(next, count);
+                    predecessors.insert(next, vec![(node, edge)]);
+                    heap.push(State(next_distance, next));
+                }
+            }
+        }
+    }
+
+    let mut dependencies: HashMap<NodeIndex, f64> = HashMap::new();
+    for node in order.into_iter().rev() {
+        let dependency = dependencies.get(&node).copied().unwrap_or(0.0);
+        for &(previous, edge) in predecessors.get(&node).into_iter().flatten() {
+            let share = path_counts[&previous] / path_counts[&node] * (1.0 + dependency);
+            *scores.entry(edge).or_insert(0.0) += share;
+            *dependencies.entry(previous).or_insert(0.0) += share;
+        }
+    }
+}
+
+#[derive(PartialEq)]
+struct State(f64, NodeIndex);
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::{
+        from_railway_elements, Coordinate, ElementType, RailwayElement,
+    };
+    use crate::railway_algorithms::tests::{loop_elements, siding_elements, test_elements};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_bridges_and_articulation_points() {
+        let graph = from_railway_elements(&loop_elements());
+        assert_eq!(graph.bridges(), vec![10]);
+        assert_eq!(graph.articulation_points(), vec![1]);
+
+        let graph = from_railway_elements(&siding_elements());
+        assert_eq!(graph.bridges(), vec![20, 21, 22, 23]);
+        assert_eq!(graph.articulation_points(), vec![11, 12]);
+    }
+
+    #[test]
+    fn test_parallel_edges_are_no_bridges() {
+        let mut elements = test_elements();
+        elements.push(RailwayElement {
+            id: 6,
+            element_type: ElementType::Way,
+            lat: None,
+            lon: None,
+            tags: Some(HashMap::new()),
+            nodes: Some(vec![1, 2]),
+            geometry: Some(vec![
+                Coordinate {
+                    lat: 50.1109,
+                    lon: 8.6821,
+                },
+                Coordinate {
+                    lat: 50.1159,
+                    lon: 8.6881,
+                },
+                Coordinate {
+                    lat: 50.1209,
+                    lon: 8.6921,
+                },
+            ]),
+        });
+        let graph = from_railway_elements(&elements);
+
+        assert_eq!(graph.physical_graph.graph.edge_count(), 3);
+        assert_eq!(graph.bridges(), vec![5]);
+        assert_eq!(graph.articulation_points(), vec![2]);
+    }
+
+    #[test]
+    fn test_edge_betweenness() {
+        let graph = from_railway_elements(&test_elements());
+        let betweenness = graph.edge_betweenness();
+        // Both edges lie on the paths of two of the three node pairs.
+        assert_relative_eq!(betweenness[&4], 2.0);
+        assert_relative_eq!(betweenness[&5], 2.0);
+
+        let graph = from_railway_elements(&loop_elements());
+        let betweenness = graph.edge_betweenness();
+        // The bridge to node 4 carries the paths of the three pairs with node 4.
+        assert_relative_eq!(betweenness[&10], 3.0);
+        let total: f64 = betweenness.values().sum();
+        let path_edges: f64 = [(4, 1), (4, 2), (4, 3), (1, 2), (1, 3), (2, 3)]
+            .iter()
+            .map(|&(from, to)| {
+                use crate::railway_algorithms::PathFinding;
+                graph.shortest_path_edges(from, to).unwrap().len() as f64
+            })
+            .sum();
+        assert_relative_eq!(total, path_edges);
+    }
+
+    #[test]
+    fn test_closure_impact() {
+        let graph = from_railway_elements(&loop_elements());
+
+        let impact = graph.closure_impact(&[11], &[1, 2, 3, 4]);
+        assert_eq!(impact.closed_edges, vec![11]);
+        assert!(impact.disconnected.is_empty());
+        let pairs: Vec<(NodeId, NodeId)> = impact
+            .detours
+            .iter()
+            .map(|detour| (detour.from, detour.to))
+            .collect();
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs.contains(&(1, 2)));
+        assert!(pairs.contains(&(2, 4)));
+        assert!(impact.detours[0].closed_distance > impact.detours[0].distance);
+
+        let impact = graph.closure_impact(&[10], &[1, 2, 3, 4]);
+        assert_eq!(impact.disconnected, vec![(1, 4), (2, 4), (3, 4)]);
+        assert!(impact.detours.is_empty());
+    }
+}
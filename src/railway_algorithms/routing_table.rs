@@ -0,0 +1,411 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BinaryHeap, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, RwLock},
+};
+
+use petgraph::{graph::EdgeIndex, visit::EdgeRef};
+use uom::si::length::meter;
+
+use super::directional_routing::{DirectionalSearch, ReversalOptions};
+use crate::{
+    prelude::{RailwayGraph, RoutingConstraints, TrackDirection},
+    types::NodeId,
+};
+
+/// The number of targets whose costs are kept before the cache is cleared.
+const MAX_CACHED_TARGETS: usize = 4096;
+
+/// A prepared next-hop table answering the queries of `DirectionalPathFinding::next_directional_node`
+/// or `DirectionalPathFinding::next_compatible_node` without searching the graph.
+///
+/// The table stores the transitions of the direction-aware search once. The first query towards a
+/// target runs a single backward search from the target and caches the cost to reach it from every
+/// topology edge, so that all later queries towards the same target only compare the few edges
+/// leaving the current node.
+///
+/// The table holds copies of the graph's topology, so it has to be rebuilt after the graph, the
+/// `ReversalOptions` or the `RoutingConstraints` are changed. `is_valid_for` tells whether this is
+/// the case.
+#[derive(Debug)]
+pub struct RoutingTable {
+    fingerprint: u64,
+    costs: Vec<f64>,
+    nodes_ahead: Vec<Option<NodeId>>,
+    successors: Vec<Vec<(usize, f64)>>,
+    predecessors: Vec<Vec<(usize, f64)>>,
+    departures: HashMap<NodeId, Vec<usize>>,
+    arrivals: HashMap<(NodeId, NodeId), usize>,
+    goals: HashMap<NodeId, Vec<usize>>,
+    costs_to_go: RwLock<HashMap<NodeId, Arc<Vec<f64>>>>,
+}
+
+impl RoutingTable {
+    /// Prepares the table for a graph and the rules for where trains may reverse.
+    pub fn new(graph: &RailwayGraph, options: &ReversalOptions) -> Self {
+        Self::prepare(graph, options, None)
+    }
+
+    /// Prepares the table for a graph, the rules for where trains may reverse and the
+    /// constraints of a vehicle and the direction restrictions of the track.
+    pub fn with_constraints(
+        graph: &RailwayGraph,
+        options: &ReversalOptions,
+        constraints: RoutingConstraints,
+    ) -> Self {
+        Self::prepare(graph, options, Some(constraints))
+    }
+
+    fn prepare(
+        graph: &RailwayGraph,
+        options: &ReversalOptions,
+        constraints: Option<RoutingConstraints>,
+    ) -> Self {
+        let search = DirectionalSearch::new(graph, options, constraints);
+        let topology = &graph.topology_graph.graph;
+        let penalty = options.reversal_penalty.get::<meter>();
+        let edge_count = topology.edge_count();
+
+        // Edges the constraints do not allow cost infinity and are never part of a route.
+        let costs: Vec<f64> = (0..edge_count)
+            .map(|edge| search.cost(EdgeIndex::new(edge)).unwrap_or(f64::INFINITY))
+            .collect();
+        let nodes_ahead: Vec<Option<NodeId>> = (0..edge_count)
+            .map(|edge| search.node_ahead(EdgeIndex::new(edge)))
+            .collect();
+
+        // The same transitions as in `directional_path`: on through the port ahead, or back
+        // along the edge where the train may reverse.
+        let mut successors = vec![Vec::new(); edge_count];
+        let mut predecessors = vec![Vec::new(); edge_count];
+        for edge in topology.edge_references() {
+            let mut next: Vec<(usize, f64)> = topology
+                .edges(edge.target())
+                .map(|next| (next.id().index(), costs[next.id().index()]))
+                .collect();
+            if let Some(reverse) = search.reversal(edge.id()) {
+                next.push((reverse.index(), costs[reverse.index()] + penalty));
+            }
+            next.retain(|(_, cost)| cost.is_finite());
+            for &(next_edge, cost) in &next {
+                predecessors[next_edge].push((edge.id().index(), cost));
+            }
+            successors[edge.id().index()] = next;
+        }
+
+        let mut departures = HashMap::new();
+        let mut goals: HashMap<NodeId, Vec<usize>> = HashMap::new();
+        for node in graph.physical_graph.graph.node_weights() {
+            if let Some(&(port_1, port_2)) = graph.topology_graph.id_to_index(node.id) {
+                let edges = topology
+                    .edges(port_1)
+                    .chain(topology.edges(port_2))
+                    .map(|edge| edge.id().index())
+                    .collect();
+                departures.insert(node.id, edges);
+            }
+        }
+        for (edge, node) in nodes_ahead.iter().enumerate() {
+            if let Some(node) = node {
+                goals.entry(*node).or_default().push(edge);
+            }
+        }
+
+        let mut arrivals = HashMap::new();
+        for edge in graph.physical_graph.graph.edge_weights() {
+            for (previous, node) in [(edge.source, edge.target), (edge.target, edge.source)] {
+                if let Some(arrival) = search.arrival_edge(previous, node) {
+                    arrivals.entry((previous, node)).or_insert(arrival.index());
+                }
+            }
+        }
+
+        Self {
+            fingerprint: fingerprint(graph, options, constraints),
+            costs,
+            nodes_ahead,
+            successors,
+            predecessors,
+            departures,
+            arrivals,
+            goals,
+            costs_to_go: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether the table was prepared for this graph, these `ReversalOptions` and
+    /// these `RoutingConstraints`, if any.
+    ///
+    /// The check hashes the edges of the graph, which takes time linear in the size of the
+    /// graph, but no search.
+    pub fn is_valid_for(
+        &self,
+        graph: &RailwayGraph,
+        options: &ReversalOptions,
+        constraints: Option<RoutingConstraints>,
+    ) -> bool {
+        self.fingerprint == fingerprint(graph, options, constraints)
+    }
+
+    /// Returns the next node on the direction-aware route to a target.
+    ///
+    /// The next node is `previous` if the train has to turn back at `current`.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The ID of the current node.
+    /// * `previous` - The ID of the node the train arrived from, or `None` if it may depart in
+    ///   either direction.
+    /// * `target` - The ID of the target node.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the next node, or `None` if `current` is the target or the target cannot be
+    /// reached.
+    pub fn next_node(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+    ) -> Option<NodeId> {
+        let (edge, _) = self.first_edge(current, previous, target)?;
+        self.nodes_ahead[edge]
+    }
+
+    /// Returns the cost of the direction-aware route to a target, which is the distance
+    /// travelled plus the reversal penalties in meters, as in `DirectionalRoute::cost`.
+    ///
+    /// The arguments are the same as for `next_node`. The cost is zero if `current` is the
+    /// target.
+    pub fn route_cost(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+    ) -> Option<f64> {
+        if current == target {
+            return self.departures.contains_key(&current).then_some(0.0);
+        }
+        self.first_edge(current, previous, target)
+            .map(|(_, cost)| cost)
+    }
+
+    /// Returns the first edge of the cheapest route and the cost of the route.
+    fn first_edge(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+    ) -> Option<(usize, f64)> {
+        if current == target || !self.departures.contains_key(&target) {
+            return None;
+        }
+        let departures = self.departures.get(&current)?;
+        let costs_to_go = self.costs_to_go(target);
+
+        let arrival = previous.and_then(|previous| self.arrivals.get(&(previous, current)));
+        let candidates: Vec<(usize, f64)> = match arrival {
+            Some(&arrival) => self.successors[arrival].clone(),
+            None => departures
+                .iter()
+                .map(|&edge| (edge, self.costs[edge]))
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .map(|(edge, cost)| (edge, cost + costs_to_go[edge]))
+            .filter(|(_, cost)| cost.is_finite())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Returns the cost to reach `target` from the end of every topology edge, computing and
+    /// caching them on the first query.
+    fn costs_to_go(&self, target: NodeId) -> Arc<Vec<f64>> {
+        if let Some(costs) = self.costs_to_go.read().unwrap().get(&target) {
+            return costs.clone();
+        }
+
+        let mut costs = vec![f64::INFINITY; self.costs.len()];
+        let mut heap = BinaryHeap::new();
+        for &goal in self.goals.get(&target).into_iter().flatten() {
+            costs[goal] = 0.0;
+            heap.push(State(0.0, goal));
+        }
+        while let Some(State(cost, edge)) = heap.pop() {
+            if cost > costs[edge] {
+                continue;
+            }
+            for &(previous, step) in &self.predecessors[edge] {
+                let previous_cost = cost + step;
+                if previous_cost < costs[previous] {
+                    costs[previous] = previous_cost;
+                    heap.push(State(previous_cost, previous));
+                }
+            }
+        }
+
+        let costs = Arc::new(costs);
+        let mut cache = self.costs_to_go.write().unwrap();
+        if cache.len() >= MAX_CACHED_TARGETS {
+            cache.clear();
+        }
+        cache.insert(target, costs.clone());
+        costs
+    }
+}
+
+/// Hashes everything the routing table depends on.
+fn fingerprint(
+    graph: &RailwayGraph,
+    options: &ReversalOptions,
+    constraints: Option<RoutingConstraints>,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for edge in graph.physical_graph.graph.edge_weights() {
+        (edge.id, edge.source, edge.target, edge.length.to_bits()).hash(&mut hasher);
+        if let Some(constraints) = constraints {
+            for direction in [TrackDirection::Forward, TrackDirection::Backward] {
+                constraints
+                    .cost(edge.id, direction, edge.length)
+                    .map(f64::to_bits)
+                    .hash(&mut hasher);
+            }
+        }
+    }
+    for edge in graph.topology_graph.graph.edge_references() {
+        (edge.source(), edge.target(), edge.weight().edge_id).hash(&mut hasher);
+    }
+    let mut reversal_points: Vec<NodeId> = options.reversal_points.iter().copied().collect();
+    reversal_points.sort_unstable();
+    reversal_points.hash(&mut hasher);
+    options.train_length.value.to_bits().hash(&mut hasher);
+    options.reversal_penalty.value.to_bits().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(PartialEq)]
+struct State(f64, usize);
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use uom::si::f64::Length;
+
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::{tests::siding_elements, DirectionalPathFinding};
+    use crate::railway_model::RailwayGraphExt;
+    use crate::tests::test_graph_vilbel;
+
+    #[test]
+    fn test_next_node_matches_directional_search() {
+        let graph = from_railway_elements(&siding_elements());
+        let length_11_12 = graph.railway_edge(11, 12).unwrap().length;
+        for options in [
+            ReversalOptions::default(),
+            ReversalOptions {
+                train_length: Length::new::<meter>(length_11_12 + 1.0),
+                ..Default::default()
+            },
+            ReversalOptions {
+                train_length: Length::new::<meter>(length_11_12 + 1.0),
+                reversal_points: HashSet::from([11]),
+                ..Default::default()
+            },
+        ] {
+            let table = RoutingTable::new(&graph, &options);
+            let nodes: Vec<NodeId> = graph
+                .physical_graph
+                .graph
+                .node_weights()
+                .map(|node| node.id)
+                .collect();
+            for &current in &nodes {
+                let neighbours = graph.get_edges_of_node(current).into_iter().map(|edge| {
+                    Some(if edge.source == current {
+                        edge.target
+                    } else {
+                        edge.source
+                    })
+                });
+                for previous in std::iter::once(None).chain(neighbours) {
+                    for &target in &nodes {
+                        assert_eq!(
+                            table.next_node(current, previous, target),
+                            graph.next_directional_node(current, previous, target, &options),
+                        );
+                        let route = graph.directional_path(current, previous, target, &options);
+                        let cost = table.route_cost(current, previous, target);
+                        assert_eq!(cost.is_some(), route.is_some());
+                        if let (Some(cost), Some(route)) = (cost, route) {
+                            approx::assert_relative_eq!(cost, route.cost, epsilon = 1e-6);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_route_cost_on_vilbel() {
+        let graph = test_graph_vilbel();
+        let options = ReversalOptions::default();
+        let table = RoutingTable::new(&graph, &options);
+        let nodes: Vec<NodeId> = graph
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| node.id)
+            .collect();
+
+        for &current in nodes.iter().step_by(17) {
+            for &target in nodes.iter().step_by(23) {
+                let route = graph.directional_path(current, None, target, &options);
+                match (table.route_cost(current, None, target), route) {
+                    (Some(cost), Some(route)) => {
+                        approx::assert_relative_eq!(cost, route.cost, epsilon = 1e-6)
+                    }
+                    (cost, route) => assert_eq!(cost, route.map(|route| route.cost)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_valid_for() {
+        let mut graph = from_railway_elements(&siding_elements());
+        let options = ReversalOptions::default();
+        let table = RoutingTable::new(&graph, &options);
+        assert!(table.is_valid_for(&graph, &options, None));
+
+        let other_options = ReversalOptions {
+            reversal_points: HashSet::from([11]),
+            ..Default::default()
+        };
+        assert!(!table.is_valid_for(&graph, &other_options, None));
+
+        let edge = graph
+            .physical_graph
+            .graph
+            .edge_weights_mut()
+            .next()
+            .unwrap();
+        edge.length += 1.0;
+        assert!(!table.is_valid_for(&graph, &options, None));
+    }
+}
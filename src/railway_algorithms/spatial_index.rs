@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+
+use geo::Coord;
+use uom::si::{f64::Length, length::meter};
+
+use super::railway_edge_algos::project_on_path;
+use crate::{
+    prelude::{RailwayEdge, RailwayGraph},
+    types::EdgeId,
+};
+
+/// The side length of a grid cell in degrees, about 1 km in latitude.
+const CELL_SIZE: f64 = 0.01;
+
+/// The radius of the first search for the closest edge, doubled until an edge is found.
+const INITIAL_SEARCH_RADIUS: f64 = 100.0;
+
+/// Meters per degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// The closest point of an edge to a coordinate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeProjection {
+    /// The ID of the edge.
+    pub edge_id: EdgeId,
+    /// The distance along the edge's geometry from its source node to the closest point.
+    pub offset: Length,
+    /// The closest point on the edge.
+    pub point: Coord<f64>,
+    /// The distance between the coordinate and the closest point.
+    pub distance: Length,
+}
+
+/// A grid index over the geometries of the edges of a `RailwayGraph` to find the edges near a
+/// coordinate without projecting onto every edge of the network.
+///
+/// The index holds copies of the edges, so it has to be rebuilt after the graph is changed.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeSpatialIndex {
+    edges: Vec<RailwayEdge>,
+    edge_positions: HashMap<EdgeId, usize>,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    bounds: Option<(Coord<f64>, Coord<f64>)>,
+}
+
+impl EdgeSpatialIndex {
+    /// Builds the index over all edges of a graph.
+    pub fn new(graph: &RailwayGraph) -> Self {
+        let mut index = Self::default();
+        for edge in graph.physical_graph.graph.edge_weights() {
+            let position = index.edges.len();
+            index.edge_positions.insert(edge.id, position);
+            if let Some((min, max)) = bounds(edge.path.0.iter().copied()) {
+                for cell in cells_between(min, max) {
+                    index.cells.entry(cell).or_default().push(position);
+                }
+                index.bounds = bounds(
+                    index
+                        .bounds
+                        .into_iter()
+                        .flat_map(|(min_all, max_all)| [min_all, max_all])
+                        .chain([min, max]),
+                );
+            }
+            index.edges.push(edge.clone());
+        }
+        index
+    }
+
+    /// Returns the indexed edge with the given ID.
+    pub fn edge(&self, edge_id: EdgeId) -> Option<&RailwayEdge> {
+        self.edge_positions
+            .get(&edge_id)
+            .map(|&position| &self.edges[position])
+    }
+
+    /// Finds the edges passing within a radius of a coordinate.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The coordinate to search around.
+    /// * `radius` - The maximum distance of an edge from `location`.
+    ///
+    /// # Returns
+    ///
+    /// The closest point of every edge within the radius, the closest edge first.
+    pub fn edges_within(&self, location: Coord<f64>, radius: Length) -> Vec<EdgeProjection> {
+        let Some((min_all, max_all)) = self.bounds else {
+            return Vec::new();
+        };
+        let (min, max) = search_bounds(location, radius);
+        // Only the cells within the extent of the edges can hold edges.
+        let min = Coord {
+            x: min.x.max(min_all.x),
+            y: min.y.max(min_all.y),
+        };
+        let max = Coord {
+            x: max.x.min(max_all.x),
+            y: max.y.min(max_all.y),
+        };
+        if !(min.x <= max.x && min.y <= max.y) {
+            return Vec::new();
+        }
+
+        let mut seen = HashSet::new();
+        let mut projections: Vec<EdgeProjection> = cells_between(min, max)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(|&&position| seen.insert(position))
+            .filter_map(|&position| {
+                let edge = &self.edges[position];
+                let (offset, point, distance) = project_on_path(&edge.path, location)?;
+                (distance <= radius).then_some(EdgeProjection {
+                    edge_id: edge.id,
+                    offset,
+                    point,
+                    distance,
+                })
+            })
+            .collect();
+        projections.sort_by(|a, b| a.distance.value.total_cmp(&b.distance.value));
+        projections
+    }
+
+    /// Finds the edge closest to a coordinate.
+    ///
+    /// The search radius starts at 100 m and is doubled until an edge is found, so only the
+    /// edges near `location` are projected onto. Once the search covers all edges, the closest
+    /// of all edges is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - A `Coord<f64>` near the network.
+    ///
+    /// # Returns
+    ///
+    /// The closest point of the closest edge, or `None` if the index has no edges.
+    pub fn closest_edge(&self, location: Coord<f64>) -> Option<EdgeProjection> {
+        let (min_all, max_all) = self.bounds?;
+        if !(location.x.is_finite() && location.y.is_finite()) {
+            return None;
+        }
+        let mut radius = Length::new::<meter>(INITIAL_SEARCH_RADIUS);
+        loop {
+            if let Some(&closest) = self.edges_within(location, radius).first() {
+                return Some(closest);
+            }
+            let (min, max) = search_bounds(location, radius);
+            if min.x <= min_all.x && min.y <= min_all.y && max.x >= max_all.x && max.y >= max_all.y
+            {
+                return self
+                    .edges
+                    .iter()
+                    .filter_map(|edge| {
+                        let (offset, point, distance) = project_on_path(&edge.path, location)?;
+                        Some(EdgeProjection {
+                            edge_id: edge.id,
+                            offset,
+                            point,
+                            distance,
+                        })
+                    })
+                    .min_by(|a, b| a.distance.value.total_cmp(&b.distance.value));
+            }
+            radius *= 2.0;
+        }
+    }
+}
+
+/// Returns the corners of the box around a coordinate that contains the circle with the given
+/// radius.
+fn search_bounds(location: Coord<f64>, radius: Length) -> (Coord<f64>, Coord<f64>) {
+    let radius_meters = radius.get::<meter>().max(0.0);
+    let latitude_degrees = radius_meters / METERS_PER_DEGREE;
+    let longitude_degrees =
+        radius_meters / (METERS_PER_DEGREE * location.y.to_radians().cos().max(0.01));
+    (
+        Coord {
+            x: location.x - longitude_degrees,
+            y: location.y - latitude_degrees,
+        },
+        Coord {
+            x: location.x + longitude_degrees,
+            y: location.y + latitude_degrees,
+        },
+    )
+}
+
+fn bounds(coords: impl Iterator<Item = Coord<f64>>) -> Option<(Coord<f64>, Coord<f64>)> {
+    coords.fold(None, |bounds, coord| match bounds {
+        None => Some((coord, coord)),
+        Some((min, max)) => Some((
+            Coord {
+                x: min.x.min(coord.x),
+                y: min.y.min(coord.y),
+            },
+            Coord {
+                x: max.x.max(coord.x),
+                y: max.y.max(coord.y),
+            },
+        )),
+    })
+}
+
+fn cell_of(coord: Coord<f64>) -> (i64, i64) {
+    (
+        (coord.x / CELL_SIZE).floor() as i64,
+        (coord.y / CELL_SIZE).floor() as i64,
+    )
+}
+
+fn cells_between(min: Coord<f64>, max: Coord<f64>) -> impl Iterator<Item = (i64, i64)> {
+    let (min_x, min_y) = cell_of(min);
+    let (max_x, max_y) = cell_of(max);
+    (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::siding_elements;
+    use crate::railway_algorithms::LinearReferencing;
+    use crate::tests::test_graph_vilbel;
+
+    #[test]
+    fn test_edges_within() {
+        let graph = from_railway_elements(&siding_elements());
+        let index = EdgeSpatialIndex::new(&graph);
+
+        // 0.0003 degrees of latitude are about 33 m north of edge 20.
+        let location = Coord {
+            x: 8.005,
+            y: 50.0003,
+        };
+        let found = index.edges_within(location, Length::new::<meter>(50.0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].edge_id, 20);
+        assert!((found[0].distance.get::<meter>() - 33.4).abs() < 0.5);
+        assert!((found[0].point.y - 50.0).abs() < 1e-9);
+        assert!(index
+            .edges_within(location, Length::new::<meter>(20.0))
+            .is_empty());
+
+        let near_switch = index.edges_within(Coord { x: 8.02, y: 50.0 }, Length::new::<meter>(1.0));
+        let mut ids: Vec<EdgeId> = near_switch.iter().map(|found| found.edge_id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![21, 22, 23]);
+        assert_eq!(index.edge(22).unwrap().id, 22);
+        assert!(index.edge(99).is_none());
+    }
+
+    #[test]
+    fn test_closest_edge_matches_full_scan() {
+        let graph = test_graph_vilbel();
+        let index = EdgeSpatialIndex::new(&graph);
+
+        for node in graph.physical_graph.graph.node_weights().step_by(13) {
+            let location = Coord {
+                x: node.location.x + 0.0002,
+                y: node.location.y - 0.0001,
+            };
+            let closest = index.edges_within(location, Length::new::<meter>(200.0));
+            let expected = graph.snap_to_edge(location).unwrap();
+            assert!((closest[0].distance - expected.distance).abs() < Length::new::<meter>(1e-6));
+            let found = index.closest_edge(location).unwrap();
+            assert!((found.distance - expected.distance).abs() < Length::new::<meter>(1e-6));
+        }
+
+        // About 50 km away from the network, beyond the first search radii.
+        let far = Coord { x: 8.7, y: 50.6 };
+        let expected = graph.snap_to_edge(far).unwrap();
+        let found = index.closest_edge(far).unwrap();
+        assert!((found.distance - expected.distance).abs() < Length::new::<meter>(1e-6));
+
+        assert!(index
+            .closest_edge(Coord {
+                x: f64::NAN,
+                y: 50.0
+            })
+            .is_none());
+        assert!(EdgeSpatialIndex::default()
+            .closest_edge(Coord { x: 8.0, y: 50.0 })
+            .is_none());
+    }
+}
@@ -0,0 +1,377 @@
+use uom::si::{
+    f64::{Length, Velocity},
+    length::meter,
+    velocity::{kilometer_per_hour, mile_per_hour},
+};
+
+use super::RailwayEdgeAlgos;
+use crate::{
+    prelude::{
+        CantConfig, RailwayCurvature, RailwayEdge, RailwayGraph, RailwayGraphExt,
+        RailwaySpeedLimits, RailwayTags, SpeedProfile, SpeedSection, TrackDirection, TrackPosition,
+    },
+    types::NodeId,
+};
+
+/// A change of the speed limit ahead of a position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedLimitAhead {
+    /// The distance from the position to where the limit starts.
+    pub distance: Length,
+    /// The speed limit from there on, or `None` if no limit is known.
+    pub limit: Option<Velocity>,
+}
+
+/// Speed limit profiles of railway graphs.
+pub trait RailwayGraphSpeedLimits {
+    /// Builds the speed profile of every edge from its tags and curve radii.
+    ///
+    /// The limit of an edge is taken from `maxspeed:forward` and `maxspeed:backward`, falling
+    /// back to `maxspeed`. If curvature profiles are given, the permissible speed of each curved
+    /// vertex applies halfway towards its neighbouring vertices and lowers the tagged limit
+    /// there. Edges without any limit are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The OpenStreetMap tags of the graph.
+    /// * `curvature` - The curvature profiles of the edges, if curve limits should be derived.
+    /// * `cant` - The track parameters used to derive the curve limits.
+    ///
+    /// # Returns
+    ///
+    /// A `RailwaySpeedLimits` with the speed profiles of the edges.
+    fn speed_limits(
+        &self,
+        tags: &RailwayTags,
+        curvature: Option<&RailwayCurvature>,
+        cant: &CantConfig,
+    ) -> RailwaySpeedLimits;
+
+    /// Looks up the speed limits ahead of a position.
+    ///
+    /// Whenever the end of an edge is reached, `next_node` is called with the node reached and
+    /// returns the node to continue towards, as in `LinearReferencing::move_along`.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The speed profiles of the edges.
+    /// * `position` - The position to look ahead from.
+    /// * `distance` - How far to look ahead.
+    /// * `next_node` - Chooses the next node at each node reached.
+    ///
+    /// # Returns
+    ///
+    /// The limit at the position followed by every change of the limit within `distance`,
+    /// ordered by distance. Empty if the edge of the position does not exist.
+    fn speed_limits_ahead(
+        &self,
+        limits: &RailwaySpeedLimits,
+        position: &TrackPosition,
+        distance: Length,
+        next_node: impl FnMut(NodeId) -> Option<NodeId>,
+    ) -> Vec<SpeedLimitAhead>;
+}
+
+impl RailwayGraphSpeedLimits for RailwayGraph {
+    fn speed_limits(
+        &self,
+        tags: &RailwayTags,
+        curvature: Option<&RailwayCurvature>,
+        cant: &CantConfig,
+    ) -> RailwaySpeedLimits {
+        let edges = self
+            .physical_graph
+            .graph
+            .edge_weights()
+            .filter_map(|edge| {
+                let profile = speed_profile(edge, tags, curvature, cant);
+                (!profile.forward.is_empty() || !profile.backward.is_empty())
+                    .then_some((edge.id, profile))
+            })
+            .collect();
+        RailwaySpeedLimits { edges }
+    }
+
+    fn speed_limits_ahead(
+        &self,
+        limits: &RailwaySpeedLimits,
+        position: &TrackPosition,
+        distance: Length,
+        mut next_node: impl FnMut(NodeId) -> Option<NodeId>,
+    ) -> Vec<SpeedLimitAhead> {
+        let Some(mut edge) = self.get_edge_by_id(position.edge_id) else {
+            return Vec::new();
+        };
+        let distance = distance.get::<meter>();
+        let mut position = *position;
+        let mut travelled = 0.0;
+        let mut ahead: Vec<SpeedLimitAhead> = Vec::new();
+
+        loop {
+            let length = edge.path_length().get::<meter>();
+            let start = match position.direction {
+                TrackDirection::Forward => position.offset.get::<meter>(),
+                TrackDirection::Backward => length - position.offset.get::<meter>(),
+            };
+            for (section_start, section_end, limit) in
+                travel_sections(limits, &edge, position.direction, length)
+            {
+                if section_end <= start && section_end < length {
+                    continue;
+                }
+                let section_distance = travelled + (section_start - start).max(0.0);
+                if section_distance > distance {
+                    return ahead;
+                }
+                let limit = limit.map(Velocity::new::<kilometer_per_hour>);
+                if ahead.last().is_none_or(|last| last.limit != limit) {
+                    ahead.push(SpeedLimitAhead {
+                        distance: Length::new::<meter>(section_distance),
+                        limit,
+                    });
+                }
+            }
+            travelled += length - start;
+            if travelled > distance {
+                break;
+            }
+
+            let node_id = edge.node_ahead(position.direction);
+            let next_edge = next_node(node_id)
+                .and_then(|next_node_id| self.railway_edge(node_id, next_node_id))
+                .cloned();
+            match next_edge.as_ref().and_then(|e| e.start_position(node_id)) {
+                Some(next_position) => {
+                    edge = next_edge.unwrap();
+                    position = next_position;
+                }
+                None => break,
+            }
+        }
+        ahead
+    }
+}
+
+/// Returns the sections of an edge in its direction of travel as `(start, end, limit)`, measured
+/// in meters from the node the edge is entered at. Gaps between sections have no limit.
+fn travel_sections(
+    limits: &RailwaySpeedLimits,
+    edge: &RailwayEdge,
+    direction: TrackDirection,
+    length: f64,
+) -> Vec<(f64, f64, Option<f64>)> {
+    let mut sections: Vec<(f64, f64, f64)> = limits
+        .profile(edge.id)
+        .map(|profile| profile.sections(direction))
+        .unwrap_or_default()
+        .iter()
+        .map(|section| match direction {
+            TrackDirection::Forward => (section.start, section.end, section.limit),
+            TrackDirection::Backward => {
+                (length - section.end, length - section.start, section.limit)
+            }
+        })
+        .collect();
+    sections.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut result = Vec::new();
+    let mut covered = 0.0;
+    for (start, end, limit) in sections {
+        if start > covered {
+            result.push((covered, start, None));
+        }
+        result.push((start, end, Some(limit)));
+        covered = end.max(covered);
+    }
+    if covered < length || result.is_empty() {
+        result.push((covered, length, None));
+    }
+    result
+}
+
+/// Builds the speed profile of a single edge.
+fn speed_profile(
+    edge: &RailwayEdge,
+    tags: &RailwayTags,
+    curvature: Option<&RailwayCurvature>,
+    cant: &CantConfig,
+) -> SpeedProfile {
+    let length = edge.path_length().get::<meter>();
+    let tag_limit = |key: &str| {
+        tags.edge_tag(edge.id, key)
+            .or_else(|| tags.edge_tag(edge.id, "maxspeed"))
+            .and_then(parse_maxspeed)
+            .map(|speed| speed.get::<kilometer_per_hour>())
+    };
+
+    // Each curved vertex limits the track halfway towards its neighbouring vertices.
+    let samples = curvature
+        .and_then(|curvature| curvature.profile(edge.id))
+        .map(|profile| profile.samples.as_slice())
+        .unwrap_or_default();
+    let mut breakpoints = vec![0.0];
+    let mut curve_limits = Vec::new();
+    for (index, sample) in samples.iter().enumerate() {
+        let previous = index
+            .checked_sub(1)
+            .map_or(0.0, |i| (samples[i].offset + sample.offset) / 2.0);
+        let next = samples
+            .get(index + 1)
+            .map_or(length, |next| (sample.offset + next.offset) / 2.0);
+        breakpoints.push(previous);
+        if let Some(radius) = sample.radius() {
+            let speed = cant.permissible_speed(Length::new::<meter>(radius));
+            curve_limits.push((previous, next, speed.get::<kilometer_per_hour>()));
+        }
+    }
+    breakpoints.push(length);
+    breakpoints.sort_by(|a, b| a.total_cmp(b));
+    breakpoints.dedup();
+
+    let sections = |tag_limit: Option<f64>| {
+        let mut sections: Vec<SpeedSection> = Vec::new();
+        for pair in breakpoints.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let middle = (start + end) / 2.0;
+            let curve_limit = curve_limits
+                .iter()
+                .filter(|(from, to, _)| (*from..=*to).contains(&middle))
+                .map(|(_, _, limit)| *limit)
+                .min_by(|a, b| a.total_cmp(b));
+            let limit = match (tag_limit, curve_limit) {
+                (Some(a), Some(b)) => a.min(b),
+                (a, b) => match a.or(b) {
+                    Some(limit) => limit,
+                    None => continue,
+                },
+            };
+            match sections.last_mut() {
+                Some(last) if last.end == start && last.limit == limit => last.end = end,
+                _ => sections.push(SpeedSection { start, end, limit }),
+            }
+        }
+        sections
+    };
+
+    SpeedProfile {
+        forward: sections(tag_limit("maxspeed:forward")),
+        backward: sections(tag_limit("maxspeed:backward")),
+    }
+}
+
+/// Parses the value of an OpenStreetMap `maxspeed` tag.
+///
+/// Plain numbers are in km/h, values with a `mph` suffix in miles per hour. For lists such as
+/// `100;80` the first value is used. Non-numeric values such as `none` or `signals` and values
+/// that are not positive return `None`.
+///
+/// # Arguments
+///
+/// * `value` - The value of the tag.
+///
+/// # Returns
+///
+/// The speed limit, or `None` if the value is not a positive numeric limit.
+pub fn parse_maxspeed(value: &str) -> Option<Velocity> {
+    let value = value.split(';').next()?.trim();
+    if let Some(mph) = value.strip_suffix("mph") {
+        let speed: f64 = mph.trim().parse().ok()?;
+        return (speed > 0.0).then(|| Velocity::new::<mile_per_hour>(speed));
+    }
+    let value = value
+        .strip_suffix("km/h")
+        .or_else(|| value.strip_suffix("kmh"))
+        .unwrap_or(value);
+    let speed: f64 = value.trim().parse().ok()?;
+    (speed > 0.0).then(|| Velocity::new::<kilometer_per_hour>(speed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{CurvatureProfile, CurvatureSample, Tags};
+    use crate::railway_algorithms::tests::test_elements;
+    use approx::assert_relative_eq;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_maxspeed() {
+        let kmh = |value| parse_maxspeed(value).map(|speed| speed.get::<kilometer_per_hour>());
+        assert_relative_eq!(kmh("120").unwrap(), 120.0);
+        assert_relative_eq!(kmh("80 km/h").unwrap(), 80.0);
+        assert_relative_eq!(kmh("100;80").unwrap(), 100.0);
+        assert_relative_eq!(kmh("50 mph").unwrap(), 80.4672, epsilon = 1e-4);
+        assert_eq!(kmh("none"), None);
+        assert_eq!(kmh("0"), None);
+        assert_eq!(kmh("0 mph"), None);
+        assert_eq!(kmh("-20 mph"), None);
+        assert_eq!(kmh("signals"), None);
+    }
+
+    #[test]
+    fn test_speed_limits() {
+        let graph = from_railway_elements(&test_elements());
+        let mut tags = RailwayTags::default();
+        tags.edges.insert(
+            4,
+            Tags::from([
+                ("maxspeed".to_string(), "120".to_string()),
+                ("maxspeed:backward".to_string(), "100".to_string()),
+            ]),
+        );
+        let length = graph
+            .get_edge_by_id(5)
+            .unwrap()
+            .path_length()
+            .get::<meter>();
+        let curvature = RailwayCurvature {
+            edges: HashMap::from([(
+                5,
+                CurvatureProfile {
+                    samples: vec![CurvatureSample {
+                        offset: length / 2.0,
+                        curvature: 1.0 / 300.0,
+                    }],
+                },
+            )]),
+        };
+        let limits = graph.speed_limits(&tags, Some(&curvature), &CantConfig::default());
+
+        let edge_4 = limits.profile(4).unwrap();
+        assert_eq!(edge_4.forward.len(), 1);
+        assert_relative_eq!(edge_4.forward[0].limit, 120.0);
+        assert_relative_eq!(edge_4.backward[0].limit, 100.0);
+        let edge_5 = limits.profile(5).unwrap();
+        assert_eq!(edge_5.forward.len(), 1);
+        assert_relative_eq!(
+            edge_5.forward[0].limit,
+            (300.0f64 * 250.0 / 11.8).sqrt(),
+            epsilon = 0.5
+        );
+
+        // Travelling from node 1 towards node 3 along edges 4 and 5.
+        let start = graph.get_edge_by_id(4).unwrap().start_position(1).unwrap();
+        let length_4 = graph.get_edge_by_id(4).unwrap().path_length();
+        let ahead = graph.speed_limits_ahead(
+            &limits,
+            &start,
+            Length::new::<meter>(100_000.0),
+            |node_id| (node_id == 2).then_some(3),
+        );
+        assert_eq!(ahead.len(), 2);
+        assert_relative_eq!(ahead[0].distance.get::<meter>(), 0.0);
+        assert_relative_eq!(ahead[0].limit.unwrap().get::<kilometer_per_hour>(), 120.0);
+        assert_relative_eq!(
+            ahead[1].distance.get::<meter>(),
+            length_4.get::<meter>(),
+            epsilon = 1e-6
+        );
+        assert!(ahead[1].limit.unwrap() < ahead[0].limit.unwrap());
+
+        let short = graph.speed_limits_ahead(&limits, &start, length_4 / 2.0, |node_id| {
+            (node_id == 2).then_some(3)
+        });
+        assert_eq!(short.len(), 1);
+    }
+}
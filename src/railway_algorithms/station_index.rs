@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uom::si::{f64::Length, length::meter};
+
+use super::spatial_index::EdgeSpatialIndex;
+use crate::{
+    prelude::{RailwayGraph, RailwayNode, RailwayTags},
+    types::NodeId,
+};
+
+/// The tags whose values can be searched for, e.g. `Frankfurt (Main) Hauptbahnhof` or `FF`.
+const NAME_KEYS: [&str; 6] = [
+    "name",
+    "short_name",
+    "official_name",
+    "ref",
+    "railway:ref",
+    "uic_ref",
+];
+
+/// Abbreviations expanded before matching, so that `Frankfurt Hbf` finds the `Hauptbahnhof`.
+const ABBREVIATIONS: [(&str, &str); 5] = [
+    ("hbf", "hauptbahnhof"),
+    ("bf", "bahnhof"),
+    ("bhf", "bahnhof"),
+    ("pbf", "personenbahnhof"),
+    ("gbf", "guterbahnhof"),
+];
+
+/// The maximum distance between a station placed beside the track and the track it is snapped
+/// to.
+const STATION_SNAP_RADIUS: f64 = 1000.0;
+
+/// A node found by a `StationIndex` search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StationMatch {
+    /// The ID of the node carrying the name.
+    pub node_id: NodeId,
+    /// The ID of the node on the track to route from or to: the node itself if it is a node of
+    /// the graph with edges, otherwise the closest end of the closest edge. `None` if there is
+    /// no track nearby.
+    pub track_node_id: Option<NodeId>,
+    /// The matched name or ref.
+    pub name: String,
+    /// How well the name matches the query, between 0 and 1.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct StationEntry {
+    node_id: NodeId,
+    track_node_id: Option<NodeId>,
+    name: String,
+    tokens: Vec<String>,
+    rank: u8,
+}
+
+/// A search index over the names and refs of the nodes of a `RailwayGraph`, so that users can
+/// pick nodes by typing `Frankfurt Hbf` instead of a node id.
+///
+/// Queries match whole words, word prefixes and, with a few typos, similar words. Case,
+/// diacritics and the common abbreviations of station names are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StationIndex {
+    entries: Vec<StationEntry>,
+}
+
+impl StationIndex {
+    /// Builds the index over the `name`, `short_name`, `official_name`, `ref`, `railway:ref`
+    /// and `uic_ref` tags of the nodes of a graph and of the stations.
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The railway graph to route on.
+    /// * `tags` - The tags of the nodes of the graph and of the stations.
+    /// * `stations` - The locations of the stations that are not part of the graph, e.g.
+    ///   imported with `OverpassImporter::import_nodes` from the response of
+    ///   `fetch_stations_by_area_name`.
+    ///
+    /// Stations outside the graph and nodes without edges are snapped to the closest edge
+    /// within 1 km.
+    pub fn new(graph: &RailwayGraph, tags: &RailwayTags, stations: &[RailwayNode]) -> Self {
+        let station_locations: HashMap<NodeId, _> = stations
+            .iter()
+            .map(|station| (station.id, station.location))
+            .collect();
+        let mut spatial_index: Option<EdgeSpatialIndex> = None;
+        let mut entries = Vec::new();
+        let mut node_ids: Vec<&NodeId> = tags.nodes.keys().collect();
+        node_ids.sort_unstable();
+        for &node_id in node_ids {
+            let node_tags = &tags.nodes[&node_id];
+            let node_index = graph.physical_graph.id_to_index(node_id).copied();
+            let Some(location) = node_index
+                .map(|node_index| graph.physical_graph.graph[node_index].location)
+                .or_else(|| station_locations.get(&node_id).copied())
+            else {
+                continue;
+            };
+            let names: Vec<&String> = NAME_KEYS
+                .iter()
+                .filter_map(|key| node_tags.get(*key))
+                .collect();
+            if names.is_empty() {
+                continue;
+            }
+
+            let track_node_id = if node_index.is_some_and(|node_index| {
+                graph
+                    .physical_graph
+                    .graph
+                    .edges(node_index)
+                    .next()
+                    .is_some()
+            }) {
+                Some(node_id)
+            } else {
+                let spatial_index =
+                    spatial_index.get_or_insert_with(|| EdgeSpatialIndex::new(graph));
+                spatial_index
+                    .edges_within(location, Length::new::<meter>(STATION_SNAP_RADIUS))
+                    .first()
+                    .and_then(|closest| {
+                        let edge = spatial_index.edge(closest.edge_id)?;
+                        Some(if closest.offset.get::<meter>() < edge.length / 2.0 {
+                            edge.source
+                        } else {
+                            edge.target
+                        })
+                    })
+            };
+            let rank = match node_tags.get("railway").map(String::as_str) {
+                Some("station") => 0,
+                Some("halt") => 1,
+                Some("stop") => 2,
+                _ => 3,
+            };
+            for name in names {
+                entries.push(StationEntry {
+                    node_id,
+                    track_node_id,
+                    name: name.clone(),
+                    tokens: tokenize(name),
+                    rank,
+                });
+            }
+        }
+        Self { entries }
+    }
+
+    /// Returns the number of names in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no node of the graph has a name.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Searches the nodes whose names match a query.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The name or the beginning of the name to search for, e.g. `Frankfurt Hbf`.
+    /// * `limit` - The maximum number of matches.
+    ///
+    /// # Returns
+    ///
+    /// The best match of each node, the best match first. Stations come before halts and stops
+    /// with equally good names.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<StationMatch> {
+        let query = tokenize(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(&StationEntry, f64)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((entry, score(&query, &entry.tokens)?)))
+            .collect();
+        matches.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then(a.rank.cmp(&b.rank))
+                .then(a.name.len().cmp(&b.name.len()))
+                .then(a.node_id.cmp(&b.node_id))
+        });
+
+        let mut seen = HashSet::new();
+        matches
+            .into_iter()
+            .filter(|(entry, _)| seen.insert(entry.node_id))
+            .take(limit)
+            .map(|(entry, score)| StationMatch {
+                node_id: entry.node_id,
+                track_node_id: entry.track_node_id,
+                name: entry.name.clone(),
+                score,
+            })
+            .collect()
+    }
+
+    /// Returns the track node of the best match of a query, to route from or to.
+    pub fn find_node(&self, query: &str) -> Option<NodeId> {
+        self.search(query, 1)
+            .into_iter()
+            .next()
+            .and_then(|found| found.track_node_id)
+    }
+}
+
+/// Splits a name into lowercase words without diacritics and with abbreviations expanded.
+fn tokenize(name: &str) -> Vec<String> {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ä' | 'á' | 'à' | 'â' | 'å' => folded.push('a'),
+            'ö' | 'ó' | 'ò' | 'ô' | 'ø' => folded.push('o'),
+            'ü' | 'ú' | 'ù' | 'û' => folded.push('u'),
+            'é' | 'è' | 'ê' | 'ë' => folded.push('e'),
+            'í' | 'ì' | 'î' | 'ï' => folded.push('i'),
+            'ç' => folded.push('c'),
+            'ß' => folded.push_str("ss"),
+            c if c.is_alphanumeric() => folded.push(c),
+            _ => folded.push(' '),
+        }
+    }
+    folded
+        .split_whitespace()
+        .map(|word| {
+            ABBREVIATIONS
+                .iter()
+                .find(|(abbreviation, _)| *abbreviation == word)
+                .map_or(word, |(_, expansion)| expansion)
+                .to_string()
+        })
+        .collect()
+}
+
+/// Scores how well the words of a name match the words of a query, `None` if a query word
+/// matches no word of the name.
+///
+/// Whole words score higher than prefixes and prefixes higher than similar words. Names with
+/// fewer words beyond the query score slightly higher.
+fn score(query: &[String], name: &[String]) -> Option<f64> {
+    let mut total = 0.0;
+    for word in query {
+        total += name
+            .iter()
+            .filter_map(|candidate| word_score(word, candidate))
+            .max_by(f64::total_cmp)?;
+    }
+    let coverage = (query.len() as f64 / name.len() as f64).min(1.0);
+    Some(0.9 * total / query.len() as f64 + 0.1 * coverage)
+}
+
+fn word_score(word: &str, candidate: &str) -> Option<f64> {
+    if word == candidate {
+        return Some(1.0);
+    }
+    let word_length = word.chars().count();
+    let candidate_length = candidate.chars().count();
+    if candidate.starts_with(word) {
+        return Some(0.8 + 0.1 * word_length as f64 / candidate_length as f64);
+    }
+    let max_edits = match word_length {
+        0..=3 => return None,
+        4..=6 => 1,
+        _ => 2,
+    };
+    let prefix: String = candidate.chars().take(word_length).collect();
+    [
+        (levenshtein(word, candidate), 0.7),
+        (levenshtein(word, &prefix), 0.6),
+    ]
+    .into_iter()
+    .filter(|&(edits, _)| edits <= max_edits)
+    .map(|(edits, weight): (usize, f64)| weight * (1.0 - edits as f64 / word_length as f64))
+    .max_by(f64::total_cmp)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1)
+                .min(row[j] + 1)
+                .min(diagonal + usize::from(a_char != b_char));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::{
+        from_railway_elements, tags_from_railway_elements, RailwayElement,
+    };
+    use crate::railway_algorithms::tests::loop_elements;
+
+    fn index() -> (RailwayGraph, StationIndex) {
+        let mut elements = loop_elements();
+        let mut name = |id: i64, pairs: &[(&str, &str)]| {
+            let element = elements
+                .iter_mut()
+                .find(|element| element.id == id)
+                .unwrap();
+            element.tags = Some(
+                pairs
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            );
+        };
+        name(
+            1,
+            &[
+                ("railway", "station"),
+                ("name", "Frankfurt (Main) Hauptbahnhof"),
+                ("railway:ref", "FF"),
+            ],
+        );
+        name(2, &[("railway", "halt"), ("name", "Frankfurt-Süd")]);
+        name(3, &[("railway", "switch"), ("ref", "W 42")]);
+        let graph = from_railway_elements(&elements);
+
+        // A station mapped beside the track, 20 m north of node 4, fetched apart from the
+        // graph.
+        let mut station = RailwayElement::new_with_id(5);
+        station.lat = Some(50.00018);
+        station.lon = Some(7.99);
+        station.tags = Some(
+            [
+                ("railway".to_string(), "station".to_string()),
+                ("name".to_string(), "Offenbach Hbf".to_string()),
+            ]
+            .into(),
+        );
+        elements.push(station);
+        let stations = [RailwayNode {
+            id: 5,
+            location: geo::coord! { x: 7.99, y: 50.00018 },
+        }];
+
+        let index = StationIndex::new(&graph, &tags_from_railway_elements(&elements), &stations);
+        (graph, index)
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Frankfurt (Main) Hbf"),
+            vec!["frankfurt", "main", "hauptbahnhof"]
+        );
+        assert_eq!(tokenize("Frankfurt-Süd"), vec!["frankfurt", "sud"]);
+        assert_eq!(tokenize("Gießen"), vec!["giessen"]);
+        assert_eq!(levenshtein("frankfrt", "frankfurt"), 1);
+    }
+
+    #[test]
+    fn test_search_stations() {
+        let (_, index) = index();
+        assert_eq!(index.len(), 5);
+
+        let found = index.search("Frankfurt Hbf", 5);
+        assert_eq!(found[0].node_id, 1);
+        assert_eq!(found[0].name, "Frankfurt (Main) Hauptbahnhof");
+        assert_eq!(found.len(), 1);
+
+        // The shorter name ranks first for a prefix of both names.
+        let found = index.search("frankf", 5);
+        assert_eq!(
+            found.iter().map(|found| found.node_id).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert!(found[0].score < 1.0);
+
+        assert_eq!(index.search("FF", 5)[0].node_id, 1);
+        assert_eq!(index.search("frankfrt sud", 5)[0].node_id, 2);
+        assert_eq!(index.search("W 42", 5)[0].node_id, 3);
+        assert!(index.search("Berlin", 5).is_empty());
+        assert!(index.search("  ", 5).is_empty());
+    }
+
+    #[test]
+    fn test_find_node_snaps_to_track() {
+        let (graph, index) = index();
+        assert!(graph.physical_graph.id_to_index(5).is_none());
+        let found = &index.search("Offenbach", 1)[0];
+        assert_eq!(found.node_id, 5);
+        assert_eq!(found.track_node_id, Some(4));
+        assert_eq!(index.find_node("Offenbach Hbf"), Some(4));
+        assert_eq!(index.find_node("Frankfurt Hbf"), Some(1));
+
+        let empty = StationIndex::new(&graph, &RailwayTags::default(), &[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.find_node("Frankfurt"), None);
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    rc::Rc,
+};
+
+use petgraph::{graph::EdgeIndex, visit::EdgeRef};
+use serde::{Deserialize, Serialize};
+use uom::si::{
+    acceleration::meter_per_second_squared,
+    f64::{Acceleration, Velocity},
+    length::meter,
+    velocity::{kilometer_per_hour, meter_per_second},
+};
+
+use super::RailwayEdgeAlgos;
+use crate::{
+    prelude::{RailwayGraph, RailwayGraphExt, RailwaySpeedLimits, TrackDirection},
+    types::{EdgeId, NodeId},
+};
+
+/// The running characteristics of a train used to estimate travel times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainProfile {
+    /// The highest speed of the train.
+    pub max_speed: Velocity,
+    /// The acceleration of the train when speeding up.
+    pub acceleration: Acceleration,
+    /// The deceleration of the train when braking.
+    pub deceleration: Acceleration,
+}
+
+impl Default for TrainProfile {
+    fn default() -> Self {
+        Self {
+            max_speed: Velocity::new::<kilometer_per_hour>(120.0),
+            acceleration: Acceleration::new::<meter_per_second_squared>(0.5),
+            deceleration: Acceleration::new::<meter_per_second_squared>(0.5),
+        }
+    }
+}
+
+/// The cost minimised by a route search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoutingCost {
+    /// The shortest route by track length.
+    #[default]
+    Distance,
+    /// The fastest route by estimated running time.
+    TravelTime,
+}
+
+/// The estimated running time along one edge of a route.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EdgeTravelTime {
+    /// The ID of the edge.
+    pub edge_id: EdgeId,
+    /// The length of the edge in meters.
+    pub length: f64,
+    /// The running time along the edge in seconds, including the time lost to speed changes
+    /// on the edge and at its start.
+    pub time: f64,
+    /// The speed in m/s the train runs at when entering the edge, without speed changes.
+    pub entry_speed: f64,
+    /// The speed in m/s the train runs at when leaving the edge, without speed changes.
+    pub exit_speed: f64,
+}
+
+/// A route with the estimated running time along each of its edges.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimedRoute {
+    /// The IDs of the nodes along the route, including the start and end node.
+    pub nodes: Vec<NodeId>,
+    /// The running times along the edges of the route, in order.
+    pub edges: Vec<EdgeTravelTime>,
+    /// The length of the route in meters.
+    pub length: f64,
+    /// The running time from standstill at the start to standstill at the end in seconds.
+    pub time: f64,
+}
+
+/// Finds the route with the lowest cost between two nodes and estimates its running time.
+///
+/// The search runs over the topology edges, so it follows the allowed transitions and knows
+/// the speed the train leaves each edge with. Along an edge the train runs at the lower of its
+/// maximum speed and the speed limit. Every change of speed adds the time lost while
+/// accelerating or braking, as does starting from and stopping at standstill.
+pub(super) fn timed_route(
+    graph: &RailwayGraph,
+    start: NodeId,
+    end: NodeId,
+    cost: RoutingCost,
+    profile: &TrainProfile,
+    speed_limits: &RailwaySpeedLimits,
+) -> Option<TimedRoute> {
+    let &(start_1, start_2) = graph.topology_graph.id_to_index(start)?;
+    graph.topology_graph.id_to_index(end)?;
+    if start == end {
+        return Some(TimedRoute {
+            nodes: vec![start],
+            ..Default::default()
+        });
+    }
+
+    let topology = &graph.topology_graph.graph;
+    let mut runs = EdgeRuns::new(graph, profile, speed_limits);
+    let mut costs: HashMap<EdgeIndex, f64> = HashMap::new();
+    let mut parents: HashMap<EdgeIndex, EdgeIndex> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+
+    for edge in topology.edges(start_1).chain(topology.edges(start_2)) {
+        let Some(run) = runs.get(edge.id()) else {
+            continue;
+        };
+        let edge_cost = match cost {
+            RoutingCost::Distance => run.length,
+            RoutingCost::TravelTime => run.time + profile.speed_change(0.0, run.entry_speed),
+        };
+        if costs.get(&edge.id()).is_none_or(|&known| edge_cost < known) {
+            costs.insert(edge.id(), edge_cost);
+            heap.push(State(edge_cost, edge.id(), false));
+        }
+    }
+
+    let mut goal = None;
+    while let Some(State(current, edge, finished)) = heap.pop() {
+        if finished {
+            goal = Some(edge);
+            break;
+        }
+        if current > costs[&edge] {
+            continue;
+        }
+        let run = runs.get(edge)?;
+        let (_, port) = topology.edge_endpoints(edge)?;
+        if graph.topology_graph.index_to_id(port) == Some(&end) {
+            let stop = match cost {
+                RoutingCost::Distance => 0.0,
+                RoutingCost::TravelTime => profile.speed_change(run.exit_speed, 0.0),
+            };
+            heap.push(State(current + stop, edge, true));
+            continue;
+        }
+
+        let next_edges: Vec<EdgeIndex> = topology.edges(port).map(|next| next.id()).collect();
+        for next in next_edges {
+            let Some(next_run) = runs.get(next) else {
+                continue;
+            };
+            let next_cost = current
+                + match cost {
+                    RoutingCost::Distance => next_run.length,
+                    RoutingCost::TravelTime => {
+                        next_run.time + profile.speed_change(run.exit_speed, next_run.entry_speed)
+                    }
+                };
+            if costs.get(&next).is_none_or(|&known| next_cost < known) {
+                costs.insert(next, next_cost);
+                parents.insert(next, edge);
+                heap.push(State(next_cost, next, false));
+            }
+        }
+    }
+
+    let mut edges = vec![goal?];
+    while let Some(&parent) = parents.get(edges.last().unwrap()) {
+        edges.push(parent);
+    }
+    edges.reverse();
+
+    let mut route = TimedRoute {
+        nodes: vec![start],
+        ..Default::default()
+    };
+    let mut speed = 0.0;
+    for edge in edges {
+        let (_, port) = topology.edge_endpoints(edge)?;
+        let run = runs.get(edge)?;
+        let time = run.time + profile.speed_change(speed, run.entry_speed);
+        route.nodes.push(*graph.topology_graph.index_to_id(port)?);
+        route.edges.push(EdgeTravelTime {
+            edge_id: topology[edge].edge_id,
+            length: run.length,
+            time,
+            entry_speed: run.entry_speed,
+            exit_speed: run.exit_speed,
+        });
+        route.length += run.length;
+        route.time += time;
+        speed = run.exit_speed;
+    }
+    if let Some(last) = route.edges.last_mut() {
+        let stop = profile.speed_change(speed, 0.0);
+        last.time += stop;
+        route.time += stop;
+    }
+    Some(route)
+}
+
+impl TrainProfile {
+    /// Returns the time in seconds lost by changing speed between two speeds in m/s, compared
+    /// to changing it instantly where the faster section ends or starts.
+    ///
+    /// A train changing from `v1` to `v2` with a constant rate `a` covers the distance
+    /// `(v1² - v2²) / 2a` in `|v1 - v2| / a`, which it would otherwise have run at the higher
+    /// speed. The difference is `(v1 - v2)² / (2a · max(v1, v2))`.
+    pub(super) fn speed_change(&self, from: f64, to: f64) -> f64 {
+        let rate = if to > from {
+            self.acceleration
+        } else {
+            self.deceleration
+        }
+        .get::<meter_per_second_squared>();
+        let faster = from.max(to);
+        if faster <= 0.0 || rate <= 0.0 {
+            return 0.0;
+        }
+        (from - to).powi(2) / (2.0 * rate * faster)
+    }
+}
+
+/// The running time along a topology edge, ignoring how the train enters and leaves it.
+#[derive(Debug, Clone)]
+pub(super) struct EdgeRun {
+    /// The direction the edge is travelled in.
+    pub(super) direction: TrackDirection,
+    /// The length of the edge in meters.
+    pub(super) length: f64,
+    /// The running time in seconds, including the speed changes on the edge.
+    pub(super) time: f64,
+    /// The speed in m/s at the start of the edge.
+    pub(super) entry_speed: f64,
+    /// The speed in m/s at the end of the edge.
+    pub(super) exit_speed: f64,
+    /// The parts of the edge with a constant speed in the direction of travel, as pairs of
+    /// length in meters and speed in m/s.
+    pub(super) parts: Vec<(f64, f64)>,
+}
+
+/// Computes and caches the `EdgeRun`s of the topology edges.
+pub(super) struct EdgeRuns<'a> {
+    graph: &'a RailwayGraph,
+    profile: &'a TrainProfile,
+    speed_limits: &'a RailwaySpeedLimits,
+    runs: HashMap<EdgeIndex, Option<Rc<EdgeRun>>>,
+}
+
+impl<'a> EdgeRuns<'a> {
+    pub(super) fn new(
+        graph: &'a RailwayGraph,
+        profile: &'a TrainProfile,
+        speed_limits: &'a RailwaySpeedLimits,
+    ) -> Self {
+        Self {
+            graph,
+            profile,
+            speed_limits,
+            runs: HashMap::new(),
+        }
+    }
+
+    pub(super) fn get(&mut self, edge: EdgeIndex) -> Option<Rc<EdgeRun>> {
+        if let Some(run) = self.runs.get(&edge) {
+            return run.clone();
+        }
+        let run = self.compute(edge).map(Rc::new);
+        self.runs.insert(edge, run.clone());
+        run
+    }
+
+    fn compute(&self, edge: EdgeIndex) -> Option<EdgeRun> {
+        let topology = &self.graph.topology_graph;
+        let (from, _) = topology.graph.edge_endpoints(edge)?;
+        let from = *topology.index_to_id(from)?;
+        let railway_edge = self.graph.get_edge_by_id(topology.graph[edge].edge_id)?;
+        let direction = if railway_edge.source == from {
+            TrackDirection::Forward
+        } else {
+            TrackDirection::Backward
+        };
+
+        let max_speed = self.profile.max_speed.get::<meter_per_second>();
+        let path_length = railway_edge.path_length().get::<meter>();
+        let scale = if path_length > 0.0 {
+            railway_edge.length / path_length
+        } else {
+            0.0
+        };
+
+        // Split the edge at every section boundary and take the speed in the middle of each
+        // part, ordered in the direction of travel.
+        let profile = self.speed_limits.profile(railway_edge.id);
+        let mut offsets = vec![0.0, path_length];
+        if let Some(profile) = profile {
+            for section in profile.sections(direction) {
+                offsets.push(section.start.clamp(0.0, path_length));
+                offsets.push(section.end.clamp(0.0, path_length));
+            }
+        }
+        offsets.sort_by(|a, b| a.total_cmp(b));
+        offsets.dedup();
+        let mut parts: Vec<(f64, f64)> = offsets
+            .windows(2)
+            .map(|pair| {
+                let limit = profile
+                    .and_then(|profile| profile.limit_at((pair[0] + pair[1]) / 2.0, direction))
+                    .map_or(max_speed, |limit| {
+                        limit.get::<meter_per_second>().min(max_speed)
+                    });
+                ((pair[1] - pair[0]) * scale, limit)
+            })
+            .collect();
+        if parts.is_empty() {
+            parts.push((railway_edge.length, max_speed));
+        }
+        if direction == TrackDirection::Backward {
+            parts.reverse();
+        }
+
+        let mut time = 0.0;
+        for (length, speed) in &parts {
+            if *speed <= 0.0 {
+                return None;
+            }
+            time += length / speed;
+        }
+        for pair in parts.windows(2) {
+            time += self.profile.speed_change(pair[0].1, pair[1].1);
+        }
+        Some(EdgeRun {
+            direction,
+            length: railway_edge.length,
+            time,
+            entry_speed: parts[0].1,
+            exit_speed: parts[parts.len() - 1].1,
+            parts,
+        })
+    }
+}
+
+/// A search state: the cost so far, the topology edge last travelled along and whether the
+/// end node has been reached with it.
+#[derive(PartialEq)]
+struct State(f64, EdgeIndex, bool);
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{SpeedProfile, SpeedSection};
+    use crate::railway_algorithms::{tests::loop_elements, PathFinding};
+    use approx::assert_relative_eq;
+
+    fn limit(graph: &RailwayGraph, edge_id: EdgeId, kmh: f64) -> (EdgeId, SpeedProfile) {
+        let length = graph
+            .get_edge_by_id(edge_id)
+            .unwrap()
+            .path_length()
+            .get::<meter>();
+        let section = SpeedSection {
+            start: 0.0,
+            end: length,
+            limit: kmh,
+        };
+        (
+            edge_id,
+            SpeedProfile {
+                forward: vec![section],
+                backward: vec![section],
+            },
+        )
+    }
+
+    #[test]
+    fn test_speed_change() {
+        let profile = TrainProfile {
+            acceleration: Acceleration::new::<meter_per_second_squared>(1.0),
+            deceleration: Acceleration::new::<meter_per_second_squared>(0.5),
+            ..Default::default()
+        };
+        // Starting to 20 m/s takes 20 s over 200 m, which would take 10 s at 20 m/s.
+        assert_relative_eq!(profile.speed_change(0.0, 20.0), 10.0);
+        assert_relative_eq!(profile.speed_change(20.0, 0.0), 20.0);
+        assert_relative_eq!(profile.speed_change(20.0, 10.0), 5.0);
+        assert_relative_eq!(profile.speed_change(10.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn test_fastest_route_avoids_slow_track() {
+        let graph = from_railway_elements(&loop_elements());
+        let profile = TrainProfile::default();
+
+        let shortest = graph
+            .timed_route(1, 3, RoutingCost::Distance, &profile, &Default::default())
+            .unwrap();
+        let short_side = shortest.nodes.clone();
+        let slow_edge = shortest.edges[0].edge_id;
+
+        let speed_limits = RailwaySpeedLimits {
+            edges: HashMap::from([limit(&graph, slow_edge, 20.0)]),
+        };
+        let shortest = graph
+            .timed_route(1, 3, RoutingCost::Distance, &profile, &speed_limits)
+            .unwrap();
+        let fastest = graph
+            .timed_route(1, 3, RoutingCost::TravelTime, &profile, &speed_limits)
+            .unwrap();
+
+        assert_eq!(shortest.nodes, short_side);
+        assert_ne!(fastest.nodes, short_side);
+        assert!(fastest.length > shortest.length);
+        assert!(fastest.time < shortest.time);
+
+        let time: f64 = shortest.edges.iter().map(|edge| edge.time).sum();
+        assert_relative_eq!(shortest.time, time);
+        assert_relative_eq!(
+            shortest.edges[0].entry_speed,
+            Velocity::new::<kilometer_per_hour>(20.0).get::<meter_per_second>()
+        );
+    }
+
+    #[test]
+    fn test_timed_route_times() {
+        let graph = from_railway_elements(&loop_elements());
+        let profile = TrainProfile {
+            max_speed: Velocity::new::<meter_per_second>(20.0),
+            acceleration: Acceleration::new::<meter_per_second_squared>(1.0),
+            deceleration: Acceleration::new::<meter_per_second_squared>(1.0),
+        };
+
+        let route = graph
+            .timed_route(1, 2, RoutingCost::TravelTime, &profile, &Default::default())
+            .unwrap();
+        assert_eq!(route.nodes, vec![1, 2]);
+        assert_eq!(route.edges.len(), 1);
+        // Running at 20 m/s plus 10 s lost to starting and 10 s lost to stopping.
+        assert_relative_eq!(route.time, route.length / 20.0 + 20.0, epsilon = 1e-9);
+        assert_eq!(
+            graph.shortest_path_edges(1, 2),
+            Some(vec![route.edges[0].edge_id])
+        );
+
+        let route = graph
+            .timed_route(1, 1, RoutingCost::TravelTime, &profile, &Default::default())
+            .unwrap();
+        assert_eq!(route.nodes, vec![1]);
+        assert_relative_eq!(route.time, 0.0);
+        assert_eq!(
+            graph.timed_route(1, 99, RoutingCost::Distance, &profile, &Default::default()),
+            None
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{prelude::RailwayGraph, types::NodeId};
+
+use super::{DirectionalPathFinding, DirectionalRoute, ReversalOptions};
+
+/// Up to this number of stops, the visiting order is found exactly. More stops are ordered by a
+/// nearest neighbour tour improved with 2-opt moves.
+const EXACT_ORDER_LIMIT: usize = 10;
+
+/// A route through a list of waypoints.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaypointRoute {
+    /// The waypoints in the order they are visited, including the start and end node.
+    pub waypoints: Vec<NodeId>,
+    /// The direction-aware route through all waypoints.
+    pub route: DirectionalRoute,
+}
+
+/// Routing through several waypoints.
+pub trait WaypointRouting {
+    /// Finds the shortest route visiting the waypoints in the given order.
+    ///
+    /// Each leg continues in the direction the previous leg arrived in, so a train only turns
+    /// back at a waypoint where the `ReversalOptions` allow it.
+    ///
+    /// # Arguments
+    ///
+    /// * `waypoints` - The IDs of the nodes to visit, starting with the start node.
+    /// * `options` - Where trains may reverse and what a reversal costs.
+    ///
+    /// # Returns
+    ///
+    /// The `WaypointRoute`, or `None` if there are no waypoints or a leg has no route.
+    fn waypoint_route(
+        &self,
+        waypoints: &[NodeId],
+        options: &ReversalOptions,
+    ) -> Option<WaypointRoute>;
+
+    /// Finds a short route from a start node through unordered stops.
+    ///
+    /// The stops are ordered by the cost of the routes between them, then the route is built as
+    /// with `waypoint_route`.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The ID of the start node.
+    /// * `stops` - The IDs of the nodes to visit in any order.
+    /// * `end` - The ID of the node to finish at, or `None` to finish at the last stop.
+    /// * `options` - Where trains may reverse and what a reversal costs.
+    ///
+    /// # Returns
+    ///
+    /// The `WaypointRoute` with the chosen order, or `None` if a stop cannot be reached.
+    fn optimized_waypoint_route(
+        &self,
+        start: NodeId,
+        stops: &[NodeId],
+        end: Option<NodeId>,
+        options: &ReversalOptions,
+    ) -> Option<WaypointRoute>;
+}
+
+impl WaypointRouting for RailwayGraph {
+    fn waypoint_route(
+        &self,
+        waypoints: &[NodeId],
+        options: &ReversalOptions,
+    ) -> Option<WaypointRoute> {
+        let (&start, rest) = waypoints.split_first()?;
+        let mut route = self.directional_path(start, None, start, options)?;
+        for &waypoint in rest {
+            let current = *route.nodes.last().unwrap();
+            let previous = route.nodes.len().checked_sub(2).map(|i| route.nodes[i]);
+            let leg = self.directional_path(current, previous, waypoint, options)?;
+            let offset = route.nodes.len() - 1;
+            route
+                .reversals
+                .extend(leg.reversals.iter().map(|index| index + offset));
+            route.nodes.extend_from_slice(&leg.nodes[1..]);
+            route.length += leg.length;
+            route.cost += leg.cost;
+        }
+        Some(WaypointRoute {
+            waypoints: waypoints.to_vec(),
+            route,
+        })
+    }
+
+    fn optimized_waypoint_route(
+        &self,
+        start: NodeId,
+        stops: &[NodeId],
+        end: Option<NodeId>,
+        options: &ReversalOptions,
+    ) -> Option<WaypointRoute> {
+        let nodes: Vec<NodeId> = std::iter::once(start)
+            .chain(stops.iter().copied())
+            .chain(end)
+            .collect();
+        let costs: Vec<Vec<f64>> = nodes
+            .iter()
+            .map(|&from| {
+                nodes
+                    .iter()
+                    .map(|&to| {
+                        self.directional_path(from, None, to, options)
+                            .map_or(f64::INFINITY, |route| route.cost)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let order = optimize_order(&costs, end.is_some())?;
+        let waypoints: Vec<NodeId> = order.into_iter().map(|index| nodes[index]).collect();
+        self.waypoint_route(&waypoints, options)
+    }
+}
+
+/// Orders the nodes of a cost matrix into a short open tour.
+///
+/// The tour starts at the first node and, if `has_end` is set, finishes at the last one. The
+/// returned indices include both. Returns `None` if no tour with a finite cost exists.
+fn optimize_order(costs: &[Vec<f64>], has_end: bool) -> Option<Vec<usize>> {
+    let count = costs.len();
+    let end = has_end.then(|| count - 1);
+    let stops: Vec<usize> = (1..count).filter(|&index| Some(index) != end).collect();
+
+    let order = if stops.len() <= EXACT_ORDER_LIMIT {
+        exact_order(costs, &stops, end)
+    } else {
+        let mut order = nearest_neighbour_order(costs, &stops);
+        order.extend(end);
+        improve_order(costs, &mut order, end.is_some());
+        order
+    };
+    let cost: f64 = order.windows(2).map(|pair| costs[pair[0]][pair[1]]).sum();
+    cost.is_finite().then_some(order)
+}
+
+/// Finds the cheapest order with the Held-Karp dynamic programme.
+fn exact_order(costs: &[Vec<f64>], stops: &[usize], end: Option<usize>) -> Vec<usize> {
+    let count = stops.len();
+    let full = (1usize << count) - 1;
+    // best[mask][last] is the cost of visiting the stops in mask, finishing at stops[last].
+    let mut best = vec![vec![f64::INFINITY; count]; 1 << count];
+    let mut parent = vec![vec![usize::MAX; count]; 1 << count];
+    for (last, &stop) in stops.iter().enumerate() {
+        best[1 << last][last] = costs[0][stop];
+    }
+    for mask in 1..=full {
+        for last in 0..count {
+            let cost = best[mask][last];
+            if mask & (1 << last) == 0 || !cost.is_finite() {
+                continue;
+            }
+            for next in (0..count).filter(|next| mask & (1 << next) == 0) {
+                let next_cost = cost + costs[stops[last]][stops[next]];
+                let next_mask = mask | (1 << next);
+                if next_cost < best[next_mask][next] {
+                    best[next_mask][next] = next_cost;
+                    parent[next_mask][next] = last;
+                }
+            }
+        }
+    }
+
+    let finish_cost =
+        |last: usize| best[full][last] + end.map_or(0.0, |end| costs[stops[last]][end]);
+    let mut order = vec![0];
+    if let Some(mut last) = (0..count).min_by(|&a, &b| finish_cost(a).total_cmp(&finish_cost(b))) {
+        let mut mask = full;
+        let mut visited = Vec::with_capacity(count);
+        while last != usize::MAX {
+            visited.push(stops[last]);
+            let previous = parent[mask][last];
+            mask &= !(1 << last);
+            last = previous;
+        }
+        order.extend(visited.into_iter().rev());
+    }
+    order.extend(end);
+    order
+}
+
+/// Visits the closest unvisited stop next, starting at the first node.
+fn nearest_neighbour_order(costs: &[Vec<f64>], stops: &[usize]) -> Vec<usize> {
+    let mut order = vec![0];
+    let mut remaining = stops.to_vec();
+    while !remaining.is_empty() {
+        let current = *order.last().unwrap();
+        let (index, _) = remaining
+            .iter()
+            .enumerate()
+            .min_by(|(_, &a), (_, &b)| costs[current][a].total_cmp(&costs[current][b]))
+            .unwrap();
+        order.push(remaining.swap_remove(index));
+    }
+    order
+}
+
+/// Reverses sections of the order while that makes the tour cheaper, keeping the first node
+/// and, if `fixed_end` is set, the last node in place.
+fn improve_order(costs: &[Vec<f64>], order: &mut [usize], fixed_end: bool) {
+    let tour_cost =
+        |order: &[usize]| -> f64 { order.windows(2).map(|pair| costs[pair[0]][pair[1]]).sum() };
+    let last = order.len() - usize::from(fixed_end);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..last {
+            for j in i + 1..last {
+                let cost = tour_cost(order);
+                order[i..=j].reverse();
+                if tour_cost(order) + 1e-9 < cost {
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::siding_elements;
+
+    #[test]
+    fn test_waypoint_route() {
+        let graph = from_railway_elements(&siding_elements());
+        let options = ReversalOptions::default();
+
+        let route = graph.waypoint_route(&[13, 11, 14], &options).unwrap();
+        assert_eq!(route.waypoints, vec![13, 11, 14]);
+        assert_eq!(route.route.nodes, vec![13, 12, 11, 12, 14]);
+        assert_eq!(route.route.reversals, vec![2]);
+
+        let first = graph.directional_path(13, None, 11, &options).unwrap();
+        let second = graph.directional_path(11, Some(12), 14, &options).unwrap();
+        approx::assert_relative_eq!(route.route.length, first.length + second.length);
+
+        assert_eq!(graph.waypoint_route(&[], &options), None);
+        assert_eq!(graph.waypoint_route(&[13, 99], &options), None);
+    }
+
+    #[test]
+    fn test_optimized_waypoint_route() {
+        let graph = from_railway_elements(&siding_elements());
+        let options = ReversalOptions::default();
+
+        let route = graph
+            .optimized_waypoint_route(10, &[14, 12, 11], None, &options)
+            .unwrap();
+        assert_eq!(route.waypoints, vec![10, 11, 12, 14]);
+        assert_eq!(route.route.nodes, vec![10, 11, 12, 14]);
+
+        let route = graph
+            .optimized_waypoint_route(10, &[12, 11], Some(14), &options)
+            .unwrap();
+        assert_eq!(route.waypoints, vec![10, 11, 12, 14]);
+
+        assert_eq!(
+            graph.optimized_waypoint_route(10, &[99], None, &options),
+            None
+        );
+    }
+
+    #[test]
+    fn test_optimize_order() {
+        // Stops along a line, where the cost is the distance between their positions.
+        let positions = [
+            0.0, 7.0, 3.0, 12.0, 1.0, 9.0, 4.0, 11.0, 2.0, 8.0, 6.0, 5.0, 10.0,
+        ];
+        let costs: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| f64::abs(a - b)).collect())
+            .collect();
+        let sorted = |order: Vec<usize>| order.iter().map(|&i| positions[i]).collect::<Vec<_>>();
+        let expected: Vec<f64> = (0..=12).map(f64::from).collect();
+
+        // More stops than solved exactly.
+        assert_eq!(sorted(optimize_order(&costs, false).unwrap()), expected);
+
+        let costs: Vec<Vec<f64>> = costs[..6].iter().map(|row| row[..6].to_vec()).collect();
+        assert_eq!(
+            sorted(optimize_order(&costs, false).unwrap()),
+            vec![0.0, 1.0, 3.0, 7.0, 9.0, 12.0]
+        );
+        assert_eq!(
+            sorted(optimize_order(&costs, true).unwrap()),
+            vec![0.0, 1.0, 3.0, 7.0, 12.0, 9.0]
+        );
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This module provides a trait and an implementation for fetching railway infrastructure data
 //! from an API. The `RailwayApiClient` trait provides a common asynchronous interface for
-//! fetching data by area name or bounding box.
+//! fetching data, stations and `route=train` relations by area name or bounding box.
 //!
 use anyhow::Result;
 use async_trait::async_trait;
@@ -56,4 +56,51 @@ pub trait RailwayApiClient {
     ///
     /// A `Result` containing a JSON `Value` with the fetched data on success, or an error on failure.
     async fn fetch_by_bbox(&self, bbox: &str) -> Result<Value>;
+
+    /// Fetch the `railway=station`, `halt` and `stop` nodes by area name.
+    ///
+    /// The stations are fetched apart from the railway graph, as many of them are not part of
+    /// the track.
+    ///
+    /// # Arguments
+    ///
+    /// * `area_name` - The name of the area for which to fetch the stations.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a JSON `Value` with the fetched stations on success, or an error on failure.
+    async fn fetch_stations_by_area_name(&self, area_name: &str) -> Result<Value>;
+
+    /// Fetch the `railway=station`, `halt` and `stop` nodes by bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// * `bbox` - A string representing the bounding box for which to fetch the stations.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a JSON `Value` with the fetched stations on success, or an error on failure.
+    async fn fetch_stations_by_bbox(&self, bbox: &str) -> Result<Value>;
+
+    /// Fetch the `route=train` relations by area name.
+    ///
+    /// # Arguments
+    ///
+    /// * `area_name` - The name of the area for which to fetch the relations.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a JSON `Value` with the fetched relations on success, or an error on failure.
+    async fn fetch_route_relations_by_area_name(&self, area_name: &str) -> Result<Value>;
+
+    /// Fetch the `route=train` relations by bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// * `bbox` - A string representing the bounding box for which to fetch the relations.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a JSON `Value` with the fetched relations on success, or an error on failure.
+    async fn fetch_route_relations_by_bbox(&self, bbox: &str) -> Result<Value>;
 }
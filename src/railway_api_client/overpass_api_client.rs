@@ -82,6 +82,46 @@ impl RailwayApiClient for OverpassApiClient {
         let response: Value = self.fetch_by_query(&query).await?;
         Ok(response)
     }
+
+    async fn fetch_stations_by_area_name(&self, area_name: &str) -> Result<Value> {
+        let query = format!(
+            r#"[out:json];area[name="{}"]->.searchArea;node(area.searchArea)["railway"~"^(station|halt|stop)$"];out body;"#,
+            area_name
+        );
+
+        let response: Value = self.fetch_by_query(&query).await?;
+        Ok(response)
+    }
+
+    async fn fetch_stations_by_bbox(&self, bbox: &str) -> Result<Value> {
+        let query = format!(
+            r#"[out:json];node({})["railway"~"^(station|halt|stop)$"];out body;"#,
+            bbox
+        );
+
+        let response: Value = self.fetch_by_query(&query).await?;
+        Ok(response)
+    }
+
+    async fn fetch_route_relations_by_area_name(&self, area_name: &str) -> Result<Value> {
+        let query = format!(
+            r#"[out:json];area[name="{}"]->.searchArea;relation(area.searchArea)["route"="train"];out body;"#,
+            area_name
+        );
+
+        let response: Value = self.fetch_by_query(&query).await?;
+        Ok(response)
+    }
+
+    async fn fetch_route_relations_by_bbox(&self, bbox: &str) -> Result<Value> {
+        let query = format!(
+            r#"[out:json];relation({})["route"="train"];out body;"#,
+            bbox
+        );
+
+        let response: Value = self.fetch_by_query(&query).await?;
+        Ok(response)
+    }
 }
 
 #[cfg(test)]
@@ -172,4 +212,62 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), test_json);
     }
+
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    #[cfg_attr(target_arch = "wasm32", ignore)]
+    async fn test_fetch_route_relations_by_bbox() {
+        let test_json = serde_json::json!({ "elements": [] });
+        let query = r#"[out:json];relation(1,2,3,4)["route"="train"];out body;"#;
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/interpreter")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&test_json).unwrap())
+            .match_body(mockito::Matcher::UrlEncoded(
+                "data".to_string(),
+                query.to_string(),
+            ))
+            .create_async()
+            .await;
+
+        let mut client = OverpassApiClient::new();
+        client
+            .connect(&format!("{}/api/interpreter", server.url()))
+            .await
+            .unwrap();
+        let result = client.fetch_route_relations_by_bbox("1,2,3,4").await;
+
+        mock.assert_async().await;
+        assert_eq!(result.unwrap(), test_json);
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    #[cfg_attr(target_arch = "wasm32", ignore)]
+    async fn test_fetch_stations_by_bbox() {
+        let test_json = serde_json::json!({ "elements": [] });
+        let query = r#"[out:json];node(1,2,3,4)["railway"~"^(station|halt|stop)$"];out body;"#;
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/interpreter")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&test_json).unwrap())
+            .match_body(mockito::Matcher::UrlEncoded(
+                "data".to_string(),
+                query.to_string(),
+            ))
+            .create_async()
+            .await;
+
+        let mut client = OverpassApiClient::new();
+        client
+            .connect(&format!("{}/api/interpreter", server.url()))
+            .await
+            .unwrap();
+        let result = client.fetch_stations_by_bbox("1,2,3,4").await;
+
+        mock.assert_async().await;
+        assert_eq!(result.unwrap(), test_json);
+    }
 }
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uom::si::{
+    f64::{Length, Velocity},
+    length::{meter, millimeter},
+    velocity::meter_per_second,
+};
+
+use crate::types::EdgeId;
+
+/// Standard gravity in m/s².
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// The horizontal curvature at a vertex of an edge's geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvatureSample {
+    /// The distance in meters along the edge's geometry from its source node.
+    pub offset: f64,
+    /// The curvature in 1/m, positive for left and negative for right hand curves seen from the
+    /// source node, `0.0` on straight track.
+    pub curvature: f64,
+}
+
+impl CurvatureSample {
+    /// Returns the curve radius in meters, or `None` on straight track.
+    pub fn radius(&self) -> Option<f64> {
+        (self.curvature != 0.0).then(|| 1.0 / self.curvature.abs())
+    }
+}
+
+/// The curvature profile of an edge, ordered by offset from its source node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CurvatureProfile {
+    /// The curvature at the interior vertices of the geometry, ordered by offset.
+    pub samples: Vec<CurvatureSample>,
+}
+
+impl CurvatureProfile {
+    /// Returns the smallest curve radius of the edge in meters, or `None` if the edge is straight.
+    pub fn min_radius(&self) -> Option<f64> {
+        self.samples
+            .iter()
+            .filter_map(CurvatureSample::radius)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// Track parameters used to derive the permissible speed in curves.
+///
+/// The permissible speed is the speed at which the lateral acceleration not compensated by the
+/// cant equals the allowed cant deficiency: `v = sqrt(g * r * (cant + cant_deficiency) / s)`,
+/// with `s` the distance between the rail centres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CantConfig {
+    /// The applied cant (superelevation) of the outer rail.
+    pub cant: Length,
+    /// The allowed cant deficiency.
+    pub cant_deficiency: Length,
+    /// The distance between the centres of the rail heads, about 1500 mm on standard gauge.
+    pub rail_distance: Length,
+}
+
+impl Default for CantConfig {
+    fn default() -> Self {
+        Self {
+            cant: Length::new::<millimeter>(150.0),
+            cant_deficiency: Length::new::<millimeter>(100.0),
+            rail_distance: Length::new::<millimeter>(1500.0),
+        }
+    }
+}
+
+impl CantConfig {
+    /// Returns the permissible speed in a curve with the given radius.
+    pub fn permissible_speed(&self, radius: Length) -> Velocity {
+        let lateral = STANDARD_GRAVITY
+            * radius.get::<meter>()
+            * (self.cant + self.cant_deficiency).get::<meter>()
+            / self.rail_distance.get::<meter>();
+        Velocity::new::<meter_per_second>(lateral.max(0.0).sqrt())
+    }
+}
+
+/// The curvature profiles of the edges of a `RailwayGraph`, keyed by edge id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RailwayCurvature {
+    /// Curvature profiles of the edges, keyed by edge id.
+    pub edges: HashMap<EdgeId, CurvatureProfile>,
+}
+
+impl RailwayCurvature {
+    /// Returns the curvature profile of the edge with the given id.
+    pub fn profile(&self, id: EdgeId) -> Option<&CurvatureProfile> {
+        self.edges.get(&id)
+    }
+
+    /// Returns the smallest curve radius of the edge with the given id in meters.
+    pub fn min_radius(&self, id: EdgeId) -> Option<f64> {
+        self.profile(id).and_then(CurvatureProfile::min_radius)
+    }
+
+    /// Returns the permissible speed on the edge with the given id, limited by its smallest
+    /// curve radius. Returns `None` if the edge is straight or unknown.
+    pub fn permissible_speed(&self, id: EdgeId, config: &CantConfig) -> Option<Velocity> {
+        self.min_radius(id)
+            .map(|radius| config.permissible_speed(Length::new::<meter>(radius)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use uom::si::velocity::kilometer_per_hour;
+
+    #[test]
+    fn test_permissible_speed() {
+        let config = CantConfig::default();
+        // V = sqrt(R * (D + I) / 11.8) km/h for standard gauge.
+        assert_relative_eq!(
+            config
+                .permissible_speed(Length::new::<meter>(1000.0))
+                .get::<kilometer_per_hour>(),
+            (1000.0f64 * 250.0 / 11.8).sqrt(),
+            epsilon = 0.5
+        );
+
+        let curvature = RailwayCurvature {
+            edges: HashMap::from([(
+                1,
+                CurvatureProfile {
+                    samples: vec![
+                        CurvatureSample {
+                            offset: 10.0,
+                            curvature: 0.0,
+                        },
+                        CurvatureSample {
+                            offset: 20.0,
+                            curvature: -1.0 / 300.0,
+                        },
+                        CurvatureSample {
+                            offset: 30.0,
+                            curvature: 1.0 / 500.0,
+                        },
+                    ],
+                },
+            )]),
+        };
+        assert_relative_eq!(curvature.min_radius(1).unwrap(), 300.0);
+        assert!(curvature.permissible_speed(1, &config).is_some());
+        assert_eq!(curvature.permissible_speed(2, &config), None);
+    }
+}
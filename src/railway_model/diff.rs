@@ -0,0 +1,506 @@
+use std::collections::{BTreeSet, HashMap};
+
+use geo::{Coord, LineString};
+use serde::{Deserialize, Serialize};
+use uom::si::{f64::Length, length::meter};
+
+use crate::algorithms::Distance;
+use crate::types::{EdgeId, NodeId};
+
+use super::{paths_match, RailwayEdge, RailwayGraph, RailwayNode, RailwayTags, Tags};
+
+/// Options controlling which differences between two `RailwayGraph`s are reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// Nodes and edge vertices that moved less than this are considered unchanged.
+    pub tolerance: Length,
+    /// Edge lengths that differ by less than this are considered unchanged.
+    pub length_tolerance: Length,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: Length::new::<meter>(1.0),
+            length_tolerance: Length::new::<meter>(1.0),
+        }
+    }
+}
+
+/// The kind of change of a node or an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The element only exists in the new graph.
+    Added,
+    /// The element only exists in the old graph.
+    Removed,
+    /// The element exists in both graphs, but differs.
+    Modified,
+}
+
+impl ChangeKind {
+    /// Returns the name of the change kind, e.g. `"added"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+        }
+    }
+}
+
+/// A node that was added, removed or modified between two graphs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeChange {
+    /// The id of the node.
+    pub node_id: NodeId,
+    /// The kind of change.
+    pub kind: ChangeKind,
+    /// The location in the new graph, or in the old graph for removed nodes.
+    pub location: Coord,
+    /// The location in the old graph, if the node moved.
+    pub previous_location: Option<Coord>,
+    /// The keys of the tags that were added, removed or changed.
+    pub changed_tags: Vec<String>,
+}
+
+impl NodeChange {
+    /// Returns `true` if the node moved by more than the tolerance.
+    pub fn moved(&self) -> bool {
+        self.previous_location.is_some()
+    }
+}
+
+/// An edge that was added, removed or modified between two graphs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeChange {
+    /// The id of the edge.
+    pub edge_id: EdgeId,
+    /// The kind of change.
+    pub kind: ChangeKind,
+    /// The path in the new graph, or in the old graph for removed edges.
+    pub path: LineString,
+    /// The path in the old graph, if the geometry changed.
+    pub previous_path: Option<LineString>,
+    /// The length in meters in the new graph, or in the old graph for removed edges.
+    pub length: f64,
+    /// The length in meters in the old graph, if the length changed.
+    pub previous_length: Option<f64>,
+    /// The keys of the tags that were added, removed or changed.
+    pub changed_tags: Vec<String>,
+}
+
+impl EdgeChange {
+    /// Returns `true` if vertices of the edge moved by more than the tolerance, or vertices were
+    /// added or removed.
+    pub fn geometry_moved(&self) -> bool {
+        self.previous_path.is_some()
+    }
+
+    /// Returns `true` if the length of the edge changed by more than the length tolerance.
+    pub fn length_changed(&self) -> bool {
+        self.previous_length.is_some()
+    }
+
+    /// Returns `true` if tags of the edge were added, removed or changed.
+    pub fn tags_changed(&self) -> bool {
+        !self.changed_tags.is_empty()
+    }
+
+    /// Returns the change of the track length in meters caused by this change.
+    pub fn length_delta(&self) -> f64 {
+        match self.kind {
+            ChangeKind::Added => self.length,
+            ChangeKind::Removed => -self.length,
+            ChangeKind::Modified => self
+                .previous_length
+                .map_or(0.0, |previous| self.length - previous),
+        }
+    }
+}
+
+/// A summary of the changes between two graphs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiffSummary {
+    /// The number of added nodes.
+    pub added_nodes: usize,
+    /// The number of removed nodes.
+    pub removed_nodes: usize,
+    /// The number of modified nodes.
+    pub modified_nodes: usize,
+    /// The number of added edges.
+    pub added_edges: usize,
+    /// The number of removed edges.
+    pub removed_edges: usize,
+    /// The number of modified edges.
+    pub modified_edges: usize,
+    /// The total length of the added edges in meters.
+    pub added_length: f64,
+    /// The total length of the removed edges in meters.
+    pub removed_length: f64,
+    /// The total change of length of the modified edges in meters.
+    pub modified_length: f64,
+    /// The change of the total track length in meters.
+    pub net_length: f64,
+}
+
+/// The differences between an old and a new `RailwayGraph`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphDiff {
+    /// The changed nodes, ordered by id.
+    pub nodes: Vec<NodeChange>,
+    /// The changed edges, ordered by id.
+    pub edges: Vec<EdgeChange>,
+    /// A summary of the changes.
+    pub summary: DiffSummary,
+}
+
+impl GraphDiff {
+    /// Returns `true` if the graphs do not differ.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    /// Returns the changed edges of the given kind.
+    pub fn edges_of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = &EdgeChange> {
+        self.edges.iter().filter(move |change| change.kind == kind)
+    }
+
+    /// Returns the changed nodes of the given kind.
+    pub fn nodes_of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = &NodeChange> {
+        self.nodes.iter().filter(move |change| change.kind == kind)
+    }
+}
+
+/// Comparison of two snapshots of a railway network, e.g. to monitor OpenStreetMap edits.
+pub trait RailwayGraphDiff {
+    /// Compare this graph, the old snapshot, with `other`, the new snapshot.
+    ///
+    /// Nodes and edges are matched by id. Tags are not compared; use `diff_with_tags` for that.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The new snapshot.
+    /// * `options` - Tolerances below which moved nodes and changed lengths are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `GraphDiff` with the added, removed and modified nodes and edges.
+    fn diff(&self, other: &RailwayGraph, options: &DiffOptions) -> GraphDiff {
+        self.diff_with_tags(
+            &RailwayTags::default(),
+            other,
+            &RailwayTags::default(),
+            options,
+        )
+    }
+
+    /// Compare this graph and its tags, the old snapshot, with `other` and `other_tags`, the new
+    /// snapshot.
+    ///
+    /// An edge is modified if its geometry moved, its length changed or its tags changed. A node
+    /// is modified if it moved or its tags changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tags of this graph.
+    /// * `other` - The new snapshot.
+    /// * `other_tags` - The tags of the new snapshot.
+    /// * `options` - Tolerances below which moved nodes and changed lengths are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `GraphDiff` with the added, removed and modified nodes and edges.
+    fn diff_with_tags(
+        &self,
+        tags: &RailwayTags,
+        other: &RailwayGraph,
+        other_tags: &RailwayTags,
+        options: &DiffOptions,
+    ) -> GraphDiff;
+}
+
+impl RailwayGraphDiff for RailwayGraph {
+    fn diff_with_tags(
+        &self,
+        tags: &RailwayTags,
+        other: &RailwayGraph,
+        other_tags: &RailwayTags,
+        options: &DiffOptions,
+    ) -> GraphDiff {
+        let tolerance = options.tolerance.get::<meter>();
+        let length_tolerance = options.length_tolerance.get::<meter>();
+
+        let old_nodes = nodes_by_id(self);
+        let new_nodes = nodes_by_id(other);
+        let node_ids: BTreeSet<NodeId> =
+            old_nodes.keys().chain(new_nodes.keys()).copied().collect();
+        let nodes: Vec<NodeChange> = node_ids
+            .into_iter()
+            .filter_map(|id| {
+                let change = |kind, location, previous_location, changed_tags| NodeChange {
+                    node_id: id,
+                    kind,
+                    location,
+                    previous_location,
+                    changed_tags,
+                };
+                match (old_nodes.get(&id), new_nodes.get(&id)) {
+                    (Some(old), Some(new)) => {
+                        let moved = old.location.distance(&new.location).get::<meter>() > tolerance;
+                        let changed_tags =
+                            changed_keys(tags.node_tags(id), other_tags.node_tags(id));
+                        (moved || !changed_tags.is_empty()).then(|| {
+                            change(
+                                ChangeKind::Modified,
+                                new.location,
+                                moved.then_some(old.location),
+                                changed_tags,
+                            )
+                        })
+                    }
+                    (None, Some(new)) => {
+                        Some(change(ChangeKind::Added, new.location, None, Vec::new()))
+                    }
+                    (Some(old), None) => {
+                        Some(change(ChangeKind::Removed, old.location, None, Vec::new()))
+                    }
+                    (None, None) => None,
+                }
+            })
+            .collect();
+
+        let old_edges = edges_by_id(self);
+        let new_edges = edges_by_id(other);
+        let edge_ids: BTreeSet<EdgeId> =
+            old_edges.keys().chain(new_edges.keys()).copied().collect();
+        let edges: Vec<EdgeChange> = edge_ids
+            .into_iter()
+            .filter_map(|id| match (old_edges.get(&id), new_edges.get(&id)) {
+                (Some(old), Some(new)) => {
+                    let geometry_moved = !paths_match(&old.path, &new.path, tolerance);
+                    let length_changed = (old.length - new.length).abs() > length_tolerance;
+                    let changed_tags = changed_keys(tags.edge_tags(id), other_tags.edge_tags(id));
+                    (geometry_moved || length_changed || !changed_tags.is_empty()).then(|| {
+                        EdgeChange {
+                            edge_id: id,
+                            kind: ChangeKind::Modified,
+                            path: new.path.clone(),
+                            previous_path: geometry_moved.then(|| old.path.clone()),
+                            length: new.length,
+                            previous_length: length_changed.then_some(old.length),
+                            changed_tags,
+                        }
+                    })
+                }
+                (None, Some(edge)) => Some(unmatched_edge(edge, ChangeKind::Added)),
+                (Some(edge), None) => Some(unmatched_edge(edge, ChangeKind::Removed)),
+                (None, None) => None,
+            })
+            .collect();
+
+        let summary = summarize(&nodes, &edges);
+        GraphDiff {
+            nodes,
+            edges,
+            summary,
+        }
+    }
+}
+
+fn nodes_by_id(graph: &RailwayGraph) -> HashMap<NodeId, &RailwayNode> {
+    graph
+        .physical_graph
+        .graph
+        .node_weights()
+        .map(|node| (node.id, node))
+        .collect()
+}
+
+fn edges_by_id(graph: &RailwayGraph) -> HashMap<EdgeId, &RailwayEdge> {
+    graph
+        .physical_graph
+        .graph
+        .edge_weights()
+        .map(|edge| (edge.id, edge))
+        .collect()
+}
+
+fn unmatched_edge(edge: &RailwayEdge, kind: ChangeKind) -> EdgeChange {
+    EdgeChange {
+        edge_id: edge.id,
+        kind,
+        path: edge.path.clone(),
+        previous_path: None,
+        length: edge.length,
+        previous_length: None,
+        changed_tags: Vec::new(),
+    }
+}
+
+/// Returns the sorted keys that differ between two tag sets.
+fn changed_keys(old: Option<&Tags>, new: Option<&Tags>) -> Vec<String> {
+    let empty = Tags::new();
+    let (old, new) = (old.unwrap_or(&empty), new.unwrap_or(&empty));
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+fn summarize(nodes: &[NodeChange], edges: &[EdgeChange]) -> DiffSummary {
+    let mut summary = DiffSummary::default();
+    for change in nodes {
+        match change.kind {
+            ChangeKind::Added => summary.added_nodes += 1,
+            ChangeKind::Removed => summary.removed_nodes += 1,
+            ChangeKind::Modified => summary.modified_nodes += 1,
+        }
+    }
+    for change in edges {
+        match change.kind {
+            ChangeKind::Added => {
+                summary.added_edges += 1;
+                summary.added_length += change.length;
+            }
+            ChangeKind::Removed => {
+                summary.removed_edges += 1;
+                summary.removed_length += change.length;
+            }
+            ChangeKind::Modified => {
+                summary.modified_edges += 1;
+                summary.modified_length += change.length_delta();
+            }
+        }
+        summary.net_length += change.length_delta();
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::{loop_elements, node, way};
+    use crate::railway_model::RailwayGraphExt;
+
+    #[test]
+    fn test_diff_identical_graphs() {
+        let graph = from_railway_elements(&loop_elements());
+        let diff = graph.diff(&graph, &DiffOptions::default());
+        assert!(diff.is_empty());
+        assert_eq!(diff.summary, DiffSummary::default());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = from_railway_elements(&loop_elements());
+
+        // Remove way 13, move node 4 with the end of way 10 and add way 14 from 2 to a new node 5.
+        let n1 = (1, 8.00, 50.0);
+        let n2 = (2, 8.02, 50.0);
+        let n3 = (3, 8.01, 50.005);
+        let n4 = (4, 7.98, 50.0);
+        let n5 = (5, 8.03, 50.0);
+        let new = from_railway_elements(&[
+            node(1, n1.1, n1.2),
+            node(3, n3.1, n3.2),
+            node(2, n2.1, n2.2),
+            node(4, n4.1, n4.2),
+            node(5, n5.1, n5.2),
+            way(10, &[n4, n1]),
+            way(11, &[n1, n2]),
+            way(12, &[n1, n3]),
+            way(14, &[n2, n5]),
+        ]);
+        let length = |graph: &RailwayGraph, id| graph.get_edge_by_id(id).unwrap().length;
+
+        let diff = old.diff(&new, &DiffOptions::default());
+        let kinds: Vec<_> = diff
+            .edges
+            .iter()
+            .map(|change| (change.edge_id, change.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (10, ChangeKind::Modified),
+                (13, ChangeKind::Removed),
+                (14, ChangeKind::Added)
+            ]
+        );
+        let modified = &diff.edges[0];
+        assert!(modified.geometry_moved());
+        assert!(modified.length_changed());
+        assert!(!modified.tags_changed());
+        assert_relative_eq!(modified.previous_length.unwrap(), length(&old, 10));
+
+        let nodes: Vec<_> = diff
+            .nodes
+            .iter()
+            .map(|change| (change.node_id, change.kind))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![(4, ChangeKind::Modified), (5, ChangeKind::Added)]
+        );
+        assert!(diff.nodes[0].moved());
+
+        let summary = diff.summary;
+        assert_eq!(
+            (
+                summary.added_edges,
+                summary.removed_edges,
+                summary.modified_edges
+            ),
+            (1, 1, 1)
+        );
+        assert_relative_eq!(summary.added_length, length(&new, 14));
+        assert_relative_eq!(summary.removed_length, length(&old, 13));
+        assert_relative_eq!(summary.modified_length, length(&new, 10) - length(&old, 10));
+        assert_relative_eq!(
+            summary.net_length,
+            summary.added_length - summary.removed_length + summary.modified_length
+        );
+
+        // The reverse diff swaps additions and removals.
+        let reverse = new.diff(&old, &DiffOptions::default());
+        assert_eq!(reverse.summary.added_edges, 1);
+        assert_relative_eq!(reverse.summary.net_length, -summary.net_length);
+    }
+
+    #[test]
+    fn test_diff_with_tags() {
+        let graph = from_railway_elements(&loop_elements());
+        let mut tags = RailwayTags::default();
+        tags.edges
+            .insert(11, [("maxspeed".to_string(), "100".to_string())].into());
+        let mut other_tags = tags.clone();
+        other_tags.edges.insert(
+            11,
+            [
+                ("maxspeed".to_string(), "120".to_string()),
+                ("electrified".to_string(), "contact_line".to_string()),
+            ]
+            .into(),
+        );
+        other_tags
+            .nodes
+            .insert(1, [("railway".to_string(), "switch".to_string())].into());
+
+        let diff = graph.diff_with_tags(&tags, &graph, &other_tags, &DiffOptions::default());
+        assert_eq!(diff.edges.len(), 1);
+        let change = &diff.edges[0];
+        assert_eq!((change.edge_id, change.kind), (11, ChangeKind::Modified));
+        assert_eq!(change.changed_tags, vec!["electrified", "maxspeed"]);
+        assert!(!change.geometry_moved() && !change.length_changed());
+        assert_eq!(diff.nodes.len(), 1);
+        assert_eq!(diff.nodes[0].changed_tags, vec!["railway"]);
+        assert!(!diff.nodes[0].moved());
+        assert_relative_eq!(diff.summary.net_length, 0.0);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uom::si::length::meter;
+
+use crate::types::EdgeId;
+
+use super::{TrackDirection, TrackPosition};
+
+/// A single elevation sample along an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ElevationSample {
+    /// The distance in meters along the edge's geometry from its source node.
+    pub offset: f64,
+    /// The elevation in meters above sea level.
+    pub elevation: f64,
+}
+
+/// The elevation profile of an edge, ordered by offset from its source node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ElevationProfile {
+    /// The elevation samples, ordered by offset.
+    pub samples: Vec<ElevationSample>,
+}
+
+impl ElevationProfile {
+    /// Returns the gradient of every segment between two consecutive samples.
+    ///
+    /// Gradients are given as rise over run in the direction from source to target, so `0.01`
+    /// is an ascent of 10 ‰.
+    pub fn gradients(&self) -> Vec<f64> {
+        self.samples
+            .windows(2)
+            .map(|pair| gradient(&pair[0], &pair[1]))
+            .collect()
+    }
+
+    /// Returns the gradient between the first and the last sample.
+    pub fn average_gradient(&self) -> Option<f64> {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(first), Some(last)) if last.offset > first.offset => Some(gradient(first, last)),
+            _ => None,
+        }
+    }
+
+    /// Returns the elevation at the given offset, interpolated between the samples.
+    pub fn elevation_at(&self, offset: f64) -> Option<f64> {
+        let index = self.segment_index(offset)?;
+        if self.samples.len() == 1 {
+            return Some(self.samples[0].elevation);
+        }
+        let (start, end) = (&self.samples[index], &self.samples[index + 1]);
+        let ratio = ((offset - start.offset) / (end.offset - start.offset)).clamp(0.0, 1.0);
+        Some(start.elevation + ratio * (end.elevation - start.elevation))
+    }
+
+    /// Returns the gradient of the segment containing the given offset.
+    pub fn gradient_at(&self, offset: f64) -> Option<f64> {
+        let index = self.segment_index(offset)?;
+        self.samples
+            .get(index + 1)
+            .map(|end| gradient(&self.samples[index], end))
+    }
+
+    /// Returns the index of the sample starting the segment that contains `offset`.
+    fn segment_index(&self, offset: f64) -> Option<usize> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let last_segment = self.samples.len().saturating_sub(2);
+        Some(
+            self.samples
+                .iter()
+                .skip(1)
+                .position(|sample| offset <= sample.offset)
+                .unwrap_or(last_segment),
+        )
+    }
+}
+
+fn gradient(start: &ElevationSample, end: &ElevationSample) -> f64 {
+    let run = end.offset - start.offset;
+    if run > 0.0 {
+        (end.elevation - start.elevation) / run
+    } else {
+        0.0
+    }
+}
+
+/// The elevation profiles of the edges of a `RailwayGraph`, keyed by edge id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RailwayElevation {
+    /// Elevation profiles of the edges, keyed by edge id.
+    pub edges: HashMap<EdgeId, ElevationProfile>,
+}
+
+impl RailwayElevation {
+    /// Returns the elevation profile of the edge with the given id.
+    pub fn profile(&self, id: EdgeId) -> Option<&ElevationProfile> {
+        self.edges.get(&id)
+    }
+
+    /// Returns the average gradient of the edge with the given id.
+    pub fn average_gradient(&self, id: EdgeId) -> Option<f64> {
+        self.profile(id)
+            .and_then(ElevationProfile::average_gradient)
+    }
+
+    /// Returns the gradient at a position, positive when climbing in its direction of travel.
+    pub fn gradient_at(&self, position: &TrackPosition) -> Option<f64> {
+        let gradient = self
+            .profile(position.edge_id)?
+            .gradient_at(position.offset.get::<meter>())?;
+        Some(match position.direction {
+            TrackDirection::Forward => gradient,
+            TrackDirection::Backward => -gradient,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use uom::si::f64::Length;
+
+    #[test]
+    fn test_elevation_profile() {
+        let profile = ElevationProfile {
+            samples: vec![
+                ElevationSample {
+                    offset: 0.0,
+                    elevation: 100.0,
+                },
+                ElevationSample {
+                    offset: 100.0,
+                    elevation: 101.0,
+                },
+                ElevationSample {
+                    offset: 300.0,
+                    elevation: 99.0,
+                },
+            ],
+        };
+
+        assert_eq!(profile.gradients(), vec![0.01, -0.01]);
+        assert_relative_eq!(profile.average_gradient().unwrap(), -1.0 / 300.0);
+        assert_relative_eq!(profile.elevation_at(50.0).unwrap(), 100.5);
+        assert_relative_eq!(profile.elevation_at(200.0).unwrap(), 100.0);
+        assert_relative_eq!(profile.gradient_at(250.0).unwrap(), -0.01);
+
+        let elevation = RailwayElevation {
+            edges: HashMap::from([(1, profile)]),
+        };
+        let position = TrackPosition::new(1, Length::new::<meter>(50.0), TrackDirection::Backward);
+        assert_relative_eq!(elevation.gradient_at(&position).unwrap(), -0.01);
+        assert_eq!(elevation.average_gradient(2), None);
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use geo::{Coord, LineString};
+use serde::{Deserialize, Serialize};
+use transit_grid::prelude::{TransitNetworkModifier, TransitNetworkRepairer};
+use uom::si::{f64::Length, length::meter};
+
+use crate::algorithms::Distance;
+use crate::types::{EdgeId, NodeId};
+
+use super::{RailwayEdge, RailwayGraph, RailwayNode};
+
+/// Options controlling how two `RailwayGraph`s are merged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergeOptions {
+    /// Nodes of the other graph closer than this to a node of the first graph are treated as the
+    /// same node, even if their ids differ.
+    pub node_tolerance: Length,
+    /// Edges with the same id whose lengths differ by more than this are reported as conflicting.
+    pub length_tolerance: Length,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            node_tolerance: Length::new::<meter>(1.0),
+            length_tolerance: Length::new::<meter>(1.0),
+        }
+    }
+}
+
+/// A conflict found while merging two `RailwayGraph`s.
+///
+/// In every case the data of the first graph is kept and the data of the other graph is dropped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MergeConflict {
+    /// A node exists in both graphs, but at different locations.
+    NodeLocation {
+        /// The id of the node.
+        node_id: NodeId,
+        /// The location in the first graph.
+        location: Coord,
+        /// The location in the other graph.
+        other_location: Coord,
+    },
+    /// An edge exists in both graphs, but with different lengths.
+    EdgeLength {
+        /// The id of the edge.
+        edge_id: EdgeId,
+        /// The length in meters in the first graph.
+        length: f64,
+        /// The length in meters in the other graph.
+        other_length: f64,
+    },
+    /// An edge exists in both graphs, but with different geometries.
+    EdgeGeometry {
+        /// The id of the edge.
+        edge_id: EdgeId,
+    },
+    /// An edge of the other graph would become a self-loop because both of its nodes were
+    /// merged into the same node.
+    CollapsedEdge {
+        /// The id of the edge.
+        edge_id: EdgeId,
+        /// The node both ends were merged into.
+        node_id: NodeId,
+    },
+}
+
+/// The result of merging two `RailwayGraph`s.
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The merged railway graph.
+    pub graph: RailwayGraph,
+    /// Nodes of the other graph that were identified with a node of the first graph by spatial
+    /// tolerance, mapped to the id of that node.
+    pub node_mapping: HashMap<NodeId, NodeId>,
+    /// Edges of the other graph that were dropped because an equal edge already exists.
+    pub duplicate_edges: Vec<EdgeId>,
+    /// Conflicts between the two graphs.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Merging of railway graphs, e.g. neighbouring tiles or OSM data plus a manual overlay.
+pub trait RailwayGraphMerge {
+    /// Merge `other` into a copy of this graph.
+    ///
+    /// Nodes are identified by id first and then by spatial tolerance. Edges are unioned: an edge
+    /// of `other` is dropped if an edge with the same id exists, or if an edge with the same
+    /// (merged) end nodes and the same geometry exists. Differences between edges or nodes sharing
+    /// an id are reported as conflicts. The topology graph of the result is rebuilt from scratch.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The graph to merge into this one.
+    /// * `options` - Tolerances used to identify nodes and to detect conflicts.
+    ///
+    /// # Returns
+    ///
+    /// A `MergeResult` containing the merged graph, the node mapping and all conflicts.
+    fn merge(&self, other: &RailwayGraph, options: &MergeOptions) -> MergeResult;
+}
+
+impl RailwayGraphMerge for RailwayGraph {
+    fn merge(&self, other: &RailwayGraph, options: &MergeOptions) -> MergeResult {
+        let tolerance = options.node_tolerance.get::<meter>();
+        let mut conflicts = Vec::new();
+        let mut node_mapping = HashMap::new();
+        let mut duplicate_edges = Vec::new();
+
+        let nodes: HashMap<NodeId, RailwayNode> = self
+            .physical_graph
+            .graph
+            .node_weights()
+            .map(|node| (node.id, *node))
+            .collect();
+        let index = SpatialIndex::new(nodes.values(), tolerance);
+
+        let mut merged = RailwayGraph::new();
+        for node in self.physical_graph.graph.node_weights() {
+            merged.add_node(*node);
+        }
+
+        // Maps every node id of `other` to the id it has in the merged graph.
+        let mut other_ids = HashMap::new();
+        for node in other.physical_graph.graph.node_weights() {
+            if let Some(existing) = nodes.get(&node.id) {
+                if existing.location.distance(&node.location).get::<meter>() > tolerance {
+                    conflicts.push(MergeConflict::NodeLocation {
+                        node_id: node.id,
+                        location: existing.location,
+                        other_location: node.location,
+                    });
+                }
+                other_ids.insert(node.id, node.id);
+            } else if let Some(existing_id) = index.nearest(node.location) {
+                node_mapping.insert(node.id, existing_id);
+                other_ids.insert(node.id, existing_id);
+            } else {
+                merged.add_node(*node);
+                other_ids.insert(node.id, node.id);
+            }
+        }
+
+        let edges: HashMap<EdgeId, &RailwayEdge> = self
+            .physical_graph
+            .graph
+            .edge_weights()
+            .map(|edge| (edge.id, edge))
+            .collect();
+        let mut edges_by_nodes: HashMap<(NodeId, NodeId), Vec<&RailwayEdge>> = HashMap::new();
+        for edge in self.physical_graph.graph.edge_weights() {
+            edges_by_nodes
+                .entry(node_pair(edge.source, edge.target))
+                .or_default()
+                .push(edge);
+            merged.add_edge(edge.clone());
+        }
+
+        for edge in other.physical_graph.graph.edge_weights() {
+            if let Some(existing) = edges.get(&edge.id) {
+                if (existing.length - edge.length).abs() > options.length_tolerance.get::<meter>() {
+                    conflicts.push(MergeConflict::EdgeLength {
+                        edge_id: edge.id,
+                        length: existing.length,
+                        other_length: edge.length,
+                    });
+                }
+                if !paths_match(&existing.path, &edge.path, tolerance) {
+                    conflicts.push(MergeConflict::EdgeGeometry { edge_id: edge.id });
+                }
+                continue;
+            }
+
+            let (Some(&source), Some(&target)) =
+                (other_ids.get(&edge.source), other_ids.get(&edge.target))
+            else {
+                continue;
+            };
+
+            if source == target && edge.source != edge.target {
+                conflicts.push(MergeConflict::CollapsedEdge {
+                    edge_id: edge.id,
+                    node_id: source,
+                });
+                continue;
+            }
+
+            let is_duplicate =
+                edges_by_nodes
+                    .get(&node_pair(source, target))
+                    .is_some_and(|candidates| {
+                        candidates
+                            .iter()
+                            .any(|candidate| paths_match(&candidate.path, &edge.path, tolerance))
+                    });
+            if is_duplicate {
+                duplicate_edges.push(edge.id);
+                continue;
+            }
+
+            merged.add_edge(RailwayEdge {
+                source,
+                target,
+                ..edge.clone()
+            });
+        }
+
+        merged.repair();
+        merged.repair();
+
+        MergeResult {
+            graph: merged,
+            node_mapping,
+            duplicate_edges,
+            conflicts,
+        }
+    }
+}
+
+fn node_pair(a: NodeId, b: NodeId) -> (NodeId, NodeId) {
+    (a.min(b), a.max(b))
+}
+
+/// Returns `true` if both paths have the same vertices within `tolerance` meters, in the same or
+/// in reversed order.
+pub(crate) fn paths_match(a: &LineString, b: &LineString, tolerance: f64) -> bool {
+    if a.0.len() != b.0.len() {
+        return false;
+    }
+    let within = |x: &Coord, y: &Coord| x.distance(y).get::<meter>() <= tolerance;
+    a.0.iter().zip(b.0.iter()).all(|(x, y)| within(x, y))
+        || a.0.iter().zip(b.0.iter().rev()).all(|(x, y)| within(x, y))
+}
+
+/// A simple grid over node locations to find nodes within a tolerance without comparing all pairs.
+struct SpatialIndex {
+    cell_size: f64,
+    tolerance: f64,
+    cells: HashMap<(i64, i64), Vec<RailwayNode>>,
+}
+
+impl SpatialIndex {
+    fn new<'a>(nodes: impl Iterator<Item = &'a RailwayNode> + Clone, tolerance: f64) -> Self {
+        // One degree of latitude is about 111 km; a degree of longitude shrinks with cos(lat).
+        // Using the widest cell needed anywhere in the graph keeps all candidates in the
+        // neighbouring cells.
+        let max_lat = nodes
+            .clone()
+            .map(|node| node.location.y.abs())
+            .fold(0.0, f64::max)
+            .min(89.0);
+        let cell_size = (tolerance / (111_320.0 * max_lat.to_radians().cos())).max(f64::EPSILON);
+
+        let mut cells: HashMap<(i64, i64), Vec<RailwayNode>> = HashMap::new();
+        for node in nodes {
+            cells
+                .entry(Self::cell(node.location, cell_size))
+                .or_default()
+                .push(*node);
+        }
+        Self {
+            cell_size,
+            tolerance,
+            cells,
+        }
+    }
+
+    fn cell(location: Coord, cell_size: f64) -> (i64, i64) {
+        (
+            (location.x / cell_size).floor() as i64,
+            (location.y / cell_size).floor() as i64,
+        )
+    }
+
+    fn nearest(&self, location: Coord) -> Option<NodeId> {
+        let (cx, cy) = Self::cell(location, self.cell_size);
+        let mut nearest = None;
+        let mut nearest_distance = self.tolerance;
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                for node in self.cells.get(&(x, y)).into_iter().flatten() {
+                    let distance = node.location.distance(&location).get::<meter>();
+                    if distance <= nearest_distance {
+                        nearest = Some(node.id);
+                        nearest_distance = distance;
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::importer::overpass_importer::{
+        from_railway_elements, Coordinate, ElementType, RailwayElement,
+    };
+    use crate::railway_algorithms::{tests::test_elements, PathFinding};
+    use crate::railway_model::RailwayGraphExt;
+
+    fn node(id: i64, lat: f64, lon: f64) -> RailwayElement {
+        RailwayElement {
+            id,
+            element_type: ElementType::Node,
+            lat: Some(lat),
+            lon: Some(lon),
+            tags: Some(HashMap::new()),
+            nodes: None,
+            geometry: None,
+        }
+    }
+
+    fn way(id: i64, nodes: Vec<i64>, geometry: Vec<(f64, f64)>) -> RailwayElement {
+        RailwayElement {
+            id,
+            element_type: ElementType::Way,
+            lat: None,
+            lon: None,
+            tags: Some(HashMap::new()),
+            nodes: Some(nodes),
+            geometry: Some(
+                geometry
+                    .into_iter()
+                    .map(|(lat, lon)| Coordinate { lat, lon })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_merge_with_itself() {
+        let graph = from_railway_elements(&test_elements());
+        let result = graph.merge(&graph, &MergeOptions::default());
+
+        assert_eq!(result.graph.physical_graph.graph.node_count(), 3);
+        assert_eq!(result.graph.physical_graph.graph.edge_count(), 2);
+        assert!(result.node_mapping.is_empty());
+        assert!(result.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_neighbouring_tiles() {
+        let west = from_railway_elements(&[
+            node(1, 50.1109, 8.6821),
+            node(2, 50.1209, 8.6921),
+            way(4, vec![1, 2], vec![(50.1109, 8.6821), (50.1209, 8.6921)]),
+        ]);
+        let east = from_railway_elements(&[
+            node(2, 50.1209, 8.6921),
+            node(3, 50.1309, 8.6721),
+            way(5, vec![2, 3], vec![(50.1209, 8.6921), (50.1309, 8.6721)]),
+        ]);
+
+        let result = west.merge(&east, &MergeOptions::default());
+
+        assert_eq!(result.graph.physical_graph.graph.node_count(), 3);
+        assert_eq!(result.graph.physical_graph.graph.edge_count(), 2);
+        assert_eq!(result.graph.shortest_path_edges(1, 3), Some(vec![4, 5]));
+    }
+
+    #[test]
+    fn test_merge_by_spatial_tolerance() {
+        let graph = from_railway_elements(&test_elements());
+        let overlay = from_railway_elements(&[
+            node(20, 50.1209, 8.6921),
+            node(21, 50.1409, 8.7021),
+            way(22, vec![20, 21], vec![(50.1209, 8.6921), (50.1409, 8.7021)]),
+            // duplicates way 5 under a different id
+            node(23, 50.1309, 8.6721),
+            way(24, vec![20, 23], vec![(50.1209, 8.6921), (50.1309, 8.6721)]),
+        ]);
+
+        let result = graph.merge(&overlay, &MergeOptions::default());
+
+        assert_eq!(result.node_mapping.get(&20), Some(&2));
+        assert_eq!(result.node_mapping.get(&23), Some(&3));
+        assert_eq!(result.duplicate_edges, vec![24]);
+        assert_eq!(result.graph.physical_graph.graph.node_count(), 4);
+        assert_eq!(result.graph.physical_graph.graph.edge_count(), 3);
+        assert!(result.graph.railway_edge(2, 21).is_some());
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let graph = from_railway_elements(&test_elements());
+        let changed = from_railway_elements(&[
+            node(1, 50.1109, 8.6821),
+            node(2, 50.1209, 8.6921),
+            way(
+                4,
+                vec![1, 2],
+                vec![(50.1109, 8.6821), (50.1159, 8.6921), (50.1209, 8.6921)],
+            ),
+        ]);
+
+        let result = graph.merge(&changed, &MergeOptions::default());
+
+        assert_eq!(result.graph.physical_graph.graph.edge_count(), 2);
+        assert!(result
+            .conflicts
+            .contains(&MergeConflict::EdgeGeometry { edge_id: 4 }));
+        assert!(result
+            .conflicts
+            .iter()
+            .any(|conflict| matches!(conflict, MergeConflict::EdgeLength { edge_id: 4, .. })));
+    }
+}
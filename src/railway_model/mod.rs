@@ -2,14 +2,47 @@
 //!
 //! This module provides data structures and functions for working with railway infrastructure data.
 //! It includes the RailwayNode, RailwayEdge, and RailwayGraph structs, as well as a
-//! RailwayGraphBuilder for creating RailwayGraphs from raw data.
+//! RailwayGraphBuilder for creating RailwayGraphs from raw data. Graphs from different sources can
+//! be combined with the `RailwayGraphMerge` trait and simplified with the `RailwayGraphSimplify`
+//! trait, and two snapshots of a network are compared with the `RailwayGraphDiff` trait.
+//! OpenStreetMap tags are kept in a separate `RailwayTags` table. Positions on the network are
+//! expressed as a `TrackPosition`, an edge id with an offset along the edge and a direction of
+//! travel. Elevation profiles and gradients of the edges are kept in a `RailwayElevation` table,
+//! horizontal curvature and curve radii in a `RailwayCurvature` table and speed limits in a
+//! `RailwaySpeedLimits` table. Gauge, electrification, usage, access and axle load limits are kept
+//! in a `RailwayTrackAttributes` table and checked against the `VehicleProfile` of a train,
+//! together with the `DirectionRestriction` of track operated in one direction.
 //!
+mod curvature_profile;
+mod diff;
+mod elevation_profile;
+mod merge;
 mod railway_edge;
 /// A module for working with railway graphs.
 pub mod railway_graph;
 mod railway_node;
+mod railway_tags;
+mod simplify;
+mod speed_profile;
+mod track_attributes;
+mod track_position;
 
+pub use curvature_profile::{CantConfig, CurvatureProfile, CurvatureSample, RailwayCurvature};
+pub use diff::{
+    ChangeKind, DiffOptions, DiffSummary, EdgeChange, GraphDiff, NodeChange, RailwayGraphDiff,
+};
+pub use elevation_profile::{ElevationProfile, ElevationSample, RailwayElevation};
+pub(crate) use merge::paths_match;
+pub use merge::{MergeConflict, MergeOptions, MergeResult, RailwayGraphMerge};
 pub use railway_edge::RailwayEdge;
 pub use railway_graph::RailwayGraph;
 pub use railway_graph::RailwayGraphExt;
 pub use railway_node::RailwayNode;
+pub use railway_tags::{RailwayTags, Tags};
+pub use simplify::{RailwayGraphSimplify, SimplifyOptions, SimplifyResult};
+pub use speed_profile::{RailwaySpeedLimits, SpeedProfile, SpeedSection};
+pub use track_attributes::{
+    DirectionOptions, DirectionRestriction, RailwayTrackAttributes, RoutingConstraints,
+    TrackAttributes, Traction, VehicleProfile,
+};
+pub use track_position::{TrackDirection, TrackPosition};
@@ -1,7 +1,9 @@
 use geo::{coord, Coord};
 use petgraph::visit::IntoNodeReferences;
 use transit_grid::prelude::TransitNetwork;
+use uom::si::length::meter;
 
+use crate::algorithms::Distance;
 use crate::types::{EdgeId, NodeId};
 
 use super::{RailwayEdge, RailwayNode};
@@ -204,7 +206,7 @@ impl RailwayGraphExt for RailwayGraph {
         for node_index in self.physical_graph.graph.node_indices() {
             let node = &self.physical_graph.graph[node_index];
             let coord = node.location;
-            let distance = point_distance(&point_on_edge, &coord);
+            let distance = point_on_edge.distance(&coord).get::<meter>();
 
             if distance < nearest_distance {
                 nearest_node_index = Some(node_index);
@@ -216,12 +218,6 @@ impl RailwayGraphExt for RailwayGraph {
     }
 }
 
-fn point_distance(coord1: &Coord, coord2: &Coord) -> f64 {
-    let dx = coord1.x - coord2.x;
-    let dy = coord1.y - coord2.y;
-    (dx * dx + dy * dy).sqrt()
-}
-
 #[cfg(test)]
 mod tests {
     use crate::{
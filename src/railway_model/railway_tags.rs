@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{EdgeId, NodeId};
+
+/// The OpenStreetMap tags of a single element.
+pub type Tags = HashMap<String, String>;
+
+/// The OpenStreetMap tags of the nodes and edges of a `RailwayGraph`.
+///
+/// `RailwayNode` and `RailwayEdge` only carry ids and geometry, so attributes such as
+/// `railway=switch` or `maxspeed` are kept in this table, keyed by node and edge id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RailwayTags {
+    /// Tags of the nodes, keyed by node id.
+    pub nodes: HashMap<NodeId, Tags>,
+    /// Tags of the edges, keyed by edge id.
+    pub edges: HashMap<EdgeId, Tags>,
+}
+
+impl RailwayTags {
+    /// Returns the tags of the node with the given id, if it has any.
+    pub fn node_tags(&self, id: NodeId) -> Option<&Tags> {
+        self.nodes.get(&id)
+    }
+
+    /// Returns the tags of the edge with the given id, if it has any.
+    pub fn edge_tags(&self, id: EdgeId) -> Option<&Tags> {
+        self.edges.get(&id)
+    }
+
+    /// Returns the value of `key` on the node with the given id.
+    pub fn node_tag(&self, id: NodeId, key: &str) -> Option<&str> {
+        self.nodes
+            .get(&id)
+            .and_then(|tags| tags.get(key))
+            .map(String::as_str)
+    }
+
+    /// Returns the value of `key` on the edge with the given id.
+    pub fn edge_tag(&self, id: EdgeId, key: &str) -> Option<&str> {
+        self.edges
+            .get(&id)
+            .and_then(|tags| tags.get(key))
+            .map(String::as_str)
+    }
+
+    /// Returns `true` if the node has a `railway` tag, e.g. a switch, signal or station.
+    pub fn is_railway_node(&self, id: NodeId) -> bool {
+        self.node_tag(id, "railway").is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_railway_tags() {
+        let mut tags = RailwayTags::default();
+        tags.nodes.insert(
+            1,
+            Tags::from([("railway".to_string(), "switch".to_string())]),
+        );
+        tags.edges
+            .insert(2, Tags::from([("maxspeed".to_string(), "80".to_string())]));
+
+        assert_eq!(tags.node_tag(1, "railway"), Some("switch"));
+        assert_eq!(tags.node_tag(2, "railway"), None);
+        assert_eq!(tags.edge_tag(2, "maxspeed"), Some("80"));
+        assert!(tags.is_railway_node(1));
+        assert!(!tags.is_railway_node(3));
+    }
+}
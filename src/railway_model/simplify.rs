@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+
+use geo::{Coord, LineString};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use transit_grid::prelude::{TransitNetworkModifier, TransitNetworkRepairer};
+
+use crate::types::{EdgeId, NodeId};
+
+use super::{RailwayEdge, RailwayGraph, RailwayTags};
+
+/// Options for simplifying a `RailwayGraph`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimplifyOptions {
+    /// Nodes that are never contracted, even if they have exactly two edges.
+    pub keep_nodes: HashSet<NodeId>,
+}
+
+impl SimplifyOptions {
+    /// Keeps all nodes that carry a `railway` tag, such as stations, signals or level crossings.
+    pub fn with_tagged_nodes(mut self, tags: &RailwayTags) -> Self {
+        self.keep_nodes.extend(
+            tags.nodes
+                .keys()
+                .filter(|id| tags.is_railway_node(**id))
+                .copied(),
+        );
+        self
+    }
+}
+
+/// The result of simplifying a `RailwayGraph`.
+#[derive(Debug, Clone)]
+pub struct SimplifyResult {
+    /// The simplified railway graph.
+    pub graph: RailwayGraph,
+    /// Maps every edge id of the original graph to the id of the edge it became part of.
+    pub edge_mapping: HashMap<EdgeId, EdgeId>,
+    /// Maps every contracted node id of the original graph to the id of the edge it lies on.
+    pub node_mapping: HashMap<NodeId, EdgeId>,
+}
+
+impl SimplifyResult {
+    /// Maps a path of original edge ids onto the simplified graph, dropping consecutive repeats.
+    pub fn map_edges(&self, edges: &[EdgeId]) -> Vec<EdgeId> {
+        let mut mapped: Vec<EdgeId> = edges
+            .iter()
+            .filter_map(|id| self.edge_mapping.get(id).copied())
+            .collect();
+        mapped.dedup();
+        mapped
+    }
+}
+
+/// Simplification of railway graphs.
+pub trait RailwayGraphSimplify {
+    /// Contract chains of degree-2 nodes into single edges.
+    ///
+    /// Nodes with exactly two edges that are neither switches nor ends of a track are removed.
+    /// The edges of a chain are joined into one edge: their geometries are concatenated, their
+    /// lengths are summed and the joined edge keeps the id of the first edge of the chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Nodes to keep regardless of their degree.
+    ///
+    /// # Returns
+    ///
+    /// A `SimplifyResult` with the simplified graph and a mapping from the original ids.
+    fn simplify(&self, options: &SimplifyOptions) -> SimplifyResult;
+}
+
+impl RailwayGraphSimplify for RailwayGraph {
+    fn simplify(&self, options: &SimplifyOptions) -> SimplifyResult {
+        let graph = &self.physical_graph.graph;
+
+        let is_contractible = |index: NodeIndex| {
+            let node = &graph[index];
+            let edges: Vec<_> = graph.edges(index).collect();
+            !options.keep_nodes.contains(&node.id)
+                && edges.len() == 2
+                && edges.iter().all(|edge| edge.source() != edge.target())
+        };
+
+        let mut anchors: Vec<NodeIndex> = graph
+            .node_indices()
+            .filter(|index| !is_contractible(*index))
+            .collect();
+        let mut visited: HashSet<EdgeIndex> = HashSet::new();
+        let mut chains = Vec::new();
+
+        let mut next = 0;
+        loop {
+            while next < anchors.len() {
+                let anchor = anchors[next];
+                let edges: Vec<_> = graph.edges(anchor).map(|edge| edge.id()).collect();
+                for edge in edges {
+                    if !visited.contains(&edge) {
+                        chains.push(walk_chain(self, anchor, edge, &mut visited, |index| {
+                            index == anchor || !is_contractible(index)
+                        }));
+                    }
+                }
+                next += 1;
+            }
+            // Whatever is left are closed loops of contractible nodes, keep one node of each.
+            match graph
+                .edge_indices()
+                .find(|edge| !visited.contains(edge))
+                .and_then(|edge| graph.edge_endpoints(edge))
+            {
+                Some((index, _)) => anchors.push(index),
+                None => break,
+            }
+        }
+
+        let mut simplified = RailwayGraph::new();
+        for anchor in &anchors {
+            simplified.add_node(graph[*anchor]);
+        }
+
+        let mut edge_mapping = HashMap::new();
+        let mut node_mapping = HashMap::new();
+        for chain in chains {
+            let edge = join_chain(self, &chain);
+            for (index, from) in &chain {
+                edge_mapping.insert(graph[*index].id, edge.id);
+                if *from != chain[0].1 {
+                    node_mapping.insert(graph[*from].id, edge.id);
+                }
+            }
+            simplified.add_edge(edge);
+        }
+
+        simplified.repair();
+        simplified.repair();
+
+        SimplifyResult {
+            graph: simplified,
+            edge_mapping,
+            node_mapping,
+        }
+    }
+}
+
+/// Follows a chain of edges from `start` until a node accepted by `is_end` is reached.
+///
+/// Returns the edges of the chain together with the node each edge is entered from.
+fn walk_chain(
+    network: &RailwayGraph,
+    start: NodeIndex,
+    first_edge: EdgeIndex,
+    visited: &mut HashSet<EdgeIndex>,
+    is_end: impl Fn(NodeIndex) -> bool,
+) -> Vec<(EdgeIndex, NodeIndex)> {
+    let graph = &network.physical_graph.graph;
+    let mut chain = Vec::new();
+    let mut from = start;
+    let mut edge = first_edge;
+    loop {
+        visited.insert(edge);
+        chain.push((edge, from));
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        let to = if a == from { b } else { a };
+        if is_end(to) {
+            return chain;
+        }
+        match graph
+            .edges(to)
+            .map(|next| next.id())
+            .find(|next| *next != edge && !visited.contains(next))
+        {
+            Some(next) => {
+                from = to;
+                edge = next;
+            }
+            None => return chain,
+        }
+    }
+}
+
+/// Joins the edges of a chain into a single edge running from the first to the last node.
+fn join_chain(network: &RailwayGraph, chain: &[(EdgeIndex, NodeIndex)]) -> RailwayEdge {
+    let graph = &network.physical_graph.graph;
+    let first = &graph[chain[0].0];
+    let source = graph[chain[0].1].id;
+    let mut target = source;
+    let mut length = 0.0;
+    let mut coords: Vec<Coord> = Vec::new();
+
+    for (index, from) in chain {
+        let edge = &graph[*index];
+        let from_id = graph[*from].id;
+        let forward = edge.source == from_id;
+        target = if forward { edge.target } else { edge.source };
+        length += edge.length;
+
+        let path: Box<dyn Iterator<Item = &Coord>> = if forward {
+            Box::new(edge.path.0.iter())
+        } else {
+            Box::new(edge.path.0.iter().rev())
+        };
+        for coord in path {
+            if coords.last() != Some(coord) {
+                coords.push(*coord);
+            }
+        }
+    }
+
+    RailwayEdge {
+        id: first.id,
+        length,
+        path: LineString::from(coords),
+        source,
+        target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::test_elements;
+    use crate::railway_model::{RailwayGraphExt, Tags};
+    use crate::tests::test_graph_vilbel;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_simplify_chain() {
+        let graph = from_railway_elements(&test_elements());
+        let result = graph.simplify(&SimplifyOptions::default());
+
+        assert_eq!(result.graph.physical_graph.graph.node_count(), 2);
+        assert_eq!(result.graph.physical_graph.graph.edge_count(), 1);
+
+        let edge = result.graph.railway_edge(1, 3).unwrap();
+        assert_eq!(edge.id, 4);
+        assert_eq!(edge.path.0.len(), 3);
+        assert_relative_eq!(edge.length, graph.total_length());
+
+        assert_eq!(result.edge_mapping.get(&5), Some(&4));
+        assert_eq!(result.node_mapping.get(&2), Some(&4));
+        assert_eq!(result.map_edges(&[4, 5]), vec![4]);
+    }
+
+    #[test]
+    fn test_simplify_keeps_tagged_nodes() {
+        let graph = from_railway_elements(&test_elements());
+        let mut tags = RailwayTags::default();
+        tags.nodes.insert(
+            2,
+            Tags::from([("railway".to_string(), "signal".to_string())]),
+        );
+
+        let result = graph.simplify(&SimplifyOptions::default().with_tagged_nodes(&tags));
+
+        assert_eq!(result.graph.physical_graph.graph.node_count(), 3);
+        assert_eq!(result.graph.physical_graph.graph.edge_count(), 2);
+        assert!(result.node_mapping.is_empty());
+    }
+
+    #[test]
+    fn test_simplify_vilbel() {
+        let graph = test_graph_vilbel();
+        let result = graph.simplify(&SimplifyOptions::default());
+
+        assert!(
+            result.graph.physical_graph.graph.node_count()
+                < graph.physical_graph.graph.node_count()
+        );
+        assert_relative_eq!(
+            result.graph.total_length(),
+            graph.total_length(),
+            epsilon = 0.001
+        );
+        assert_eq!(
+            result.edge_mapping.len(),
+            graph.physical_graph.graph.edge_count()
+        );
+    }
+}
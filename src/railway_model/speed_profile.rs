@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uom::si::{f64::Velocity, length::meter, velocity::kilometer_per_hour};
+
+use crate::types::EdgeId;
+
+use super::{TrackDirection, TrackPosition};
+
+/// A section of an edge with a constant speed limit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpeedSection {
+    /// The distance in meters along the edge's geometry from its source node where the section
+    /// starts.
+    pub start: f64,
+    /// The distance in meters along the edge's geometry from its source node where the section
+    /// ends.
+    pub end: f64,
+    /// The speed limit in km/h, the unit of the OpenStreetMap `maxspeed` tag.
+    pub limit: f64,
+}
+
+impl SpeedSection {
+    /// Returns the speed limit of the section.
+    pub fn speed(&self) -> Velocity {
+        Velocity::new::<kilometer_per_hour>(self.limit)
+    }
+
+    /// Returns `true` if the section contains the given offset.
+    pub fn contains(&self, offset: f64) -> bool {
+        (self.start..=self.end).contains(&offset)
+    }
+}
+
+/// The speed limits along an edge for both directions of travel.
+///
+/// The sections of both directions are ordered by offset from the source node. Parts of the edge
+/// not covered by a section have no known limit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeedProfile {
+    /// The sections applying when travelling from the source towards the target node.
+    pub forward: Vec<SpeedSection>,
+    /// The sections applying when travelling from the target towards the source node.
+    pub backward: Vec<SpeedSection>,
+}
+
+impl SpeedProfile {
+    /// Returns the sections applying in the given direction of travel, ordered by offset.
+    pub fn sections(&self, direction: TrackDirection) -> &[SpeedSection] {
+        match direction {
+            TrackDirection::Forward => &self.forward,
+            TrackDirection::Backward => &self.backward,
+        }
+    }
+
+    /// Returns the speed limit at an offset in meters in the given direction of travel.
+    ///
+    /// Where two sections meet, the lower limit applies.
+    pub fn limit_at(&self, offset: f64, direction: TrackDirection) -> Option<Velocity> {
+        self.sections(direction)
+            .iter()
+            .filter(|section| section.contains(offset))
+            .map(|section| section.limit)
+            .min_by(|a, b| a.total_cmp(b))
+            .map(Velocity::new::<kilometer_per_hour>)
+    }
+
+    /// Returns the lowest speed limit on the edge in the given direction of travel.
+    pub fn min_limit(&self, direction: TrackDirection) -> Option<Velocity> {
+        self.sections(direction)
+            .iter()
+            .map(|section| section.limit)
+            .min_by(|a, b| a.total_cmp(b))
+            .map(Velocity::new::<kilometer_per_hour>)
+    }
+}
+
+/// The speed limit profiles of the edges of a `RailwayGraph`, keyed by edge id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RailwaySpeedLimits {
+    /// Speed profiles of the edges, keyed by edge id.
+    pub edges: HashMap<EdgeId, SpeedProfile>,
+}
+
+impl RailwaySpeedLimits {
+    /// Returns the speed profile of the edge with the given id.
+    pub fn profile(&self, id: EdgeId) -> Option<&SpeedProfile> {
+        self.edges.get(&id)
+    }
+
+    /// Returns the speed limit at a position in its direction of travel.
+    pub fn limit_at(&self, position: &TrackPosition) -> Option<Velocity> {
+        self.profile(position.edge_id)?
+            .limit_at(position.offset.get::<meter>(), position.direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use uom::si::f64::Length;
+
+    #[test]
+    fn test_speed_profile() {
+        let profile = SpeedProfile {
+            forward: vec![
+                SpeedSection {
+                    start: 0.0,
+                    end: 100.0,
+                    limit: 120.0,
+                },
+                SpeedSection {
+                    start: 100.0,
+                    end: 200.0,
+                    limit: 80.0,
+                },
+            ],
+            backward: vec![SpeedSection {
+                start: 0.0,
+                end: 200.0,
+                limit: 100.0,
+            }],
+        };
+        let limits = RailwaySpeedLimits {
+            edges: HashMap::from([(1, profile)]),
+        };
+
+        let position =
+            |offset, direction| TrackPosition::new(1, Length::new::<meter>(offset), direction);
+        let limit = |offset, direction| {
+            limits
+                .limit_at(&position(offset, direction))
+                .map(|limit| limit.get::<kilometer_per_hour>())
+        };
+        assert_relative_eq!(limit(50.0, TrackDirection::Forward).unwrap(), 120.0);
+        assert_relative_eq!(limit(100.0, TrackDirection::Forward).unwrap(), 80.0);
+        assert_relative_eq!(limit(150.0, TrackDirection::Backward).unwrap(), 100.0);
+        assert_eq!(limit(250.0, TrackDirection::Forward), None);
+        assert_relative_eq!(
+            limits
+                .profile(1)
+                .unwrap()
+                .min_limit(TrackDirection::Forward)
+                .unwrap()
+                .get::<kilometer_per_hour>(),
+            80.0
+        );
+        assert_eq!(
+            limits.limit_at(&TrackPosition::new(
+                2,
+                Length::new::<meter>(0.0),
+                TrackDirection::Forward
+            )),
+            None
+        );
+    }
+}
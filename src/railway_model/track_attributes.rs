@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::EdgeId;
+
+use super::{RailwayTags, Tags, TrackDirection};
+
+/// The attributes of an edge that decide which vehicles may use it, read from its
+/// OpenStreetMap tags.
+///
+/// Attributes missing from the tags are unknown and do not restrict any vehicle.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackAttributes {
+    /// The track gauges in millimeters from the `gauge` tag, more than one on mixed gauge
+    /// track.
+    pub gauges: Vec<u32>,
+    /// Whether the track is electrified, from the `electrified` tag.
+    pub electrified: Option<bool>,
+    /// The voltages of the electrification in volts from the `voltage` tag.
+    pub voltages: Vec<u32>,
+    /// The `usage` tag, e.g. `main`, `branch` or `industrial`.
+    pub usage: Option<String>,
+    /// The `access` tag, e.g. `no` or `private`.
+    pub access: Option<String>,
+    /// The maximum axle load in tonnes from the `maxaxleload` tag.
+    pub max_axle_load: Option<f64>,
+    /// The direction the track is operated in, from the `oneway` or
+    /// `railway:preferred_direction` tag.
+    pub direction: Option<DirectionRestriction>,
+}
+
+/// A track operated in one direction only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectionRestriction {
+    /// The direction trains run in, relative to the edge from its source to its target node.
+    pub direction: TrackDirection,
+    /// Whether running in the other direction is forbidden, as on `oneway` track, rather than
+    /// only penalised, as against the `railway:preferred_direction`.
+    pub hard: bool,
+}
+
+impl DirectionRestriction {
+    /// Reads the restriction from the `oneway` and `railway:preferred_direction` tags. The
+    /// `oneway` tag takes precedence.
+    pub fn from_tags(tags: &Tags) -> Option<Self> {
+        let restriction = |direction, hard| Some(Self { direction, hard });
+        match tags.get("oneway").map(|value| value.trim()) {
+            Some("yes" | "true" | "1") => return restriction(TrackDirection::Forward, true),
+            Some("-1" | "reverse") => return restriction(TrackDirection::Backward, true),
+            _ => {}
+        }
+        match tags
+            .get("railway:preferred_direction")
+            .map(|value| value.trim())
+        {
+            Some("forward") => restriction(TrackDirection::Forward, false),
+            Some("backward") => restriction(TrackDirection::Backward, false),
+            _ => None,
+        }
+    }
+}
+
+impl TrackAttributes {
+    /// Reads the attributes from the tags of an edge.
+    pub fn from_tags(tags: &Tags) -> Self {
+        let numbers = |key: &str| -> Vec<u32> {
+            tags.get(key)
+                .map(|value| {
+                    value
+                        .split(';')
+                        .filter_map(|part| part.trim().parse().ok())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            gauges: numbers("gauge"),
+            electrified: tags.get("electrified").map(|value| value.trim() != "no"),
+            voltages: numbers("voltage"),
+            usage: tags.get("usage").cloned(),
+            access: tags.get("access").cloned(),
+            max_axle_load: tags
+                .get("maxaxleload")
+                .and_then(|value| value.trim().trim_end_matches('t').trim().parse().ok()),
+            direction: DirectionRestriction::from_tags(tags),
+        }
+    }
+}
+
+/// The attributes of the edges of a `RailwayGraph`, keyed by edge id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RailwayTrackAttributes {
+    /// Attributes of the edges, keyed by edge id.
+    pub edges: HashMap<EdgeId, TrackAttributes>,
+}
+
+impl RailwayTrackAttributes {
+    /// Reads the attributes of all edges with tags.
+    pub fn from_tags(tags: &RailwayTags) -> Self {
+        let edges = tags
+            .edges
+            .iter()
+            .map(|(&id, tags)| (id, TrackAttributes::from_tags(tags)))
+            .collect();
+        Self { edges }
+    }
+
+    /// Returns the attributes of the edge with the given id.
+    pub fn attributes(&self, id: EdgeId) -> Option<&TrackAttributes> {
+        self.edges.get(&id)
+    }
+
+    /// Returns `true` if the vehicle may use the edge with the given id. Edges without
+    /// attributes may be used by any vehicle.
+    pub fn allows(&self, id: EdgeId, vehicle: &VehicleProfile) -> bool {
+        self.attributes(id)
+            .is_none_or(|attributes| vehicle.allows(attributes))
+    }
+}
+
+/// How a vehicle is powered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Traction {
+    /// A self-powered vehicle, e.g. a diesel train, which may use any track.
+    #[default]
+    Diesel,
+    /// A vehicle which may only use electrified track.
+    Electric {
+        /// The voltages in volts the vehicle can run on. Empty if it runs on any voltage.
+        voltages: Vec<u32>,
+    },
+}
+
+/// The properties of a vehicle that restrict the track it may use.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VehicleProfile {
+    /// The track gauge of the vehicle in millimeters, `None` if it runs on any gauge.
+    pub gauge: Option<u32>,
+    /// How the vehicle is powered.
+    pub traction: Traction,
+    /// The `usage` values of the track the vehicle may use, e.g. `main` and `branch`. Empty
+    /// if it may use track of any usage.
+    pub usages: Vec<String>,
+    /// Whether the vehicle may use track with `access=private`, such as sidings of
+    /// industrial sites.
+    pub private_access: bool,
+    /// The axle load of the vehicle in tonnes, `None` if it is not restricted.
+    pub axle_load: Option<f64>,
+}
+
+impl VehicleProfile {
+    /// Returns `true` if the vehicle may use track with the given attributes.
+    pub fn allows(&self, track: &TrackAttributes) -> bool {
+        let gauge = self
+            .gauge
+            .is_none_or(|gauge| track.gauges.is_empty() || track.gauges.contains(&gauge));
+        let traction = match &self.traction {
+            Traction::Diesel => true,
+            Traction::Electric { voltages } => {
+                track.electrified != Some(false)
+                    && (voltages.is_empty()
+                        || track.voltages.is_empty()
+                        || track
+                            .voltages
+                            .iter()
+                            .any(|voltage| voltages.contains(voltage)))
+            }
+        };
+        let usage = self.usages.is_empty()
+            || track
+                .usage
+                .as_ref()
+                .is_none_or(|usage| self.usages.contains(usage));
+        let access = match track.access.as_deref() {
+            Some("no") => false,
+            Some("private") => self.private_access,
+            _ => true,
+        };
+        let axle_load = match (self.axle_load, track.max_axle_load) {
+            (Some(axle_load), Some(max_axle_load)) => axle_load <= max_axle_load,
+            _ => true,
+        };
+        gauge && traction && usage && access && axle_load
+    }
+}
+
+/// How routes treat track operated in one direction.
+///
+/// The factors should be at least 1, as the routes are searched with the straight-line
+/// distance as a lower bound of their cost.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DirectionOptions {
+    /// The factor applied to the length of track run against its
+    /// `railway:preferred_direction`.
+    pub against_preferred_direction: f64,
+    /// The factor applied to the length of `oneway` track run in the wrong direction, or
+    /// `None` if wrong-line running is not allowed.
+    pub wrong_line: Option<f64>,
+}
+
+impl Default for DirectionOptions {
+    fn default() -> Self {
+        Self {
+            against_preferred_direction: 2.0,
+            wrong_line: None,
+        }
+    }
+}
+
+/// The attributes of the track together with the vehicle routed on it and how direction
+/// restrictions are treated.
+#[derive(Debug, Clone, Copy)]
+pub struct RoutingConstraints<'a> {
+    /// The attributes of the edges.
+    pub attributes: &'a RailwayTrackAttributes,
+    /// The vehicle to route, or `None` if only the direction restrictions apply.
+    pub vehicle: Option<&'a VehicleProfile>,
+    /// How track operated in one direction is treated.
+    pub directions: DirectionOptions,
+}
+
+impl<'a> RoutingConstraints<'a> {
+    /// Creates constraints applying the direction restrictions of the edges with the default
+    /// `DirectionOptions`, for any vehicle.
+    pub fn new(attributes: &'a RailwayTrackAttributes) -> Self {
+        Self {
+            attributes,
+            vehicle: None,
+            directions: DirectionOptions::default(),
+        }
+    }
+
+    /// Returns `true` if the vehicle may use the edge with the given id in some direction.
+    pub fn allows(&self, id: EdgeId) -> bool {
+        self.vehicle
+            .is_none_or(|vehicle| self.attributes.allows(id, vehicle))
+    }
+
+    /// Returns the cost of running along an edge in a direction, or `None` if it may not be
+    /// used in that direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the edge.
+    /// * `direction` - The direction of travel along the edge.
+    /// * `length` - The length of the edge in meters.
+    pub fn cost(&self, id: EdgeId, direction: TrackDirection, length: f64) -> Option<f64> {
+        let Some(attributes) = self.attributes.attributes(id) else {
+            return Some(length);
+        };
+        if self
+            .vehicle
+            .is_some_and(|vehicle| !vehicle.allows(attributes))
+        {
+            return None;
+        }
+        match attributes.direction {
+            Some(restriction) if restriction.direction != direction => {
+                let factor = if restriction.hard {
+                    self.directions.wrong_line?
+                } else {
+                    self.directions.against_preferred_direction
+                };
+                Some(length * factor)
+            }
+            _ => Some(length),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> Tags {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_track_attributes_from_tags() {
+        let attributes = TrackAttributes::from_tags(&tags(&[
+            ("gauge", "1435;1000"),
+            ("electrified", "contact_line"),
+            ("voltage", "15000"),
+            ("usage", "main"),
+            ("maxaxleload", "22.5 t"),
+        ]));
+        assert_eq!(attributes.gauges, vec![1435, 1000]);
+        assert_eq!(attributes.electrified, Some(true));
+        assert_eq!(attributes.voltages, vec![15000]);
+        assert_eq!(attributes.usage.as_deref(), Some("main"));
+        assert_eq!(attributes.access, None);
+        assert_eq!(attributes.max_axle_load, Some(22.5));
+
+        let attributes = TrackAttributes::from_tags(&tags(&[("electrified", "no")]));
+        assert_eq!(attributes.electrified, Some(false));
+        assert!(attributes.gauges.is_empty());
+    }
+
+    #[test]
+    fn test_railway_track_attributes_from_tags() {
+        use crate::prelude::OverpassImporter;
+        use serde_json::json;
+
+        let json_value = json!({
+            "elements": [
+                {
+                    "type": "way",
+                    "id": 1,
+                    "nodes": [1, 2],
+                    "tags": {
+                        "railway": "rail",
+                        "gauge": "1435",
+                        "oneway": "yes"
+                    }
+                },
+                {
+                    "type": "way",
+                    "id": 2,
+                    "nodes": [2, 3],
+                    "tags": {
+                        "railway": "rail",
+                        "railway:preferred_direction": "backward"
+                    }
+                },
+                {
+                    "type": "way",
+                    "id": 3,
+                    "nodes": [3, 4]
+                }
+            ]
+        });
+
+        let attributes =
+            RailwayTrackAttributes::from_tags(&OverpassImporter::import_tags(&json_value).unwrap());
+        assert_eq!(attributes.edges.len(), 2);
+        let oneway = attributes.attributes(1).unwrap();
+        assert_eq!(oneway.gauges, vec![1435]);
+        assert_eq!(
+            oneway.direction,
+            Some(DirectionRestriction {
+                direction: TrackDirection::Forward,
+                hard: true
+            })
+        );
+        assert_eq!(
+            attributes.attributes(2).unwrap().direction,
+            Some(DirectionRestriction {
+                direction: TrackDirection::Backward,
+                hard: false
+            })
+        );
+        assert!(attributes.attributes(3).is_none());
+
+        let narrow_gauge = VehicleProfile {
+            gauge: Some(1000),
+            ..Default::default()
+        };
+        assert!(!attributes.allows(1, &narrow_gauge));
+        assert!(attributes.allows(3, &narrow_gauge));
+    }
+
+    #[test]
+    fn test_vehicle_profile_allows() {
+        let main_line = TrackAttributes::from_tags(&tags(&[
+            ("gauge", "1435"),
+            ("electrified", "contact_line"),
+            ("voltage", "15000"),
+            ("usage", "main"),
+            ("maxaxleload", "22.5"),
+        ]));
+        let metre_gauge =
+            TrackAttributes::from_tags(&tags(&[("gauge", "1000"), ("electrified", "no")]));
+        let private_siding =
+            TrackAttributes::from_tags(&tags(&[("usage", "industrial"), ("access", "private")]));
+
+        let diesel = VehicleProfile {
+            gauge: Some(1435),
+            ..Default::default()
+        };
+        assert!(diesel.allows(&main_line));
+        assert!(!diesel.allows(&metre_gauge));
+        assert!(!diesel.allows(&private_siding));
+        assert!(diesel.allows(&TrackAttributes::default()));
+
+        let electric = VehicleProfile {
+            traction: Traction::Electric {
+                voltages: vec![15000],
+            },
+            ..Default::default()
+        };
+        assert!(electric.allows(&main_line));
+        assert!(!electric.allows(&metre_gauge));
+        let ac_only = VehicleProfile {
+            traction: Traction::Electric {
+                voltages: vec![25000],
+            },
+            ..Default::default()
+        };
+        assert!(!ac_only.allows(&main_line));
+
+        let freight = VehicleProfile {
+            usages: vec!["main".to_string(), "industrial".to_string()],
+            private_access: true,
+            axle_load: Some(25.0),
+            ..Default::default()
+        };
+        assert!(!freight.allows(&main_line));
+        assert!(freight.allows(&private_siding));
+
+        let branch_only = VehicleProfile {
+            usages: vec!["branch".to_string()],
+            ..Default::default()
+        };
+        assert!(!branch_only.allows(&main_line));
+    }
+
+    #[test]
+    fn test_direction_restrictions() {
+        let oneway = TrackAttributes::from_tags(&tags(&[
+            ("oneway", "-1"),
+            ("railway:preferred_direction", "forward"),
+        ]));
+        let preferred =
+            TrackAttributes::from_tags(&tags(&[("railway:preferred_direction", "forward")]));
+        let both = TrackAttributes::from_tags(&tags(&[("railway:preferred_direction", "both")]));
+        assert_eq!(
+            oneway.direction,
+            Some(DirectionRestriction {
+                direction: TrackDirection::Backward,
+                hard: true
+            })
+        );
+        assert_eq!(
+            preferred.direction,
+            Some(DirectionRestriction {
+                direction: TrackDirection::Forward,
+                hard: false
+            })
+        );
+        assert_eq!(both.direction, None);
+
+        let attributes = RailwayTrackAttributes {
+            edges: HashMap::from([(1, oneway), (2, preferred)]),
+        };
+        let constraints = RoutingConstraints::new(&attributes);
+        assert_eq!(
+            constraints.cost(1, TrackDirection::Backward, 100.0),
+            Some(100.0)
+        );
+        assert_eq!(constraints.cost(1, TrackDirection::Forward, 100.0), None);
+        assert_eq!(
+            constraints.cost(2, TrackDirection::Forward, 100.0),
+            Some(100.0)
+        );
+        assert_eq!(
+            constraints.cost(2, TrackDirection::Backward, 100.0),
+            Some(200.0)
+        );
+        assert_eq!(
+            constraints.cost(3, TrackDirection::Backward, 100.0),
+            Some(100.0)
+        );
+
+        let wrong_line = RoutingConstraints {
+            directions: DirectionOptions {
+                wrong_line: Some(3.0),
+                ..Default::default()
+            },
+            ..constraints
+        };
+        assert_eq!(
+            wrong_line.cost(1, TrackDirection::Forward, 100.0),
+            Some(300.0)
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use uom::si::f64::Length;
+
+use crate::types::EdgeId;
+
+/// The direction of travel along an edge, relative to the orientation of its geometry.
+///
+/// `Forward` runs from the edge's source node towards its target node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TrackDirection {
+    /// Travelling from the source towards the target node.
+    #[default]
+    Forward,
+    /// Travelling from the target towards the source node.
+    Backward,
+}
+
+impl TrackDirection {
+    /// Returns the opposite direction.
+    pub fn reversed(self) -> Self {
+        match self {
+            TrackDirection::Forward => TrackDirection::Backward,
+            TrackDirection::Backward => TrackDirection::Forward,
+        }
+    }
+}
+
+/// A linear reference on the railway network.
+///
+/// The position is given by the edge it lies on and the offset (chainage) measured along the
+/// edge's geometry from its source node, together with the direction of travel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPosition {
+    /// The id of the edge the position lies on.
+    pub edge_id: EdgeId,
+    /// The distance along the edge's geometry from its source node.
+    pub offset: Length,
+    /// The direction of travel along the edge.
+    pub direction: TrackDirection,
+}
+
+impl TrackPosition {
+    /// Creates a new position on the given edge.
+    pub fn new(edge_id: EdgeId, offset: Length, direction: TrackDirection) -> Self {
+        Self {
+            edge_id,
+            offset,
+            direction,
+        }
+    }
+
+    /// Returns the same position facing the opposite direction.
+    pub fn reversed(&self) -> Self {
+        Self {
+            direction: self.direction.reversed(),
+            ..*self
+        }
+    }
+}
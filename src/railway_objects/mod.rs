@@ -4,6 +4,7 @@ use geo::Coord;
 use std::any::Any;
 use std::collections::VecDeque;
 mod train;
+use crate::prelude::{TrackPosition, VehicleProfile};
 use crate::types::{NodeId, RailwayObjectId};
 pub use train::Train;
 use uom::si::f64::{Acceleration, Velocity};
@@ -38,6 +39,29 @@ pub trait RailwayObject: std::fmt::Debug + Any {
     ///
     fn set_position(&mut self, position: Option<NodeId>);
 
+    /// Returns the position of the railway object on the track.
+    ///
+    /// # Returns
+    ///
+    /// An `Option<TrackPosition>` with the edge, offset and direction of the object. Returns
+    /// `None` if the object is standing at the node given by `position`. The default
+    /// implementation always returns `None`.
+    ///
+    fn track_position(&self) -> Option<TrackPosition> {
+        None
+    }
+
+    /// Sets the position of the railway object on the track.
+    ///
+    /// # Arguments
+    ///
+    /// * `track_position` - An `Option<TrackPosition>` representing the new position on the
+    ///   track. Pass `None` if the object is standing at a node.
+    ///
+    /// The default implementation ignores the track position.
+    ///
+    fn set_track_position(&mut self, _track_position: Option<TrackPosition>) {}
+
     /// Returns a reference to the `Any` trait for this object.
     ///
     /// This method is useful for downcasting the object to a concrete type
@@ -109,4 +133,10 @@ pub trait Movable {
 
     /// Sets the acceleration of the object as an Acceleration.
     fn set_acceleration(&mut self, acceleration: Acceleration);
+
+    /// Returns the vehicle profile restricting the track the object may use, or `None` if it
+    /// may use any track.
+    fn vehicle_profile(&self) -> Option<&VehicleProfile> {
+        None
+    }
 }
@@ -1,4 +1,5 @@
 use super::{GeoLocation, Movable, MultipleTargets, NextTarget, RailwayObject};
+use crate::prelude::{TrackPosition, VehicleProfile};
 use crate::types::{NodeId, RailwayObjectId};
 use geo::Coord;
 use std::any::Any;
@@ -12,6 +13,8 @@ pub struct Train {
     pub id: i64,
     /// The current position of the train, represented by a node ID.
     pub position: Option<NodeId>,
+    /// The position of the train on the track, `None` while standing at `position`.
+    pub track_position: Option<TrackPosition>,
     /// The geographical location of the train, represented by a coordinate.
     pub geo_location: Option<Coord<f64>>,
     /// The next target node ID for the train to move towards.
@@ -24,6 +27,9 @@ pub struct Train {
     pub max_speed: Velocity,
     /// The current acceleration of the train
     pub acceleration: Acceleration,
+    /// The gauge, traction and axle load of the train, restricting the track it may use.
+    /// `None` if it may use any track.
+    pub vehicle_profile: Option<VehicleProfile>,
 }
 
 /// Implements the `RailwayObject` trait for the `Train` struct.
@@ -40,6 +46,14 @@ impl RailwayObject for Train {
         self.position = position;
     }
 
+    fn track_position(&self) -> Option<TrackPosition> {
+        self.track_position
+    }
+
+    fn set_track_position(&mut self, track_position: Option<TrackPosition>) {
+        self.track_position = track_position;
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -110,6 +124,10 @@ impl Movable for Train {
     fn set_acceleration(&mut self, acceleration: Acceleration) {
         self.acceleration = acceleration;
     }
+
+    fn vehicle_profile(&self) -> Option<&VehicleProfile> {
+        self.vehicle_profile.as_ref()
+    }
 }
 
 #[cfg(test)]
@@ -152,12 +170,14 @@ mod tests {
         let mut train = Train {
             id: 1,
             position: Some(0),
+            track_position: None,
             geo_location: Some(coord! { x:1.0, y: 2.0}),
             next_target: Some(2),
             targets: VecDeque::from(vec![2, 3, 4]),
             max_speed: Velocity::new::<kilometer_per_hour>(80.0),
             speed: Velocity::new::<kilometer_per_hour>(0.0),
             acceleration: Acceleration::new::<meter_per_second_squared>(0.0),
+            vehicle_profile: None,
         };
 
         assert_eq!(train.speed(), Velocity::new::<kilometer_per_hour>(0.0));
@@ -171,12 +191,14 @@ mod tests {
         let mut train = Train {
             id: 1,
             position: Some(0),
+            track_position: None,
             geo_location: Some(coord! { x:1.0, y: 2.0}),
             next_target: Some(2),
             targets: VecDeque::from(vec![2, 3, 4]),
             max_speed: Velocity::new::<kilometer_per_hour>(80.0),
             speed: Velocity::new::<kilometer_per_hour>(0.0),
             acceleration: Acceleration::new::<meter_per_second_squared>(0.0),
+            vehicle_profile: None,
         };
 
         assert_eq!(
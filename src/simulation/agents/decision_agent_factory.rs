@@ -63,14 +63,10 @@ impl DecisionAgentFactory {
 mod tests {
     use super::*;
     use crate::railway_model::RailwayGraph;
-    use std::collections::HashMap;
 
     #[test]
     fn test_create_decision_agent() {
-        let environment = SimulationEnvironment {
-            graph: RailwayGraph::default(),
-            objects: HashMap::new(),
-        };
+        let environment = SimulationEnvironment::new(RailwayGraph::default());
         let id = 1;
 
         // Test creation of ForwardUntilTargetAgent
@@ -1,16 +1,29 @@
 use crate::{
-    simulation::{environment::ObservableEnvironment, SimulationEnvironment},
+    railway_algorithms::RailwayGraphSpeedLimits,
+    simulation::{
+        current_track_position, environment::ObservableEnvironment, next_node_towards, Router,
+        SimulationEnvironment,
+    },
     types::{NodeId, RailwayObjectId},
 };
 use std::any::Any;
+use uom::si::{
+    acceleration::meter_per_second_squared,
+    f64::{Acceleration, Length, Velocity},
+};
 
 use super::{DecisionAgent, RailMovableAction};
 
 use std::time::Duration;
 
+/// The deceleration assumed when looking ahead for lower speed limits.
+const BRAKING_DECELERATION: f64 = 1.0;
+
 /// The `ForwardUntilTargetAgent` struct represents a decision agent that moves
 /// a railway object forward until it reaches its next target node.
 ///
+/// The agent brakes when a lower speed limit ahead could not be kept otherwise.
+///
 /// # Type parameters
 ///
 /// * `T`: A type implementing the `RailwayObject`, `Movable`, and `NextTarget` traits.
@@ -19,6 +32,7 @@ pub struct ForwardUntilTargetAgent {
     object_id: RailwayObjectId,
     position: Option<NodeId>,
     target: Option<NodeId>,
+    brake_for_limit: bool,
 }
 
 impl ForwardUntilTargetAgent {
@@ -48,7 +62,7 @@ impl DecisionAgent for ForwardUntilTargetAgent {
     type A = RailMovableAction;
 
     fn next_action(&self, _delta_time: Option<Duration>) -> Self::A {
-        if self.target_reached() {
+        if self.target_reached() || self.brake_for_limit {
             RailMovableAction::Stop
         } else {
             RailMovableAction::AccelerateForward { acceleration: 20 }
@@ -63,6 +77,32 @@ impl DecisionAgent for ForwardUntilTargetAgent {
         {
             self.position = object.position();
             self.target = object.next_target();
+            self.brake_for_limit = false;
+
+            let speed = object.speed();
+            let graph = environment.get_graph();
+            let router = Router::new(environment).with_vehicle(object.vehicle_profile());
+            if let (Some(position), Some(target)) = (
+                current_track_position(router, *object),
+                object.next_target(),
+            ) {
+                let deceleration =
+                    Acceleration::new::<meter_per_second_squared>(BRAKING_DECELERATION);
+                let braking_distance = |limit: Velocity| -> Length {
+                    (speed * speed - limit * limit) / (2.0 * deceleration)
+                };
+                let limits_ahead = graph.speed_limits_ahead(
+                    environment.get_speed_limits(),
+                    &position,
+                    braking_distance(Velocity::default()),
+                    next_node_towards(router, &position, target),
+                );
+                self.brake_for_limit = limits_ahead.iter().any(|ahead| {
+                    ahead.limit.is_some_and(|limit| {
+                        limit < speed && ahead.distance <= braking_distance(limit)
+                    })
+                });
+            }
         }
     }
 
@@ -1,7 +1,16 @@
 //! module with environment traits
 use std::collections::HashMap;
 
-use crate::{prelude::RailwayGraph, types::RailwayObjectId};
+use geo::Coord;
+
+use crate::{
+    prelude::{
+        DirectionOptions, RailwayElevation, RailwayGraph, RailwaySpeedLimits,
+        RailwayTrackAttributes, RoutingConstraints,
+    },
+    railway_algorithms::{EdgeProjection, EdgeSpatialIndex, ReversalOptions, RoutingTable},
+    types::RailwayObjectId,
+};
 
 use super::SimulationObject;
 
@@ -12,6 +21,15 @@ pub trait ObservableEnvironment {
     /// Returns a reference to the railway graph.
     fn get_graph(&self) -> &RailwayGraph;
 
+    /// Returns a reference to the speed limits of the railway graph.
+    fn get_speed_limits(&self) -> &RailwaySpeedLimits;
+
+    /// Returns a reference to the rules for where trains may reverse.
+    fn get_reversal_options(&self) -> &ReversalOptions;
+
+    /// Returns a reference to the gauge, electrification and access attributes of the edges.
+    fn get_track_attributes(&self) -> &RailwayTrackAttributes;
+
     /// Returns a vector of references to the simulation objects.
     fn get_objects(&self) -> Vec<&dyn SimulationObject>;
     /// This function takes a reference to self (which in this case is an ObservableEnvironment struct)
@@ -28,12 +46,160 @@ pub trait ObservableEnvironmentRef {
 }
 
 /// Represents the environment for the decision agent, containing the `RailwayGraph`.
+///
+/// The graph, the reversal options, the track attributes and the direction options are changed
+/// through their `_mut` methods, so that the prepared routing table and the edge index are
+/// rebuilt after a change.
 #[derive(Debug)]
 pub struct SimulationEnvironment {
     /// A reference to the `RailwayGraph` in the environment.
-    pub graph: RailwayGraph,
+    pub(crate) graph: RailwayGraph,
     /// A collection of simulation objects, keyed by their unique identifiers.
     pub objects: HashMap<i64, Box<dyn SimulationObject>>,
+    /// The speed limits of the edges of the `RailwayGraph`, enforced on the simulation objects.
+    pub speed_limits: RailwaySpeedLimits,
+    /// The elevation profiles of the edges of the `RailwayGraph`, whose gradients slow down
+    /// the simulation objects climbing and speed up those descending.
+    pub elevation: RailwayElevation,
+    /// The rules for where the simulation objects may reverse to reach their targets.
+    pub(crate) reversal_options: ReversalOptions,
+    /// The attributes of the edges of the `RailwayGraph`, checked against the vehicle profiles
+    /// of the simulation objects. Their direction restrictions apply to all objects.
+    pub(crate) track_attributes: RailwayTrackAttributes,
+    /// How the simulation objects treat track operated in one direction.
+    pub(crate) direction_options: DirectionOptions,
+    /// The prepared next-hop table used instead of searching the graph for the next node of
+    /// the objects without a vehicle profile, if routing has been prepared with
+    /// `prepare_routing_table`.
+    pub(crate) routing_table: Option<RoutingTable>,
+    /// The grid index over the edges used to place simulation objects on the track, built on
+    /// first use.
+    edge_index: Option<EdgeSpatialIndex>,
+    /// Counts the changes of the graph, the reversal options, the track attributes and the
+    /// direction options.
+    generation: u64,
+    /// The `generation` the routing table was prepared for.
+    routing_table_generation: u64,
+}
+
+impl SimulationEnvironment {
+    /// Creates an environment for the given railway graph without simulation objects, speed
+    /// limits, elevation profiles or track attributes and with the default reversal and direction options.
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The railway graph representing the railway infrastructure.
+    ///
+    /// # Returns
+    ///
+    /// A new `SimulationEnvironment` instance.
+    ///
+    pub fn new(graph: RailwayGraph) -> Self {
+        Self {
+            graph,
+            objects: HashMap::new(),
+            speed_limits: RailwaySpeedLimits::default(),
+            elevation: RailwayElevation::default(),
+            reversal_options: ReversalOptions::default(),
+            track_attributes: RailwayTrackAttributes::default(),
+            direction_options: DirectionOptions::default(),
+            routing_table: None,
+            edge_index: None,
+            generation: 0,
+            routing_table_generation: 0,
+        }
+    }
+
+    /// Returns a mutable reference to the railway graph and marks the routing table and the
+    /// edge index as outdated.
+    pub fn graph_mut(&mut self) -> &mut RailwayGraph {
+        self.invalidate();
+        &mut self.graph
+    }
+
+    /// Returns a mutable reference to the reversal options and marks the routing table as
+    /// outdated.
+    pub fn reversal_options_mut(&mut self) -> &mut ReversalOptions {
+        self.invalidate();
+        &mut self.reversal_options
+    }
+
+    /// Returns a mutable reference to the track attributes and marks the routing table as
+    /// outdated.
+    pub fn track_attributes_mut(&mut self) -> &mut RailwayTrackAttributes {
+        self.invalidate();
+        &mut self.track_attributes
+    }
+
+    /// Returns how the simulation objects treat track operated in one direction.
+    pub fn direction_options(&self) -> DirectionOptions {
+        self.direction_options
+    }
+
+    /// Returns a mutable reference to the direction options and marks the routing table as
+    /// outdated.
+    pub fn direction_options_mut(&mut self) -> &mut DirectionOptions {
+        self.invalidate();
+        &mut self.direction_options
+    }
+
+    /// Returns the prepared routing table, if routing has been prepared.
+    pub fn routing_table(&self) -> Option<&RoutingTable> {
+        self.routing_table.as_ref()
+    }
+
+    /// Returns the constraints all simulation objects are routed with: the direction
+    /// restrictions of the track, treated as given by `direction_options`.
+    pub fn routing_constraints(&self) -> RoutingConstraints<'_> {
+        RoutingConstraints {
+            directions: self.direction_options,
+            ..RoutingConstraints::new(&self.track_attributes)
+        }
+    }
+
+    /// Prepares a `RoutingTable` for the graph, the reversal options and the routing
+    /// constraints, so that the simulation objects choose their next nodes without searching
+    /// the graph.
+    pub fn prepare_routing_table(&mut self) {
+        self.routing_table = Some(RoutingTable::with_constraints(
+            &self.graph,
+            &self.reversal_options,
+            self.routing_constraints(),
+        ));
+        self.routing_table_generation = self.generation;
+    }
+
+    /// Records a change of the graph, the reversal options, the track attributes or the
+    /// direction options, so that the edge index is rebuilt on its next use and the routing
+    /// table on the next `refresh_routing_table`.
+    fn invalidate(&mut self) {
+        self.generation += 1;
+        self.edge_index = None;
+    }
+
+    /// Snaps a coordinate onto the closest edge of the graph, using the edge index.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - A `Coord<f64>` near the network.
+    ///
+    /// # Returns
+    ///
+    /// An `EdgeProjection` with the closest edge, or `None` if the graph has no edges.
+    pub fn snap_to_edge(&mut self, location: Coord<f64>) -> Option<EdgeProjection> {
+        self.edge_index
+            .get_or_insert_with(|| EdgeSpatialIndex::new(&self.graph))
+            .closest_edge(location)
+    }
+
+    /// Rebuilds the prepared `RoutingTable` if the graph, the reversal options, the track
+    /// attributes or the direction options have been changed since it was prepared. Does
+    /// nothing if routing has not been prepared.
+    pub fn refresh_routing_table(&mut self) {
+        if self.routing_table.is_some() && self.routing_table_generation != self.generation {
+            self.prepare_routing_table();
+        }
+    }
 }
 
 impl ObservableEnvironment for SimulationEnvironment {
@@ -41,6 +207,18 @@ impl ObservableEnvironment for SimulationEnvironment {
         &self.graph
     }
 
+    fn get_speed_limits(&self) -> &RailwaySpeedLimits {
+        &self.speed_limits
+    }
+
+    fn get_reversal_options(&self) -> &ReversalOptions {
+        &self.reversal_options
+    }
+
+    fn get_track_attributes(&self) -> &RailwayTrackAttributes {
+        &self.track_attributes
+    }
+
     fn get_objects(&self) -> Vec<&dyn SimulationObject> {
         self.objects
             .values()
@@ -68,10 +246,7 @@ mod tests {
     fn test_environment() {
         let graph = test_graph_vilbel();
 
-        let environment = SimulationEnvironment {
-            graph: graph.clone(),
-            objects: HashMap::<i64, Box<dyn SimulationObject>>::default(),
-        };
+        let environment = SimulationEnvironment::new(graph.clone());
 
         assert_eq!(environment.graph, graph);
     }
@@ -80,10 +255,7 @@ mod tests {
     fn test_get_objects() {
         let graph = test_graph_vilbel();
 
-        let mut environment = SimulationEnvironment {
-            graph: graph.clone(),
-            objects: HashMap::<i64, Box<dyn SimulationObject>>::default(),
-        };
+        let mut environment = SimulationEnvironment::new(graph.clone());
         let objects = environment.get_objects();
         assert_eq!(objects.len(), 0);
 
@@ -94,4 +266,44 @@ mod tests {
         let objects = environment.get_objects();
         assert_eq!(objects.len(), 1);
     }
+
+    #[test]
+    fn test_refresh_routing_table() {
+        let mut environment = SimulationEnvironment::new(test_graph_vilbel());
+        environment.refresh_routing_table();
+        assert!(environment.routing_table.is_none());
+
+        environment.prepare_routing_table();
+        environment.reversal_options_mut().reversal_points.insert(1);
+        environment.refresh_routing_table();
+        let table = environment.routing_table().unwrap();
+        assert!(table.is_valid_for(
+            &environment.graph,
+            &environment.reversal_options,
+            Some(environment.routing_constraints())
+        ));
+
+        let location = environment
+            .graph
+            .physical_graph
+            .graph
+            .node_weights()
+            .next()
+            .unwrap()
+            .location;
+        assert!(environment.snap_to_edge(location).is_some());
+        assert!(environment.edge_index.is_some());
+
+        environment.graph_mut();
+        assert!(environment.edge_index.is_none());
+
+        environment.direction_options_mut().wrong_line = Some(1.1);
+        environment.refresh_routing_table();
+        let table = environment.routing_table().unwrap();
+        assert!(table.is_valid_for(
+            &environment.graph,
+            &environment.reversal_options,
+            Some(environment.routing_constraints())
+        ));
+    }
 }
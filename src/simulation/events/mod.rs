@@ -5,9 +5,11 @@
 //!
 //! The module provides a `SimulationEvent` trait that defines the interface for all simulation events. The
 //! `RailMovableEvent` and `TargetReachedEvent` structs are example implementations of this trait, representing a
-//! change in a `RailMovableAction` and a target being reached, respectively.
+//! change in a `RailMovableAction` and a target being reached, respectively. A `TargetQueueEmptyEvent` is emitted
+//! when an object reaches a target without further targets queued.
 
 use crate::simulation::agents::RailMovableAction;
+use crate::types::RailwayObjectId;
 use std::any::Any;
 
 /// The `SimulationEvent` trait defines the interface for all simulation events.
@@ -38,6 +40,19 @@ impl SimulationEvent for TargetReachedEvent {
     }
 }
 
+/// A `TargetQueueEmptyEvent` represents an object reaching its next target with no further
+/// targets queued.
+pub struct TargetQueueEmptyEvent {
+    /// The id of the object that reached its last target.
+    pub object_id: RailwayObjectId,
+}
+
+impl SimulationEvent for TargetQueueEmptyEvent {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,4 +72,11 @@ mod tests {
 
         assert!(event.as_any().is::<TargetReachedEvent>());
     }
+
+    #[test]
+    fn target_queue_empty_event_as_any() {
+        let event = TargetQueueEmptyEvent { object_id: 1 };
+
+        assert!(event.as_any().is::<TargetQueueEmptyEvent>());
+    }
 }
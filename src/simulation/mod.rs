@@ -9,11 +9,16 @@ use self::{
     environment::{ObservableEnvironment, ObservableEnvironmentRef},
 };
 use crate::{
-    algorithms::is_middle_coord_between,
-    prelude::{RailwayGraph, RailwayGraphExt},
-    railway_algorithms::{RailwayEdgeAlgos, RailwayGraphAlgos},
-    railway_objects::{GeoLocation, Movable, NextTarget, RailwayObject, Train},
-    types::RailwayObjectId,
+    prelude::{
+        DirectionOptions, RailwayGraph, RailwayGraphExt, RailwayTrackAttributes,
+        RoutingConstraints, TrackDirection, TrackPosition, VehicleProfile,
+    },
+    railway_algorithms::{
+        DirectionalPathFinding, EdgeProjection, LinearReferencing, PathFinding, RailwayEdgeAlgos,
+        ReversalOptions, RoutingTable, TrackMovement,
+    },
+    railway_objects::{GeoLocation, Movable, MultipleTargets, NextTarget, RailwayObject, Train},
+    types::{NodeId, RailwayObjectId},
 };
 use std::collections::HashMap;
 use std::fmt;
@@ -33,15 +38,19 @@ pub use environment::SimulationEnvironment;
 use rand::prelude::IndexedRandom;
 use uom::si::{
     acceleration::{meter_per_second_squared, Acceleration},
-    f64::{Length, Time},
-    length::meter,
+    f64::{Time, Velocity},
     time::second,
 };
 mod simulation_executor;
-use crate::simulation::events::{RailMovableEvent, SimulationEvent, TargetReachedEvent};
+use crate::simulation::events::{
+    RailMovableEvent, SimulationEvent, TargetQueueEmptyEvent, TargetReachedEvent,
+};
 use crate::simulation::metrics::{ActionCountHandler, MetricsHandler, TargetReachedHandler};
 pub use simulation_executor::SimulationExecutor;
 
+/// Standard gravity in m/s².
+const STANDARD_GRAVITY: f64 = 9.80665;
+
 pub mod commands;
 pub mod events;
 pub mod metrics;
@@ -100,10 +109,7 @@ impl Simulation {
         ];
 
         Self {
-            environment: SimulationEnvironment {
-                graph,
-                objects: HashMap::new(),
-            },
+            environment: SimulationEnvironment::new(graph),
             object_agents: HashMap::new(),
             metrics_handlers: default_metrics_handler,
             elapsed_time: Duration::default(),
@@ -129,6 +135,10 @@ impl Simulation {
 
     /// Adds a moveable railway object to the simulation.
     ///
+    /// An object with a geo location but neither a node nor a track position is snapped onto
+    /// the closest edge, so it starts between two nodes. It faces the direction of the shorter
+    /// route to its next target and its position becomes the node behind it.
+    ///
     /// # Arguments
     ///
     /// * `object` - The moveable railway object to be added to the simulation.
@@ -139,14 +149,20 @@ impl Simulation {
     ///
     pub fn add_object(
         &mut self,
-        object: Box<dyn SimulationObject>,
+        mut object: Box<dyn SimulationObject>,
         agent: Option<Box<dyn DecisionAgent<A = RailMovableAction>>>,
     ) -> bool {
-        if let std::collections::hash_map::Entry::Vacant(e) =
-            self.environment.objects.entry(object.id())
-        {
+        if !self.environment.objects.contains_key(&object.id()) {
             let id = object.id();
-            e.insert(object);
+            if object.position().is_none() && object.track_position().is_none() {
+                if let Some(snapped) = object
+                    .geo_location()
+                    .and_then(|location| self.environment.snap_to_edge(location))
+                {
+                    place_on_track(&self.environment.graph, object.as_mut(), &snapped);
+                }
+            }
+            self.environment.objects.insert(id, object);
 
             if let Some(agent) = agent {
                 self.add_agent_for_object(id, agent);
@@ -208,6 +224,31 @@ impl Simulation {
         self.metrics_handlers.push(handler);
     }
 
+    /// Returns the node an object is heading to.
+    ///
+    /// This is the node ahead on the edge the object is on or, for an object standing at a
+    /// node, the next node towards its target, chosen as for the movement of the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unique identifier of the object.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the node, or `None` if the object does not exist or has nowhere to go.
+    pub fn next_node(&self, id: RailwayObjectId) -> Option<NodeId> {
+        let object = self.environment.objects.get(&id)?;
+        let router = Router::new(&self.environment).with_vehicle(object.vehicle_profile());
+        let position = current_track_position(router, object.as_ref())?;
+        let direction = if object.speed().is_sign_negative() {
+            position.direction.reversed()
+        } else {
+            position.direction
+        };
+        let edge = self.environment.graph.get_edge_by_id(position.edge_id)?;
+        Some(edge.node_ahead(direction))
+    }
+
     /// Handles a simulation event by passing it to all registered metrics handlers.
     ///
     /// This function is called internally by the simulation engine whenever a simulation
@@ -233,6 +274,8 @@ impl Simulation {
     /// * `delta_time` - The elapsed time since the last update.
     pub fn update(&mut self, delta_time: Duration) {
         if !self.is_paused {
+            // Rebuild the prepared routing table if the graph or the routing options have changed.
+            self.environment.refresh_routing_table();
             let int_speedup_factor = self.speedup_factor.floor() as u32;
             let fractional_speedup_factor = self.speedup_factor.fract();
 
@@ -308,11 +351,43 @@ impl Simulation {
 
                 event = Some(RailMovableEvent { action });
 
-                // Update speed based on the acceleration
+                let router = Router::from_parts(
+                    &self.environment.graph,
+                    &self.environment.reversal_options,
+                    &self.environment.track_attributes,
+                    self.environment.direction_options,
+                    self.environment.routing_table.as_ref(),
+                )
+                .with_vehicle(object.vehicle_profile());
+                let track_position = current_track_position(router, object.as_ref());
+
+                // Update speed based on the acceleration and the resistance of the gradient
+                // the object is facing.
+                let gradient = track_position
+                    .as_ref()
+                    .and_then(|position| self.environment.elevation.gradient_at(position))
+                    .unwrap_or(0.0);
+                let gradient_resistance: uom::si::f64::Acceleration =
+                    Acceleration::new::<meter_per_second_squared>(STANDARD_GRAVITY * gradient);
+                let acceleration = object.acceleration() - gradient_resistance;
                 object.set_speed(object.max_speed().min(
-                    object.speed()
-                        + Time::new::<second>(delta_time.as_secs_f64()) * object.acceleration(),
+                    object.speed() + Time::new::<second>(delta_time.as_secs_f64()) * acceleration,
                 ));
+
+                // Enforce the speed limit of the track in the direction of travel.
+                let speed = object.speed();
+                let limit = track_position
+                    .map(|position| {
+                        if speed.is_sign_negative() {
+                            position.reversed()
+                        } else {
+                            position
+                        }
+                    })
+                    .and_then(|position| self.environment.speed_limits.limit_at(&position));
+                if let Some(limit) = limit {
+                    object.set_speed(speed.min(limit).max(-limit));
+                }
             }
         }
         if let Some(event) = event {
@@ -324,75 +399,271 @@ impl Simulation {
     }
 
     fn update_object_position(&mut self, id: RailwayObjectId, delta_time: Duration) {
-        const NEXT_NODE_DISTANCE_TOLERANCE: f64 = 1.0;
         if let Some(object) = self.environment.objects.get_mut(&id) {
-            if let Some(current_position) = object.position() {
-                let current_speed = object.speed();
-                let target = object.next_target();
-                if let Some(current_location) = object.geo_location() {
-                    let graph = &self.environment.graph;
-                    let next_node =
-                        graph.get_next_node(current_position, target.unwrap_or_default());
-
-                    if let Some(next_node_id) = next_node {
-                        let edge = graph
-                            .railway_edge(current_position, next_node_id)
-                            .expect("Invalid edge");
-                        let next_node = graph.get_node_by_id(next_node_id).unwrap();
-
-                        let direction_coord = next_node.location;
-                        let distance_to_travel =
-                            current_speed * Time::new::<second>(delta_time.as_secs_f64());
-
-                        let new_geo_location = edge.position_on_edge(
-                            current_location,
-                            distance_to_travel,
-                            direction_coord,
-                        );
-
-                        let current_node = graph.get_node_by_id(current_position).unwrap();
-
-                        // reached next node
-                        if is_middle_coord_between(
-                            current_node.location,
-                            next_node.location,
-                            new_geo_location,
-                        ) || edge.distance_to_end(new_geo_location, direction_coord)
-                            < Length::new::<meter>(NEXT_NODE_DISTANCE_TOLERANCE)
-                        {
-                            object.set_position(Some(next_node_id));
+            if object.position().is_some() {
+                let vehicle = object.vehicle_profile().cloned();
+                let router = Router::from_parts(
+                    &self.environment.graph,
+                    &self.environment.reversal_options,
+                    &self.environment.track_attributes,
+                    self.environment.direction_options,
+                    self.environment.routing_table.as_ref(),
+                )
+                .with_vehicle(vehicle.as_ref());
+                let graph = router.graph;
+                let target = object.next_target().unwrap_or_default();
+                let track_position = current_track_position(router, object.as_ref());
+
+                if let Some(track_position) = track_position {
+                    let distance_to_travel =
+                        object.speed() * Time::new::<second>(delta_time.as_secs_f64());
+
+                    let movement = if distance_to_travel.is_sign_negative() {
+                        graph
+                            .move_along(&track_position.reversed(), -distance_to_travel, |_| None)
+                            .map(|movement| TrackMovement {
+                                position: movement.position.reversed(),
+                                ..movement
+                            })
+                    } else {
+                        let next_node = next_node_towards(router, &track_position, target);
+                        graph.move_along(&track_position, distance_to_travel, next_node)
+                    };
+
+                    if let Some(movement) = movement {
+                        if let Some(&passed_node) = movement.passed_nodes.last() {
+                            object.set_position(Some(passed_node));
+                        }
+                        if let Some(node_id) = movement.stopped_at {
+                            // Standing at a node, the next update chooses the next edge.
+                            if node_id != target {
+                                // Stopped short of the target to turn back, which trains do
+                                // at standstill.
+                                object.set_speed(Velocity::default());
+                            }
+                            object.set_track_position(None);
+                            object.set_geo_location(
+                                graph.get_node_by_id(node_id).map(|node| node.location),
+                            );
+                        } else {
+                            object.set_track_position(Some(movement.position));
+                            object.set_geo_location(graph.track_coordinate(&movement.position));
                         }
-                        object.set_geo_location(Some(new_geo_location));
                     } else {
-                        warn!("object {} has no next node, unsetting target", object.id());
-                        object.set_next_target(None);
+                        warn!("object {} is on an unknown edge", object.id());
+                        object.set_track_position(None);
                     }
                 } else {
-                    warn!("object {} has no coordinates", object.id())
+                    warn!("object {} has no next node, unsetting target", object.id());
+                    object.set_next_target(None);
                 }
             }
         }
     }
 
+    /// Advances a train to its next queued target once it has reached its current one.
+    ///
+    /// Trains without queued targets get a random target they can reach with their vehicle
+    /// profile, and a
+    /// `TargetQueueEmptyEvent` is emitted when they reach a target with the queue empty.
     fn update_train_target(&mut self, id: RailwayObjectId) {
-        let mut event = None;
+        let mut events: Vec<Box<dyn SimulationEvent>> = Vec::new();
         if let Some(object) = self.environment.objects.get_mut(&id) {
             if let Some(train) = object.as_any_mut().downcast_mut::<Train>() {
-                if train.next_target().is_none() || train.position() == train.next_target() {
-                    let reachable_nodes = self
-                        .environment
-                        .graph
-                        .reachable_nodes(train.position().unwrap());
-                    if !reachable_nodes.is_empty() {
-                        event = Some(TargetReachedEvent {});
-                        let mut rng = rand::rng();
-                        train.set_next_target(Some(*reachable_nodes.choose(&mut rng).unwrap()));
+                let reached =
+                    train.next_target().is_some() && train.position() == train.next_target();
+                if reached {
+                    events.push(Box::new(TargetReachedEvent {}));
+                }
+                if train.next_target().is_none() || reached {
+                    if let Some(target) = train.remove_target() {
+                        train.set_next_target(Some(target));
+                    } else {
+                        if reached {
+                            events.push(Box::new(TargetQueueEmptyEvent { object_id: id }));
+                        }
+                        let reachable_nodes = train.position().map_or(Vec::new(), |position| {
+                            let constraints = RoutingConstraints {
+                                attributes: &self.environment.track_attributes,
+                                vehicle: train.vehicle_profile(),
+                                directions: self.environment.direction_options,
+                            };
+                            self.environment.graph.compatible_reachable_nodes(
+                                position,
+                                None,
+                                &self.environment.reversal_options,
+                                constraints,
+                            )
+                        });
+                        if !reachable_nodes.is_empty() {
+                            let mut rng = rand::rng();
+                            train.set_next_target(Some(*reachable_nodes.choose(&mut rng).unwrap()));
+                        }
                     }
                 }
             }
         }
-        if let Some(event) = event {
-            self.handle_event(&event);
+        for event in events {
+            self.handle_event(event.as_ref());
+        }
+    }
+}
+
+/// Places an object that only has a geo location at its closest point on the track.
+fn place_on_track(
+    graph: &RailwayGraph,
+    object: &mut dyn SimulationObject,
+    snapped: &EdgeProjection,
+) {
+    let Some(edge) = graph.get_edge_by_id(snapped.edge_id) else {
+        return;
+    };
+
+    let forward = TrackPosition::new(snapped.edge_id, snapped.offset, TrackDirection::Forward);
+    let distance_to_target = |position: &TrackPosition| {
+        let target = object.next_target()?;
+        let node_ahead = edge.node_ahead(position.direction);
+        let beyond = if node_ahead == target {
+            0.0
+        } else {
+            graph.shortest_path_distance(node_ahead, target)?
+        };
+        Some(edge.distance_to_end(position).value + beyond)
+    };
+    let position = [forward, forward.reversed()]
+        .into_iter()
+        .filter_map(|position| distance_to_target(&position).map(|distance| (position, distance)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(forward, |(position, _)| position);
+
+    object.set_position(Some(edge.node_ahead(position.direction.reversed())));
+    object.set_track_position(Some(position));
+    object.set_geo_location(Some(snapped.point));
+}
+
+/// Chooses the next nodes of the simulation objects, from the prepared `RoutingTable` if there
+/// is one and with the direction-aware search otherwise.
+///
+/// Objects with a vehicle profile are routed with the search over the edges they may use. All
+/// objects honour the direction restrictions of the track as given by the direction options.
+#[derive(Clone, Copy)]
+pub(crate) struct Router<'a> {
+    graph: &'a RailwayGraph,
+    reversal_options: &'a ReversalOptions,
+    track_attributes: &'a RailwayTrackAttributes,
+    direction_options: DirectionOptions,
+    routing_table: Option<&'a RoutingTable>,
+    vehicle: Option<&'a VehicleProfile>,
+}
+
+impl<'a> Router<'a> {
+    /// Creates a router for the graph, the reversal options, the track attributes, the direction
+    /// options and the routing table of an environment.
+    pub(crate) fn new(environment: &'a SimulationEnvironment) -> Self {
+        Self::from_parts(
+            &environment.graph,
+            &environment.reversal_options,
+            &environment.track_attributes,
+            environment.direction_options,
+            environment.routing_table.as_ref(),
+        )
+    }
+
+    /// Creates a router from the fields of an environment, so that its objects can be borrowed
+    /// mutably at the same time.
+    pub(crate) fn from_parts(
+        graph: &'a RailwayGraph,
+        reversal_options: &'a ReversalOptions,
+        track_attributes: &'a RailwayTrackAttributes,
+        direction_options: DirectionOptions,
+        routing_table: Option<&'a RoutingTable>,
+    ) -> Self {
+        Self {
+            graph,
+            reversal_options,
+            track_attributes,
+            direction_options,
+            routing_table,
+            vehicle: None,
+        }
+    }
+
+    /// Restricts the router to the edges a vehicle may use.
+    pub(crate) fn with_vehicle(self, vehicle: Option<&'a VehicleProfile>) -> Self {
+        Self { vehicle, ..self }
+    }
+
+    fn next_node(
+        &self,
+        current: NodeId,
+        previous: Option<NodeId>,
+        target: NodeId,
+    ) -> Option<NodeId> {
+        match (self.vehicle, self.routing_table) {
+            (None, Some(table)) => table.next_node(current, previous, target),
+            (vehicle, _) => {
+                let constraints = RoutingConstraints {
+                    attributes: self.track_attributes,
+                    vehicle,
+                    directions: self.direction_options,
+                };
+                self.graph.next_compatible_node(
+                    current,
+                    previous,
+                    target,
+                    self.reversal_options,
+                    constraints,
+                )
+            }
+        }
+    }
+}
+
+/// Returns the position of an object on the track.
+///
+/// Objects standing at a node start on the edge towards their next target, in whichever
+/// direction it lies.
+pub(crate) fn current_track_position(
+    router: Router,
+    object: &dyn SimulationObject,
+) -> Option<TrackPosition> {
+    if let Some(track_position) = object.track_position() {
+        return Some(track_position);
+    }
+    let position = object.position()?;
+    let target = object.next_target().unwrap_or_default();
+    if position == target {
+        return None;
+    }
+    router
+        .next_node(position, None, target)
+        .and_then(|next_node_id| router.graph.railway_edge(position, next_node_id))
+        .and_then(|edge| edge.start_position(position))
+}
+
+/// Returns a function choosing the next node towards `target` at each node reached by an object
+/// travelling from `position`.
+///
+/// The function returns `None` at the target and where the object has to turn back, so that it
+/// stops there.
+pub(crate) fn next_node_towards<'a>(
+    router: Router<'a>,
+    position: &TrackPosition,
+    target: NodeId,
+) -> impl FnMut(NodeId) -> Option<NodeId> + 'a {
+    let mut previous = router
+        .graph
+        .get_edge_by_id(position.edge_id)
+        .map(|edge| edge.node_ahead(position.direction.reversed()));
+    move |node_id| {
+        if node_id == target {
+            return None;
+        }
+        let next = router.next_node(node_id, previous, target);
+        if next.is_some() && next == previous {
+            return None;
         }
+        previous = Some(node_id);
+        next
     }
 }
@@ -1,5 +1,5 @@
 use super::*;
-use crate::railway_algorithms::RailwayEdgeAlgos;
+use crate::railway_algorithms::{RailwayEdgeAlgos, RailwayGraphAlgos};
 use crate::railway_objects::Train;
 use crate::simulation::agents::ForwardUntilTargetAgent;
 use crate::simulation::commands::{SetSpeedupCommand, SimulationCommand};
@@ -35,6 +35,49 @@ fn test_debug_implementation() {
     assert!(debug_output.contains("railway_objects: 1"));
 }
 
+#[test]
+fn test_add_object_snaps_to_edge() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::siding_elements;
+
+    let graph = from_railway_elements(&siding_elements());
+    let mut simulation = Simulation::new(graph.clone());
+
+    // Between nodes 11 and 12, heading for node 10 behind the start of edge 21.
+    let train = Train {
+        id: 1,
+        geo_location: Some(coord! { x: 8.014, y: 50.0002 }),
+        next_target: Some(10),
+        ..Default::default()
+    };
+    assert!(simulation.add_object(Box::new(train), None));
+
+    let object = simulation.environment.objects.get(&1).unwrap();
+    let track_position = object.track_position().unwrap();
+    let edge = graph.get_edge_by_id(21).unwrap();
+    assert_eq!(track_position.edge_id, 21);
+    assert_eq!(track_position.direction, TrackDirection::Backward);
+    assert_eq!(object.position(), Some(12));
+    assert_relative_eq!(object.geo_location().unwrap().y, 50.0, epsilon = 1e-9);
+    assert_relative_eq!(
+        track_position.offset.get::<uom::si::length::meter>(),
+        edge.path_length().get::<uom::si::length::meter>() * 0.4,
+        epsilon = 1.0
+    );
+
+    // Objects at a node keep their position.
+    let train = Train {
+        id: 2,
+        position: Some(11),
+        geo_location: Some(coord! { x: 8.014, y: 50.0002 }),
+        ..Default::default()
+    };
+    assert!(simulation.add_object(Box::new(train), None));
+    let object = simulation.environment.objects.get(&2).unwrap();
+    assert_eq!(object.position(), Some(11));
+    assert_eq!(object.track_position(), None);
+}
+
 #[test]
 fn test_simulation_with_agent() {
     // Create a railway graph
@@ -73,28 +116,24 @@ fn test_simulation_with_agent() {
         Velocity::new::<meter_per_second>(20.0)
     ); // Assuming the initial speed was 0
 
-    let current_location = train.geo_location().unwrap();
     let current_speed = Velocity::new::<meter_per_second>(20.0);
     let next_node_id = simulation
         .environment
         .graph
         .get_next_node(train.position().unwrap(), train.next_target().unwrap())
         .unwrap();
-    let direction_node = &simulation.environment.graph.physical_graph.graph[*simulation
-        .environment
-        .graph
-        .physical_graph
-        .id_to_index(next_node_id)
-        .unwrap()];
-    let direction_coord = direction_node.location;
     let distance_to_travel = current_speed * Time::new::<second>(delta_time.as_secs_f64());
     let edge = simulation
         .environment
         .graph
         .railway_edge(train.position().unwrap(), next_node_id)
         .expect("Invalid edge");
-    let expected_new_location =
-        edge.position_on_edge(current_location, distance_to_travel, direction_coord);
+    let start_position = edge.start_position(train.position().unwrap()).unwrap();
+    let expected_position = edge.position_on_edge(&start_position, distance_to_travel);
+    let expected_new_location = edge.coordinate_at(expected_position.offset);
+
+    // Check if the train's track position has been set after the update
+    assert_eq!(updated_train.track_position(), Some(expected_position));
 
     // Check if the train's geo_location has changed after the update
     assert_ne!(
@@ -202,3 +241,378 @@ fn test_simulation_with_agent_and_speedup() {
         epsilon = 0.1
     );
 }
+
+#[test]
+fn test_speed_limit_enforced() {
+    use crate::prelude::{RailwaySpeedLimits, SpeedProfile, SpeedSection};
+
+    let graph = test_graph_1();
+    let train = Train {
+        id: 1,
+        position: Some(1),
+        geo_location: Some(coord! { x: 0.0, y: 0.0 }),
+        next_target: Some(2),
+        max_speed: Velocity::new::<kilometer_per_hour>(80.0),
+        ..Default::default()
+    };
+    let next_node_id = graph.get_next_node(1, 2).unwrap();
+    let edge = graph.railway_edge(1, next_node_id).unwrap().clone();
+    let section = SpeedSection {
+        start: 0.0,
+        end: edge.path_length().get::<uom::si::length::meter>(),
+        limit: 36.0,
+    };
+
+    let mut simulation = Simulation::new(graph);
+    simulation.environment.speed_limits = RailwaySpeedLimits {
+        edges: HashMap::from([(
+            edge.id,
+            SpeedProfile {
+                forward: vec![section],
+                backward: vec![section],
+            },
+        )]),
+    };
+    let agent = ForwardUntilTargetAgent::new(train.id());
+    simulation.add_object(Box::new(train), Some(Box::new(agent)));
+    simulation.update(Duration::from_secs(1));
+
+    let updated_train = simulation.environment.objects.get(&1).unwrap();
+    assert_relative_eq!(updated_train.speed().get::<meter_per_second>(), 10.0);
+}
+
+#[test]
+fn test_gradient_slows_down_climbing_train() {
+    use crate::prelude::{ElevationProfile, ElevationSample, RailwayElevation};
+
+    let graph = test_graph_1();
+    let next_node_id = graph.get_next_node(1, 2).unwrap();
+    let edge = graph.railway_edge(1, next_node_id).unwrap().clone();
+    let length = edge.path_length().get::<uom::si::length::meter>();
+    let elevation = RailwayElevation {
+        edges: HashMap::from([(
+            edge.id,
+            ElevationProfile {
+                samples: vec![
+                    ElevationSample {
+                        offset: 0.0,
+                        elevation: 100.0,
+                    },
+                    ElevationSample {
+                        offset: length,
+                        elevation: 100.0 + 0.02 * length,
+                    },
+                ],
+            },
+        )]),
+    };
+    let gradient = elevation
+        .gradient_at(&edge.start_position(1).unwrap())
+        .unwrap();
+    assert_relative_eq!(gradient.abs(), 0.02, epsilon = 1e-9);
+
+    let speeds: Vec<_> = [RailwayElevation::default(), elevation]
+        .into_iter()
+        .map(|elevation| {
+            let train = Train {
+                id: 1,
+                position: Some(1),
+                geo_location: Some(coord! { x: 0.0, y: 0.0 }),
+                next_target: Some(2),
+                max_speed: Velocity::new::<kilometer_per_hour>(80.0),
+                ..Default::default()
+            };
+            let mut simulation = Simulation::new(test_graph_1());
+            simulation.environment.elevation = elevation;
+            let agent = ForwardUntilTargetAgent::new(train.id());
+            simulation.add_object(Box::new(train), Some(Box::new(agent)));
+            simulation.update(Duration::from_secs(1));
+            simulation.environment.objects[&1]
+                .speed()
+                .get::<meter_per_second>()
+        })
+        .collect();
+    assert_relative_eq!(
+        speeds[0] - speeds[1],
+        STANDARD_GRAVITY * gradient,
+        epsilon = 1e-9
+    );
+}
+
+#[test]
+fn test_train_turns_back_behind_switch() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::siding_elements;
+
+    // The prepared routing table has to choose the same nodes as the search.
+    for prepared in [false, true] {
+        let graph = from_railway_elements(&siding_elements());
+        let train = Train {
+            id: 1,
+            position: Some(13),
+            next_target: Some(14),
+            max_speed: Velocity::new::<kilometer_per_hour>(80.0),
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(graph);
+        if prepared {
+            simulation.environment.prepare_routing_table();
+        }
+        let agent = ForwardUntilTargetAgent::new(train.id());
+        simulation.add_object(Box::new(train), Some(Box::new(agent)));
+
+        let mut positions = vec![13];
+        let mut stopped_at_reversal = false;
+        for _ in 0..600 {
+            simulation.update(Duration::from_secs(1));
+            let train = simulation.environment.objects.get(&1).unwrap();
+            let position = train.position().unwrap();
+            if positions.last() != Some(&position) {
+                positions.push(position);
+                if position == 11 {
+                    stopped_at_reversal = train.speed() == Velocity::default();
+                }
+            }
+            if position == 14 {
+                break;
+            }
+        }
+
+        // The switch 12 only leads from 13 towards 11, so the train clears it and reverses at
+        // 11.
+        assert_eq!(positions, vec![13, 12, 11, 12, 14]);
+        assert!(stopped_at_reversal);
+    }
+}
+
+#[test]
+fn test_train_follows_target_queue() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::siding_elements;
+    use crate::simulation::events::TargetQueueEmptyEvent;
+    use std::any::Any;
+
+    struct QueueEmptyCounter(usize);
+
+    impl MetricsHandler for QueueEmptyCounter {
+        fn handle(&mut self, event: &dyn SimulationEvent) {
+            if event.as_any().is::<TargetQueueEmptyEvent>() {
+                self.0 += 1;
+            }
+        }
+
+        fn get_value(&self) -> f64 {
+            self.0 as f64
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    let graph = from_railway_elements(&siding_elements());
+    let train = Train {
+        id: 1,
+        position: Some(10),
+        next_target: Some(12),
+        targets: VecDeque::from([13, 11]),
+        max_speed: Velocity::new::<kilometer_per_hour>(80.0),
+        ..Default::default()
+    };
+    let mut simulation = Simulation::new(graph);
+    simulation.register_metrics_handler(Box::new(QueueEmptyCounter(0)));
+    let agent = ForwardUntilTargetAgent::new(train.id());
+    simulation.add_object(Box::new(train), Some(Box::new(agent)));
+
+    let mut targets = vec![12];
+    for _ in 0..600 {
+        simulation.update(Duration::from_secs(1));
+        let train = simulation.environment.objects.get(&1).unwrap();
+        let target = train.next_target().unwrap();
+        if targets.last() != Some(&target) {
+            targets.push(target);
+        }
+        if targets.len() == 4 {
+            break;
+        }
+    }
+
+    // The queued targets are taken in order before a random target is chosen.
+    assert_eq!(targets[..3], [12, 13, 11]);
+    let counter = simulation.metrics_handlers.last().unwrap();
+    assert_eq!(counter.get_value(), 1.0);
+}
+
+#[test]
+fn test_train_avoids_incompatible_track() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{TrackAttributes, VehicleProfile};
+    use crate::railway_algorithms::tests::loop_elements;
+
+    // The direct line 1 - 2 is metre gauge, so a standard gauge train takes the loop over 3.
+    let graph = from_railway_elements(&loop_elements());
+    let train = Train {
+        id: 1,
+        position: Some(4),
+        next_target: Some(2),
+        max_speed: Velocity::new::<kilometer_per_hour>(80.0),
+        vehicle_profile: Some(VehicleProfile {
+            gauge: Some(1435),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let mut simulation = Simulation::new(graph);
+    simulation.environment.track_attributes_mut().edges.insert(
+        11,
+        TrackAttributes {
+            gauges: vec![1000],
+            ..Default::default()
+        },
+    );
+    let agent = ForwardUntilTargetAgent::new(train.id());
+    simulation.add_object(Box::new(train), Some(Box::new(agent)));
+
+    let mut positions = vec![4];
+    for _ in 0..600 {
+        simulation.update(Duration::from_secs(1));
+        let position = simulation.environment.objects[&1].position().unwrap();
+        if positions.last() != Some(&position) {
+            positions.push(position);
+        }
+        if position == 2 {
+            break;
+        }
+    }
+
+    assert_eq!(positions, vec![4, 1, 3, 2]);
+}
+
+#[test]
+fn test_train_honours_oneway_track() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{DirectionRestriction, TrackAttributes, TrackDirection};
+    use crate::railway_algorithms::tests::loop_elements;
+
+    // The direct line 1 - 2 is operated from 2 to 1 only, so a train from 4 to 2 takes the
+    // loop over 3 unless wrong-line running is allowed.
+    let route = |wrong_line: Option<f64>| {
+        let graph = from_railway_elements(&loop_elements());
+        let train = Train {
+            id: 1,
+            position: Some(4),
+            next_target: Some(2),
+            max_speed: Velocity::new::<kilometer_per_hour>(80.0),
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(graph);
+        simulation.environment.track_attributes_mut().edges.insert(
+            11,
+            TrackAttributes {
+                direction: Some(DirectionRestriction {
+                    direction: TrackDirection::Backward,
+                    hard: true,
+                }),
+                ..Default::default()
+            },
+        );
+        simulation.environment.direction_options_mut().wrong_line = wrong_line;
+        simulation.environment.prepare_routing_table();
+        let agent = ForwardUntilTargetAgent::new(train.id());
+        simulation.add_object(Box::new(train), Some(Box::new(agent)));
+
+        let mut positions = vec![4];
+        for _ in 0..600 {
+            simulation.update(Duration::from_secs(1));
+            let position = simulation.environment.objects[&1].position().unwrap();
+            if positions.last() != Some(&position) {
+                positions.push(position);
+            }
+            if position == 2 {
+                break;
+            }
+        }
+        positions
+    };
+
+    assert_eq!(route(None), vec![4, 1, 3, 2]);
+    assert_eq!(route(Some(1.1)), vec![4, 1, 2]);
+}
+
+#[test]
+fn test_next_node() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{DirectionRestriction, TrackAttributes, TrackDirection};
+    use crate::railway_algorithms::tests::loop_elements;
+
+    let graph = from_railway_elements(&loop_elements());
+    let train = Train {
+        id: 1,
+        position: Some(1),
+        next_target: Some(2),
+        ..Default::default()
+    };
+    let mut simulation = Simulation::new(graph);
+    simulation.environment.track_attributes_mut().edges.insert(
+        11,
+        TrackAttributes {
+            direction: Some(DirectionRestriction {
+                direction: TrackDirection::Backward,
+                hard: true,
+            }),
+            ..Default::default()
+        },
+    );
+    simulation.environment.prepare_routing_table();
+    simulation.add_object(Box::new(train), None);
+    assert_eq!(simulation.next_node(1), Some(3));
+    assert_eq!(simulation.next_node(2), None);
+
+    // The prepared routing table is rebuilt after the track attributes have been changed.
+    simulation
+        .environment
+        .track_attributes_mut()
+        .edges
+        .remove(&11);
+    simulation.environment.refresh_routing_table();
+    assert_eq!(simulation.next_node(1), Some(2));
+}
+
+#[test]
+fn test_random_target_is_reachable() {
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{TrackAttributes, Traction};
+    use crate::railway_algorithms::tests::siding_elements;
+    use uom::si::{f64::Length, length::meter};
+
+    // The electric train cannot reverse on the unelectrified track to the dead end 10, so
+    // it can only reach 12 and 11.
+    for _ in 0..20 {
+        let graph = from_railway_elements(&siding_elements());
+        let length_11_12 = graph.railway_edge(11, 12).unwrap().length;
+        let train = Train {
+            id: 1,
+            position: Some(13),
+            vehicle_profile: Some(VehicleProfile {
+                traction: Traction::Electric { voltages: vec![] },
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(graph);
+        simulation.environment.reversal_options_mut().train_length =
+            Length::new::<meter>(length_11_12 + 1.0);
+        simulation.environment.track_attributes_mut().edges.insert(
+            20,
+            TrackAttributes {
+                electrified: Some(false),
+                ..Default::default()
+            },
+        );
+        simulation.add_object(Box::new(train), None);
+        simulation.update(Duration::from_secs(1));
+
+        let target = simulation.environment.objects[&1].next_target();
+        assert!(matches!(target, Some(11 | 12)), "{target:?}");
+    }
+}
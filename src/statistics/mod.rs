@@ -1,8 +1,19 @@
 //! A module providing utility functions for calculating path lengths, remaining lengths, and the total length of a railway network.
+//!
+//! `network_statistics` summarizes a whole network: track lengths, counts of switches, buffer
+//! stops and crossings, node degrees, connected components and edge length percentiles.
+//! `network_diameter` adds the longest shortest route, which is slow on large networks.
 
 use uom::si::{f64::Length, length::meter};
 
-use crate::{prelude::RailwayGraph, types::EdgeId};
+use crate::{
+    prelude::{RailwayGraph, RailwayGraphExt},
+    types::EdgeId,
+};
+
+mod network;
+
+pub use network::{network_diameter, network_statistics, LengthPercentiles, NetworkStatistics};
 
 /// Calculate the total length of the given sequence of edges in the railway network.
 ///
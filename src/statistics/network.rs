@@ -0,0 +1,296 @@
+use std::collections::{BTreeMap, HashMap};
+
+use petgraph::{
+    algo::dijkstra,
+    unionfind::UnionFind,
+    visit::{EdgeRef, NodeIndexable},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{RailwayGraph, RailwayTags};
+use crate::types::{EdgeId, NodeId};
+
+/// Percentiles of the edge lengths of a railway network, in meters.
+///
+/// Percentiles are linearly interpolated between the sorted edge lengths.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LengthPercentiles {
+    /// The shortest edge.
+    pub min: f64,
+    /// The 25th percentile.
+    pub p25: f64,
+    /// The median.
+    pub p50: f64,
+    /// The 75th percentile.
+    pub p75: f64,
+    /// The 90th percentile.
+    pub p90: f64,
+    /// The longest edge.
+    pub max: f64,
+}
+
+/// A summary of the size, elements and structure of a railway network.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkStatistics {
+    /// The number of nodes.
+    pub node_count: usize,
+    /// The number of edges.
+    pub edge_count: usize,
+    /// The total track length in meters.
+    pub total_length: f64,
+    /// The track length in meters per `railway` tag of the edges, e.g. `rail` or `tram`.
+    /// Edges without the tag are counted as `unknown`.
+    pub length_by_type: BTreeMap<String, f64>,
+    /// The number of nodes tagged `railway=switch`.
+    pub switches: usize,
+    /// The number of nodes tagged `railway=buffer_stop`.
+    pub buffer_stops: usize,
+    /// The number of nodes tagged `railway=railway_crossing`, `railway=level_crossing` or
+    /// `railway=crossing`.
+    pub crossings: usize,
+    /// The number of nodes per node degree.
+    pub degree_distribution: BTreeMap<usize, usize>,
+    /// The number of connected components.
+    pub connected_components: usize,
+    /// The number of nodes of each connected component, largest first.
+    pub component_sizes: Vec<usize>,
+    /// The longest shortest route between any two connected nodes in meters, following the
+    /// switches without reversing. `None` unless calculated with `network_diameter`.
+    pub diameter: Option<f64>,
+    /// Percentiles of the edge lengths.
+    pub edge_length_percentiles: LengthPercentiles,
+}
+
+/// Calculates the statistics of a railway network.
+///
+/// The diameter is left out, as it takes a while on networks with many thousand nodes; see
+/// `network_diameter`.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the `RailwayGraph` instance.
+/// * `tags` - The OpenStreetMap tags of the graph's nodes and edges.
+///
+/// # Returns
+///
+/// The `NetworkStatistics` of the graph.
+pub fn network_statistics(graph: &RailwayGraph, tags: &RailwayTags) -> NetworkStatistics {
+    let physical = &graph.physical_graph.graph;
+
+    let mut length_by_type = BTreeMap::new();
+    let mut lengths = Vec::with_capacity(physical.edge_count());
+    for edge in physical.edge_weights() {
+        let track_type = tags.edge_tag(edge.id, "railway").unwrap_or("unknown");
+        *length_by_type.entry(track_type.to_string()).or_insert(0.0) += edge.length;
+        lengths.push(edge.length);
+    }
+
+    let count_nodes = |values: &[&str]| {
+        physical
+            .node_weights()
+            .filter(|node| {
+                tags.node_tag(node.id, "railway")
+                    .is_some_and(|value| values.contains(&value))
+            })
+            .count()
+    };
+
+    let mut degree_distribution = BTreeMap::new();
+    for index in physical.node_indices() {
+        *degree_distribution
+            .entry(physical.edges(index).count())
+            .or_insert(0) += 1;
+    }
+
+    let mut components = UnionFind::new(physical.node_bound());
+    for edge in physical.edge_references() {
+        components.union(edge.source().index(), edge.target().index());
+    }
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for index in physical.node_indices() {
+        *sizes.entry(components.find(index.index())).or_insert(0) += 1;
+    }
+    let mut component_sizes: Vec<usize> = sizes.into_values().collect();
+    component_sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+    NetworkStatistics {
+        node_count: physical.node_count(),
+        edge_count: physical.edge_count(),
+        total_length: lengths.iter().sum(),
+        length_by_type,
+        switches: count_nodes(&["switch"]),
+        buffer_stops: count_nodes(&["buffer_stop"]),
+        crossings: count_nodes(&["railway_crossing", "level_crossing", "crossing"]),
+        degree_distribution,
+        connected_components: component_sizes.len(),
+        component_sizes,
+        diameter: None,
+        edge_length_percentiles: length_percentiles(lengths),
+    }
+}
+
+/// Calculates the diameter of a railway network, the longest shortest route between any two
+/// connected nodes in meters.
+///
+/// The routes run on the topology graph, so trains follow the switches and do not reverse. A
+/// shortest route search runs from every node, which takes a while on networks with many
+/// thousand nodes.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the `RailwayGraph` instance.
+///
+/// # Returns
+///
+/// The diameter in meters, `0.0` for a network without edges.
+pub fn network_diameter(graph: &RailwayGraph) -> f64 {
+    let topology = &graph.topology_graph;
+    let lengths: HashMap<EdgeId, f64> = graph
+        .physical_graph
+        .graph
+        .edge_weights()
+        .map(|edge| (edge.id, edge.length))
+        .collect();
+
+    let mut diameter: f64 = 0.0;
+    for node in graph.physical_graph.graph.node_weights() {
+        let Some(&(port_1, port_2)) = topology.id_to_index(node.id) else {
+            continue;
+        };
+        // The distance to a node is the shorter one to either of its ports.
+        let mut distances: HashMap<NodeId, f64> = HashMap::new();
+        for port in [port_1, port_2] {
+            let reached = dijkstra(&topology.graph, port, None, |edge| {
+                lengths
+                    .get(&edge.weight().edge_id)
+                    .copied()
+                    .unwrap_or(f64::INFINITY)
+            });
+            for (index, distance) in reached {
+                if let Some(&id) = topology.index_to_id(index) {
+                    let known = distances.entry(id).or_insert(distance);
+                    *known = known.min(distance);
+                }
+            }
+        }
+        diameter = distances
+            .into_values()
+            .filter(|distance| distance.is_finite())
+            .fold(diameter, f64::max);
+    }
+    diameter
+}
+
+fn length_percentiles(mut lengths: Vec<f64>) -> LengthPercentiles {
+    if lengths.is_empty() {
+        return LengthPercentiles::default();
+    }
+    lengths.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: f64| {
+        let rank = p / 100.0 * (lengths.len() - 1) as f64;
+        let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+        lengths[lower] + (lengths[upper] - lengths[lower]) * (rank - lower as f64)
+    };
+    LengthPercentiles {
+        min: lengths[0],
+        p25: percentile(25.0),
+        p50: percentile(50.0),
+        p75: percentile(75.0),
+        p90: percentile(90.0),
+        max: lengths[lengths.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::{from_railway_elements, tags_from_railway_elements};
+    use crate::prelude::{RailwayGraphExt, Tags};
+    use crate::railway_algorithms::tests::{node, test_elements, way};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_network_statistics() {
+        let graph = from_railway_elements(&test_elements());
+        let mut tags = tags_from_railway_elements(&test_elements());
+        tags.nodes.insert(
+            2,
+            Tags::from([("railway".to_string(), "switch".to_string())]),
+        );
+        tags.nodes.insert(
+            3,
+            Tags::from([("railway".to_string(), "buffer_stop".to_string())]),
+        );
+        tags.edges
+            .insert(4, Tags::from([("railway".to_string(), "rail".to_string())]));
+
+        let statistics = network_statistics(&graph, &tags);
+        let length_4 = graph.railway_edge(1, 2).unwrap().length;
+        let length_5 = graph.railway_edge(2, 3).unwrap().length;
+
+        assert_eq!(statistics.node_count, 3);
+        assert_eq!(statistics.edge_count, 2);
+        assert_relative_eq!(statistics.total_length, length_4 + length_5);
+        assert_relative_eq!(statistics.length_by_type["rail"], length_4);
+        assert_relative_eq!(statistics.length_by_type["unknown"], length_5);
+        assert_eq!(statistics.switches, 1);
+        assert_eq!(statistics.buffer_stops, 1);
+        assert_eq!(statistics.crossings, 0);
+        assert_eq!(
+            statistics.degree_distribution,
+            BTreeMap::from([(1, 2), (2, 1)])
+        );
+        assert_eq!(statistics.connected_components, 1);
+        assert_eq!(statistics.component_sizes, vec![3]);
+        assert_eq!(statistics.diameter, None);
+        assert_relative_eq!(
+            statistics.edge_length_percentiles.min,
+            length_4.min(length_5)
+        );
+        assert_relative_eq!(
+            statistics.edge_length_percentiles.p50,
+            (length_4 + length_5) / 2.0
+        );
+
+        let json = serde_json::to_value(&statistics).unwrap();
+        assert_eq!(json["switches"], 1);
+    }
+
+    #[test]
+    fn test_network_diameter() {
+        let graph = from_railway_elements(&test_elements());
+        let length_4 = graph.railway_edge(1, 2).unwrap().length;
+        let length_5 = graph.railway_edge(2, 3).unwrap().length;
+        assert_relative_eq!(network_diameter(&graph), length_4 + length_5);
+
+        // Between the long branches 13 and 14 of the switch 11 a train has to reverse behind
+        // it, so the longest route starts at the short stub 10.
+        let (n10, n11) = ((10, 8.0, 50.0), (11, 8.001, 50.0));
+        let (n13, n14) = ((13, 8.02, 50.01), (14, 8.02, 49.99));
+        let graph = from_railway_elements(&[
+            node(10, n10.1, n10.2),
+            node(11, n11.1, n11.2),
+            node(13, n13.1, n13.2),
+            node(14, n14.1, n14.2),
+            way(20, &[n10, n11]),
+            way(21, &[n11, n13]),
+            way(22, &[n11, n14]),
+        ]);
+        let length = |id| graph.get_edge_by_id(id).unwrap().length;
+        assert_relative_eq!(
+            network_diameter(&graph),
+            length(20) + length(21).max(length(22))
+        );
+    }
+
+    #[test]
+    fn test_length_percentiles() {
+        let percentiles = length_percentiles(vec![40.0, 10.0, 30.0, 20.0, 50.0]);
+        assert_relative_eq!(percentiles.min, 10.0);
+        assert_relative_eq!(percentiles.p25, 20.0);
+        assert_relative_eq!(percentiles.p50, 30.0);
+        assert_relative_eq!(percentiles.p90, 46.0);
+        assert_relative_eq!(percentiles.max, 50.0);
+        assert_eq!(length_percentiles(Vec::new()), LengthPercentiles::default());
+    }
+}
@@ -12,7 +12,7 @@ use crate::{
     },
     prelude::{
         OverpassImporter, RailwayEdge, RailwayGraph, RailwayGraphExt, RailwayGraphImporter,
-        RailwayNode,
+        RailwayNode, TrackDirection, TrackPosition,
     },
     railway_algorithms::RailwayEdgeAlgos,
 };
@@ -96,14 +96,18 @@ fn test_vilbel_json() {
     let target_node = 662529466;
     let distance_to_travel = Length::new::<meter>(100.0);
     let current_location = coord! {x: 8.7674673, y: 50.1929356};
-    let direction_coord = coord! { x: 8.7672898, y: 50.1929871 };
 
     let edge = railway_graph
         .railway_edge(source_node, target_node)
         .expect("Invalid edge");
 
-    let new_geo_location =
-        edge.position_on_edge(current_location, distance_to_travel, direction_coord);
+    let current_position = TrackPosition::new(
+        edge.id,
+        edge.project(current_location),
+        TrackDirection::Forward,
+    );
+    let new_position = edge.position_on_edge(&current_position, distance_to_travel);
+    let new_geo_location = edge.coordinate_at(new_position.offset);
 
     assert_ne!(current_location, new_geo_location);
 }
@@ -0,0 +1,383 @@
+//! Module `validation` checks a `RailwayGraph` for data problems.
+//!
+//! The `RailwayGraphValidation` trait produces a `ValidationReport` listing every problem found
+//! together with the ids and coordinates of the affected elements, so the data can be fixed in an
+//! OpenStreetMap editor such as JOSM. The `RouteRelationComparison` trait checks that the
+//! `route=train` relations are continuous and compares them with the shortest paths between
+//! their terminals.
+mod route_relations;
+
+use std::collections::HashMap;
+
+use geo::Coord;
+use serde::{Deserialize, Serialize};
+use uom::si::{f64::Length, length::meter};
+
+use crate::algorithms::Distance;
+use crate::importer::overpass_importer::Coordinate;
+use crate::prelude::{RailwayEdge, RailwayGraph, RailwayTags};
+use crate::railway_model::paths_match;
+use crate::types::{EdgeId, NodeId};
+
+pub use route_relations::{RelationGap, RouteRelationComparison, RouteRelationReport};
+
+/// Options for validating a `RailwayGraph`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidationOptions {
+    /// Maximum distance between the end of an edge's geometry and its source or target node.
+    pub endpoint_tolerance: Length,
+    /// Maximum relative difference between an edge's `length` and the length of its geometry.
+    pub length_tolerance: f64,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            endpoint_tolerance: Length::new::<meter>(1.0),
+            length_tolerance: 0.01,
+        }
+    }
+}
+
+/// The kind of a problem found by the validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// An edge with a length of zero.
+    ZeroLengthEdge,
+    /// An edge whose source and target are the same node.
+    SelfLoop,
+    /// An edge with the same end nodes and geometry as another edge.
+    DuplicateEdge,
+    /// An edge whose geometry does not start at its source or end at its target node.
+    EndpointMismatch,
+    /// An edge whose `length` disagrees with the length of its geometry.
+    LengthMismatch,
+    /// A node without any edges.
+    IsolatedNode,
+    /// A node tagged as switch with less than three edges.
+    SwitchDegree,
+    /// A node of the topology graph without a node in the physical graph.
+    OrphanTopologyNode,
+}
+
+/// A single problem found by the validation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// The kind of the problem.
+    pub kind: IssueKind,
+    /// The id of the affected node, if the problem concerns a node.
+    pub node_id: Option<NodeId>,
+    /// The id of the affected edge, if the problem concerns an edge.
+    pub edge_id: Option<EdgeId>,
+    /// The location of the problem.
+    pub location: Option<Coordinate>,
+    /// A human readable description of the problem.
+    pub message: String,
+}
+
+/// The result of validating a `RailwayGraph`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// All problems found.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the problems of the given kind.
+    pub fn issues_of_kind(&self, kind: IssueKind) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.kind == kind)
+            .collect()
+    }
+}
+
+/// Validation of railway graphs.
+pub trait RailwayGraphValidation {
+    /// Check the graph for data problems.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - The tags of the graph, used to find switches.
+    /// * `options` - Tolerances used by the checks.
+    ///
+    /// # Returns
+    ///
+    /// A `ValidationReport` listing all problems found.
+    fn validate(&self, tags: &RailwayTags, options: &ValidationOptions) -> ValidationReport;
+}
+
+impl RailwayGraphValidation for RailwayGraph {
+    fn validate(&self, tags: &RailwayTags, options: &ValidationOptions) -> ValidationReport {
+        let mut issues = Vec::new();
+        validate_edges(self, options, &mut issues);
+        validate_nodes(self, tags, &mut issues);
+        validate_topology(self, &mut issues);
+        ValidationReport { issues }
+    }
+}
+
+fn validate_edges(
+    graph: &RailwayGraph,
+    options: &ValidationOptions,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let endpoint_tolerance = options.endpoint_tolerance.get::<meter>();
+    let mut edges_by_nodes: HashMap<(NodeId, NodeId), Vec<&RailwayEdge>> = HashMap::new();
+
+    for edge in graph.physical_graph.graph.edge_weights() {
+        let location = edge_location(edge);
+        let mut edge_issue = |kind, message: String| {
+            issues.push(ValidationIssue {
+                kind,
+                node_id: None,
+                edge_id: Some(edge.id),
+                location: location.clone(),
+                message,
+            })
+        };
+
+        if edge.length <= 0.0 {
+            edge_issue(
+                IssueKind::ZeroLengthEdge,
+                format!("edge {} has a length of {} m", edge.id, edge.length),
+            );
+        }
+        if edge.source == edge.target {
+            edge_issue(
+                IssueKind::SelfLoop,
+                format!("edge {} starts and ends at node {}", edge.id, edge.source),
+            );
+        }
+
+        let geometry_length = geometry_length(edge);
+        if (geometry_length - edge.length).abs()
+            > (options.length_tolerance * edge.length.abs()).max(1.0)
+        {
+            edge_issue(
+                IssueKind::LengthMismatch,
+                format!(
+                    "edge {} has a length of {:.3} m, but its geometry is {:.3} m long",
+                    edge.id, edge.length, geometry_length
+                ),
+            );
+        }
+
+        let endpoints = [
+            (edge.source, edge.path.0.first()),
+            (edge.target, edge.path.0.last()),
+        ];
+        for (node_id, coord) in endpoints {
+            let node_index = graph.physical_graph.id_to_index(node_id);
+            if let (Some(node_index), Some(coord)) = (node_index, coord) {
+                let node = &graph.physical_graph.graph[*node_index];
+                let distance = node.location.distance(coord).get::<meter>();
+                if distance > endpoint_tolerance {
+                    edge_issue(
+                        IssueKind::EndpointMismatch,
+                        format!(
+                            "geometry of edge {} ends {:.3} m away from node {}",
+                            edge.id, distance, node_id
+                        ),
+                    );
+                }
+            }
+        }
+
+        let key = (edge.source.min(edge.target), edge.source.max(edge.target));
+        let candidates = edges_by_nodes.entry(key).or_default();
+        if let Some(duplicate) = candidates
+            .iter()
+            .find(|other| paths_match(&other.path, &edge.path, endpoint_tolerance))
+        {
+            edge_issue(
+                IssueKind::DuplicateEdge,
+                format!("edge {} duplicates edge {}", edge.id, duplicate.id),
+            );
+        }
+        candidates.push(edge);
+    }
+}
+
+fn validate_nodes(graph: &RailwayGraph, tags: &RailwayTags, issues: &mut Vec<ValidationIssue>) {
+    let physical_graph = &graph.physical_graph.graph;
+    for node_index in physical_graph.node_indices() {
+        let node = &physical_graph[node_index];
+        let degree = physical_graph.edges(node_index).count();
+        let mut node_issue = |kind, message: String| {
+            issues.push(ValidationIssue {
+                kind,
+                node_id: Some(node.id),
+                edge_id: None,
+                location: Some(coordinate(node.location)),
+                message,
+            })
+        };
+
+        if degree == 0 {
+            node_issue(
+                IssueKind::IsolatedNode,
+                format!("node {} has no edges", node.id),
+            );
+        }
+        if tags.node_tag(node.id, "railway") == Some("switch") && degree < 3 {
+            node_issue(
+                IssueKind::SwitchDegree,
+                format!("switch {} has only {} edges", node.id, degree),
+            );
+        }
+    }
+}
+
+fn validate_topology(graph: &RailwayGraph, issues: &mut Vec<ValidationIssue>) {
+    for topology_index in graph.topology_graph.graph.node_indices() {
+        let node_id = graph.topology_graph.index_to_id(topology_index).copied();
+        let has_physical_node =
+            node_id.is_some_and(|id| graph.physical_graph.id_to_index(id).is_some());
+        if !has_physical_node {
+            let edges: Vec<EdgeId> = graph
+                .topology_graph
+                .graph
+                .edges(topology_index)
+                .map(|edge| edge.weight().edge_id)
+                .collect();
+            issues.push(ValidationIssue {
+                kind: IssueKind::OrphanTopologyNode,
+                node_id,
+                edge_id: edges.first().copied(),
+                location: None,
+                message: format!(
+                    "topology node {} has no physical node, connected edges: {:?}",
+                    topology_index.index(),
+                    edges
+                ),
+            });
+        }
+    }
+}
+
+fn geometry_length(edge: &RailwayEdge) -> f64 {
+    edge.path
+        .0
+        .windows(2)
+        .map(|pair| pair[0].distance(&pair[1]).get::<meter>())
+        .sum()
+}
+
+fn edge_location(edge: &RailwayEdge) -> Option<Coordinate> {
+    edge.path
+        .0
+        .get(edge.path.0.len() / 2)
+        .copied()
+        .map(coordinate)
+}
+
+fn coordinate(coord: Coord) -> Coordinate {
+    Coordinate {
+        lat: coord.y,
+        lon: coord.x,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::prelude::{RailwayNode, Tags};
+    use crate::railway_algorithms::tests::test_elements;
+    use geo::{coord, line_string};
+    use transit_grid::prelude::TransitNetworkModifier;
+
+    fn node(id: NodeId, x: f64, y: f64) -> RailwayNode {
+        RailwayNode {
+            id,
+            location: coord! { x: x, y: y },
+        }
+    }
+
+    #[test]
+    fn test_validate_clean_graph() {
+        let graph = from_railway_elements(&test_elements());
+        let report = graph.validate(&RailwayTags::default(), &ValidationOptions::default());
+
+        assert!(report.is_valid(), "{:?}", report);
+    }
+
+    #[test]
+    fn test_validate_finds_problems() {
+        let mut graph = RailwayGraph::new();
+        graph.add_node(node(1, 8.6821, 50.1109));
+        graph.add_node(node(2, 8.6921, 50.1209));
+        graph.add_node(node(3, 8.7021, 50.1309));
+        graph.add_node(node(4, 8.7121, 50.1409));
+        let path = line_string![
+            coord! { x: 8.6821, y: 50.1109 },
+            coord! { x: 8.6921, y: 50.1209 },
+        ];
+        graph.add_edge(RailwayEdge {
+            id: 10,
+            length: 1322.421,
+            path: path.clone(),
+            source: 1,
+            target: 2,
+        });
+        graph.add_edge(RailwayEdge {
+            id: 11,
+            length: 1322.421,
+            path: path.0.iter().rev().copied().collect(),
+            source: 2,
+            target: 1,
+        });
+        graph.add_edge(RailwayEdge {
+            id: 12,
+            length: 0.0,
+            path: line_string![coord! { x: 8.7021, y: 50.1309 }],
+            source: 3,
+            target: 3,
+        });
+        graph.add_edge(RailwayEdge {
+            id: 13,
+            length: 10.0,
+            path,
+            source: 2,
+            target: 3,
+        });
+
+        let mut tags = RailwayTags::default();
+        tags.nodes.insert(
+            2,
+            Tags::from([("railway".to_string(), "switch".to_string())]),
+        );
+        tags.nodes.insert(
+            1,
+            Tags::from([("railway".to_string(), "switch".to_string())]),
+        );
+
+        let report = graph.validate(&tags, &ValidationOptions::default());
+        let ids = |kind| {
+            report
+                .issues_of_kind(kind)
+                .iter()
+                .map(|issue| issue.edge_id.or(issue.node_id).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(IssueKind::DuplicateEdge), vec![11]);
+        assert_eq!(ids(IssueKind::ZeroLengthEdge), vec![12]);
+        assert_eq!(ids(IssueKind::SelfLoop), vec![12]);
+        assert_eq!(ids(IssueKind::LengthMismatch), vec![13]);
+        assert_eq!(ids(IssueKind::EndpointMismatch), vec![13, 13]);
+        assert_eq!(ids(IssueKind::IsolatedNode), vec![4]);
+        assert_eq!(ids(IssueKind::SwitchDegree), vec![1]);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"kind\":\"isolated_node\""));
+        assert!(json.contains("\"lat\":50.1409"));
+    }
+}
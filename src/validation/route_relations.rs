@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use uom::si::length::meter;
+
+use super::coordinate;
+use crate::algorithms::Distance;
+use crate::importer::overpass_importer::{Coordinate, RouteRelation};
+use crate::prelude::{RailwayEdge, RailwayGraph, RailwayGraphExt};
+use crate::railway_algorithms::PathFinding;
+use crate::types::{EdgeId, NodeId};
+
+/// A place where two consecutive ways of a relation do not share a node in the graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelationGap {
+    /// The id of the way before the gap.
+    pub after_edge: EdgeId,
+    /// The id of the way after the gap.
+    pub before_edge: EdgeId,
+    /// The node the route reached on the way before the gap.
+    pub from_node: NodeId,
+    /// The node the route continues from on the way after the gap.
+    pub to_node: NodeId,
+    /// The straight-line distance between the two nodes in meters.
+    pub distance: f64,
+    /// The location of `from_node`.
+    pub location: Option<Coordinate>,
+}
+
+/// The comparison of a `route=train` relation with the shortest path between its terminals.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteRelationReport {
+    /// The id of the relation.
+    pub relation_id: i64,
+    /// The name or ref of the relation.
+    pub name: Option<String>,
+    /// The node the relation starts at, `None` if none of its ways is in the graph.
+    pub start: Option<NodeId>,
+    /// The node the relation ends at, `None` if none of its ways is in the graph.
+    pub end: Option<NodeId>,
+    /// The length of the ways of the relation in the graph in meters.
+    pub relation_length: f64,
+    /// The length of the shortest path between the terminals in meters, `None` if they are
+    /// not connected.
+    pub shortest_path_length: Option<f64>,
+    /// How much longer the relation is than the shortest path in meters.
+    pub extra_length: Option<f64>,
+    /// The edges of the relation not on the shortest path.
+    pub relation_only_edges: Vec<EdgeId>,
+    /// The edges of the shortest path not in the relation.
+    pub shortest_path_only_edges: Vec<EdgeId>,
+    /// The ways of the relation without an edge in the graph.
+    pub missing_ways: Vec<EdgeId>,
+    /// The places where consecutive ways of the relation do not connect.
+    pub gaps: Vec<RelationGap>,
+}
+
+impl RouteRelationReport {
+    /// Returns `true` if all ways of the relation are in the graph and connect to each other.
+    pub fn is_continuous(&self) -> bool {
+        self.missing_ways.is_empty() && self.gaps.is_empty()
+    }
+}
+
+/// Comparison of `route=train` relations with the shortest paths of a railway graph.
+pub trait RouteRelationComparison {
+    /// Compare a relation with the shortest path between its terminals.
+    ///
+    /// The terminals are the free ends of the first and the last way of the relation. The ways
+    /// are followed in the order of the members, and every way not continuing from the end of
+    /// the previous one is reported as a gap.
+    ///
+    /// # Arguments
+    ///
+    /// * `relation` - The relation to compare.
+    ///
+    /// # Returns
+    ///
+    /// A `RouteRelationReport` with the extra length, the edges unique to either path and the
+    /// gaps of the relation.
+    fn compare_route_relation(&self, relation: &RouteRelation) -> RouteRelationReport;
+
+    /// Compare each relation with the shortest path between its terminals.
+    fn compare_route_relations(&self, relations: &[RouteRelation]) -> Vec<RouteRelationReport> {
+        relations
+            .iter()
+            .map(|relation| self.compare_route_relation(relation))
+            .collect()
+    }
+}
+
+impl RouteRelationComparison for RailwayGraph {
+    fn compare_route_relation(&self, relation: &RouteRelation) -> RouteRelationReport {
+        let (edges, missing_ways): (Vec<RailwayEdge>, Vec<EdgeId>) =
+            relation
+                .ways
+                .iter()
+                .fold((Vec::new(), Vec::new()), |(mut edges, mut missing), &id| {
+                    match self.get_edge_by_id(id) {
+                        Some(edge) => edges.push(edge),
+                        None => missing.push(id),
+                    }
+                    (edges, missing)
+                });
+
+        let mut gaps = Vec::new();
+        let mut start = None;
+        let mut current: Option<NodeId> = None;
+        for (position, edge) in edges.iter().enumerate() {
+            let entry = match current {
+                Some(node) if node == edge.source || node == edge.target => node,
+                _ => {
+                    let entry = free_end(edge, edges.get(position + 1));
+                    if let Some(node) = current {
+                        gaps.push(gap(self, &edges[position - 1], edge, node, entry));
+                    }
+                    entry
+                }
+            };
+            start.get_or_insert(entry);
+            current = Some(if entry == edge.source {
+                edge.target
+            } else {
+                edge.source
+            });
+        }
+        let end = current;
+
+        let relation_length: f64 = edges.iter().map(|edge| edge.length).sum();
+        let shortest_path = match (start, end) {
+            (Some(start), Some(end)) if start != end => self.shortest_path_edges(start, end),
+            (Some(_), Some(_)) => Some(Vec::new()),
+            _ => None,
+        };
+        let shortest_path_length = shortest_path.as_ref().map(|path| {
+            path.iter()
+                .filter_map(|&id| self.get_edge_by_id(id))
+                .map(|edge| edge.length)
+                .sum::<f64>()
+        });
+
+        let relation_ids: HashSet<EdgeId> = edges.iter().map(|edge| edge.id).collect();
+        let shortest_path = shortest_path.unwrap_or_default();
+        let shortest_ids: HashSet<EdgeId> = shortest_path.iter().copied().collect();
+        let mut seen = HashSet::new();
+        let relation_only_edges = edges
+            .iter()
+            .map(|edge| edge.id)
+            .filter(|id| !shortest_ids.contains(id) && seen.insert(*id))
+            .collect();
+        let shortest_path_only_edges = shortest_path
+            .into_iter()
+            .filter(|id| !relation_ids.contains(id))
+            .collect();
+
+        RouteRelationReport {
+            relation_id: relation.id,
+            name: relation.name().map(str::to_string),
+            start,
+            end,
+            relation_length,
+            shortest_path_length,
+            extra_length: shortest_path_length.map(|length| relation_length - length),
+            relation_only_edges,
+            shortest_path_only_edges,
+            missing_ways,
+            gaps,
+        }
+    }
+}
+
+/// Describes the gap between two consecutive ways of a relation.
+fn gap(
+    graph: &RailwayGraph,
+    after: &RailwayEdge,
+    before: &RailwayEdge,
+    from_node: NodeId,
+    to_node: NodeId,
+) -> RelationGap {
+    let location = |id: NodeId| {
+        graph
+            .physical_graph
+            .id_to_index(id)
+            .map(|&index| graph.physical_graph.graph[index].location)
+    };
+    let distance = match (location(from_node), location(to_node)) {
+        (Some(from), Some(to)) => from.distance(&to).get::<meter>(),
+        _ => f64::NAN,
+    };
+    RelationGap {
+        after_edge: after.id,
+        before_edge: before.id,
+        from_node,
+        to_node,
+        distance,
+        location: location(from_node).map(coordinate),
+    }
+}
+
+/// Returns the end of an edge the route enters it from: the end not shared with the next way,
+/// or its source if the next way does not connect either.
+fn free_end(edge: &RailwayEdge, next: Option<&RailwayEdge>) -> NodeId {
+    match next {
+        Some(next) if edge.source == next.source || edge.source == next.target => edge.target,
+        _ => edge.source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::importer::overpass_importer::from_railway_elements;
+    use crate::railway_algorithms::tests::loop_elements;
+
+    fn relation(ways: &[EdgeId]) -> RouteRelation {
+        RouteRelation {
+            id: 100,
+            tags: [("name".to_string(), "RB 1".to_string())].into(),
+            ways: ways.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_compare_route_relation() {
+        let graph = from_railway_elements(&loop_elements());
+        let length = |id| graph.get_edge_by_id(id).unwrap().length;
+
+        // The relation runs 4 - 1 - 3 - 2 around the loop instead of directly 4 - 1 - 2.
+        let report = graph.compare_route_relation(&relation(&[10, 12, 13]));
+        assert_eq!(report.name.as_deref(), Some("RB 1"));
+        assert_eq!((report.start, report.end), (Some(4), Some(2)));
+        assert!(report.is_continuous());
+        assert_relative_eq!(report.relation_length, length(10) + length(12) + length(13));
+        assert_relative_eq!(
+            report.shortest_path_length.unwrap(),
+            length(10) + length(11)
+        );
+        assert_relative_eq!(
+            report.extra_length.unwrap(),
+            length(12) + length(13) - length(11)
+        );
+        assert_eq!(report.relation_only_edges, vec![12, 13]);
+        assert_eq!(report.shortest_path_only_edges, vec![11]);
+
+        // Listed from 2 to 4 with the first way against its direction.
+        let report = graph.compare_route_relation(&relation(&[11, 10]));
+        assert_eq!((report.start, report.end), (Some(2), Some(4)));
+        assert!(report.is_continuous());
+        assert_relative_eq!(report.extra_length.unwrap(), 0.0);
+        assert!(report.relation_only_edges.is_empty());
+    }
+
+    #[test]
+    fn test_compare_route_relation_with_gaps() {
+        let graph = from_railway_elements(&loop_elements());
+
+        let report = graph.compare_route_relation(&relation(&[10, 13, 99]));
+        assert!(!report.is_continuous());
+        assert_eq!(report.missing_ways, vec![99]);
+        assert_eq!(report.gaps.len(), 1);
+        let gap = &report.gaps[0];
+        assert_eq!((gap.after_edge, gap.before_edge), (10, 13));
+        assert_eq!((gap.from_node, gap.to_node), (1, 3));
+        assert!(gap.distance > 500.0 && gap.distance < 1000.0);
+        assert_eq!(gap.location.as_ref().unwrap().lon, 8.0);
+        assert_eq!((report.start, report.end), (Some(4), Some(2)));
+
+        let report = graph.compare_route_relation(&relation(&[99]));
+        assert_eq!((report.start, report.end), (None, None));
+        assert_eq!(report.shortest_path_length, None);
+    }
+}
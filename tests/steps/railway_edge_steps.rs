@@ -6,6 +6,7 @@ use uom::si::length::meter;
 
 use crate::BddWorld;
 use approx::assert_relative_eq;
+use openrailwaymap_exporter::prelude::{TrackDirection, TrackPosition};
 use openrailwaymap_exporter::railway_algorithms::RailwayEdgeAlgos;
 
 #[given(regex = "a RailwayEdge with the following properties:")]
@@ -64,8 +65,17 @@ async fn when_call_position_on_edge(world: &mut BddWorld) {
         .direction_coord
         .expect("Direction coordinate is not set");
 
-    let position = edge.position_on_edge(current_location, distance_to_travel, direction_coord);
-    world.new_position = Some(position);
+    let offset = edge.project(current_location);
+    let direction = if edge.project(direction_coord) >= offset {
+        TrackDirection::Forward
+    } else {
+        TrackDirection::Backward
+    };
+    let position = edge.position_on_edge(
+        &TrackPosition::new(edge.id, offset, direction),
+        distance_to_travel,
+    );
+    world.new_position = Some(edge.coordinate_at(position.offset));
 }
 
 #[then(regex = r"the new position should be approximately \(([\d.]+),\s*([\d.]+)\)")]
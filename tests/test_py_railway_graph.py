@@ -12,7 +12,8 @@ def railway_graph():
                 "lat": 48.777,
                 "lon": 9.234,
                 "tags": {
-                    "railway": "station"
+                    "railway": "station",
+                    "name": "Frankfurt (Main) Hauptbahnhof"
                 },
                 "type": "node"
             },
@@ -21,7 +22,8 @@ def railway_graph():
                 "lat": 48.778,
                 "lon": 9.235,
                 "tags": {
-                    "railway": "station"
+                    "railway": "station",
+                    "name": "Frankfurt-Süd"
                 },
                 "type": "node"
             },
@@ -75,3 +77,91 @@ def test_export_svg(railway_graph):
     assert '</svg>' in svg_string
     assert '<circle' in svg_string
     assert '<path' in svg_string
+
+def test_statistics(railway_graph):
+    statistics = railway_graph.statistics()
+    assert statistics['node_count'] == 2
+    assert statistics['edge_count'] == 1
+    assert statistics['connected_components'] == 1
+    assert statistics['length_by_type']['rail'] == statistics['total_length']
+    assert statistics['diameter'] is None
+    assert railway_graph.statistics(diameter=True)['diameter'] == statistics['total_length']
+
+def test_k_shortest_paths(railway_graph):
+    routes = railway_graph.k_shortest_paths(1, 2, k=2)
+    assert len(routes) == 1
+    assert routes[0]['nodes'] == [1, 2]
+    assert routes[0]['edges'] == [3]
+    assert routes[0]['length'] > 0
+
+def test_distance_matrix(railway_graph):
+    import numpy as np
+
+    matrix = railway_graph.distance_matrix([1, 2], [2, 1, 99])
+    assert matrix.shape == (2, 3)
+    assert matrix[0, 1] == 0
+    assert matrix[0, 0] > 0
+    assert matrix[0, 0] == matrix[1, 1]
+    assert np.isnan(matrix[0, 2])
+    assert railway_graph.distance_matrix([1, 2]).shape == (2, 2)
+
+def test_search_stations(railway_graph):
+    matches = railway_graph.search_stations("Frankfurt Hbf")
+    assert len(matches) == 1
+    assert matches[0]['node_id'] == 1
+    assert matches[0]['name'] == "Frankfurt (Main) Hauptbahnhof"
+    assert [match['node_id'] for match in railway_graph.search_stations("frankf", limit=1)] == [2]
+    assert railway_graph.find_station("Frankfrt Sud") == 2
+    assert railway_graph.find_station("Berlin") is None
+
+def test_search_standalone_station():
+    importer = PyOverpassImporter()
+    railway_graph = importer.import_graph("""{
+        "elements": [
+            {
+                "id": 1,
+                "lat": 50.1109,
+                "lon": 8.6821,
+                "type": "node"
+            },
+            {
+                "id": 2,
+                "lat": 50.1073,
+                "lon": 8.6637,
+                "type": "node"
+            },
+            {
+                "id": 4,
+                "lat": 50.1075,
+                "lon": 8.6640,
+                "tags": {
+                    "railway": "station",
+                    "name": "Frankfurt-Süd"
+                },
+                "type": "node"
+            },
+            {
+                "id": 3,
+                "nodes": [1, 2],
+                "tags": {
+                    "railway": "rail"
+                },
+                "geometry": [
+                    {
+                        "lat": 50.1109,
+                        "lon": 8.6821
+                    },
+                    {
+                        "lat": 50.1073,
+                        "lon": 8.6637
+                    }
+                ],
+                "type": "way"
+            }
+        ],
+        "version": 0.6
+    }""")
+    matches = railway_graph.search_stations("Frankfurt Sud")
+    assert [match['node_id'] for match in matches] == [4]
+    assert matches[0]['track_node_id'] == 2
+    assert railway_graph.find_station("Frankfurt-Süd") == 2